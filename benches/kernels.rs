@@ -0,0 +1,40 @@
+//! Compares the scalar and vec4 inverse sqrt kernels on 16M elements.
+//!
+//! Run with `cargo bench --bench kernels`.
+
+use std::time::{Duration, Instant};
+
+use demo_wgpu_compute::{GpuContext, Kernel};
+
+const LEN: usize = 16 << 20;
+const RUNS: u32 = 5;
+
+async fn time(ctx: &GpuContext, kernel: Kernel, input: &[f32]) -> Duration {
+    // Compiles the pipeline and fills the buffer pool.
+    ctx.compute_with(kernel, input)
+        .await
+        .expect("Failed to compute");
+    let started = Instant::now();
+    for _ in 0..RUNS {
+        ctx.compute_with(kernel, input)
+            .await
+            .expect("Failed to compute");
+    }
+    started.elapsed() / RUNS
+}
+
+#[tokio::main]
+async fn main() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    println!("adapter: {}", ctx.adapter_info().name);
+    let input = (1..=LEN).map(|x| x as f32).collect::<Vec<_>>();
+
+    for (name, kernel) in [
+        ("scalar", Kernel::InverseSqrt),
+        ("vec4", Kernel::InverseSqrtVec4),
+    ] {
+        let elapsed = time(&ctx, kernel, &input).await;
+        let throughput = LEN as f64 / elapsed.as_secs_f64() / 1e6;
+        println!("{name:>6}: {elapsed:?} per call, {throughput:.1} M elements/s");
+    }
+}
@@ -1,8 +1,43 @@
-use spirv_builder::{MetadataPrintout, SpirvBuilder};
+use std::{env, fs, path::Path};
+
+use inverse_sqrt_shared::{WORKGROUP_HEIGHT, WORKGROUP_SIZE, WORKGROUP_WIDTH};
+use spirv_builder::{Capability, MetadataPrintout, ModuleResult, SpirvBuilder};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    SpirvBuilder::new("inverse_sqrt", "spirv-unknown-vulkan1.1")
-        .print_metadata(MetadataPrintout::Full)
-        .build()?;
+    // Picked up by the shader crate's build of `inverse_sqrt_shared` too.
+    println!("cargo:rerun-if-env-changed=DEMO_RSQRT_WORKGROUP_SIZE");
+    // One module per entry point, so a device that translates the SPIR-V
+    // instead of passing it through never sees the push constants of an
+    // entry point it doesn't run.
+    let mut builder = SpirvBuilder::new("inverse_sqrt", "spirv-unknown-vulkan1.1")
+        .print_metadata(MetadataPrintout::DependencyOnly)
+        .multimodule(true);
+    // The shader gates its `f64` entry point on the capability, which it
+    // sees as `target_feature = "Float64"`.
+    if env::var_os("CARGO_FEATURE_F64").is_some() {
+        builder = builder.capability(Capability::Float64);
+    }
+    let result = builder.build()?;
+    let ModuleResult::MultiModule(modules) = result.module else {
+        unreachable!("multimodule build produced a single module");
+    };
+    for (entry_point, path) in modules {
+        println!("cargo:rustc-env={entry_point}.spv={}", path.display());
+    }
+
+    // WGSL has no constants to size workgroups with, so the sources are
+    // copied with the size filled in.
+    println!("cargo:rerun-if-changed=inverse_sqrt/wgsl");
+    let wgsl_dir = Path::new(&env::var("OUT_DIR")?).join("wgsl");
+    fs::create_dir_all(&wgsl_dir)?;
+    for source in fs::read_dir("inverse_sqrt/wgsl")? {
+        let source = source?.path();
+        let wgsl = fs::read_to_string(&source)?;
+        let wgsl = wgsl
+            .replace("WORKGROUP_SIZE", &WORKGROUP_SIZE.to_string())
+            .replace("WORKGROUP_WIDTH", &WORKGROUP_WIDTH.to_string())
+            .replace("WORKGROUP_HEIGHT", &WORKGROUP_HEIGHT.to_string());
+        fs::write(wgsl_dir.join(source.file_name().unwrap()), wgsl)?;
+    }
     Ok(())
 }
@@ -0,0 +1,24 @@
+#ifndef RSQRT_GPU_H
+#define RSQRT_GPU_H
+
+#include <stddef.h>
+
+#define RSQRT_GPU_OK 0
+#define RSQRT_GPU_NO_ADAPTER -1
+#define RSQRT_GPU_DISPATCH_FAILED -2
+#define RSQRT_GPU_INVALID_ARGUMENTS -3
+#define RSQRT_GPU_NOT_INITIALIZED -4
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+int rsqrt_gpu_init(void);
+int rsqrt_gpu_shutdown(void);
+int rsqrt_gpu(const float *input, float *output, size_t len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -1,17 +1,1059 @@
 #![cfg_attr(target_arch = "spirv", no_std)]
+#![cfg_attr(target_arch = "spirv", feature(asm_experimental_arch))]
 
+use inverse_sqrt_shared::{
+    compute_shader, compute_shader_2d, Clamp, Count, Grid, Layout, Params, Refinement, Rows,
+    MAX_NEWTON_ITERATIONS, WORKGROUP_SIZE,
+};
 use spirv_std::num_traits::Float;
-use spirv_std::{glam::UVec3, spirv};
+use spirv_std::{
+    arch::{atomic_i_increment, workgroup_memory_barrier_with_group_sync},
+    float::{f16x2_to_vec2, vec2_to_f16x2},
+    glam::{UVec3, Vec2, Vec4},
+    memory::{Scope, Semantics},
+    spirv,
+};
 
-#[spirv(compute(threads(64)))]
-pub fn main_cs(
-    #[spirv(global_invocation_id)] id: UVec3,
-    #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] storage: &mut [f32],
+/// A quiet NaN, built from its bits: the GLSL that translated shaders are
+/// compiled to has no NaN literal, and leaves `0 / 0` undefined.
+const NAN_BITS: u32 = 0x7fc0_0000;
+
+/// Bits of `+inf`; every pattern above it, and every one with the sign bit
+/// set, is NaN or negative.
+const INFINITY_BITS: u32 = 0x7f80_0000;
+
+/// Bits of the smallest positive normal float. Patterns below it are
+/// subnormal.
+const MIN_POSITIVE_BITS: u32 = 0x0080_0000;
+
+/// `2^75`, from its bits. A subnormal with mantissa `m` is `m * 2^-149`,
+/// so its inverse square root is `2^75 / sqrt(2m)`.
+const SUBNORMAL_SCALE_BITS: u32 = 0x6500_0000;
+
+/// The element of invocation `id` in a dispatch of `groups` workgroups,
+/// whose rows along x continue one another when a dispatch too large for x
+/// spills into y.
+fn linear_index(id: UVec3, groups: UVec3) -> usize {
+    (id.y * groups.x * WORKGROUP_SIZE + id.x) as usize
+}
+
+/// `scale / sqrt(value)`, with the special cases every inverse sqrt kernel
+/// shares.
+fn inverse_sqrt_of(value: f32, scale: f32) -> f32 {
+    // Classified by bits, as drivers that flush subnormals to zero would
+    // do so in any float comparison.
+    let bits = value.to_bits();
+    if bits == 0 || bits > INFINITY_BITS {
+        // Negative inputs, both zeros and NaN have no real inverse square
+        // root. The host maps zeros further, per its `ZeroPolicy`.
+        f32::from_bits(NAN_BITS)
+    } else if bits == INFINITY_BITS {
+        0.
+    } else if bits < MIN_POSITIVE_BITS {
+        // The mantissa converts to a normal float exactly.
+        scale / ((2 * bits) as f32).sqrt() * f32::from_bits(SUBNORMAL_SCALE_BITS)
+    } else {
+        scale / value.sqrt()
+    }
+}
+
+/// The GLSL.std.450 `InverseSqrt` instruction, which drivers may lower to a
+/// hardware estimate less accurate than dividing by `sqrt`.
+#[cfg(target_arch = "spirv")]
+fn native_rsqrt(value: f32) -> f32 {
+    let result;
+    unsafe {
+        core::arch::asm!(
+            "%glsl = OpExtInstImport \"GLSL.std.450\"",
+            "%float = OpTypeFloat 32",
+            // 32 = InverseSqrt
+            "{result} = OpExtInst %float %glsl 32 {value}",
+            value = in(reg) value,
+            result = out(reg) result,
+        );
+    }
+    result
+}
+
+#[cfg(not(target_arch = "spirv"))]
+fn native_rsqrt(value: f32) -> f32 {
+    1. / value.sqrt()
+}
+
+/// [`inverse_sqrt_of`] with a scale of 1 and [`native_rsqrt`] in place of
+/// the division, for the same special cases.
+fn native_inverse_sqrt(index: usize, count: &Count, input: &[f32], output: &mut [f32]) {
+    if index >= count.element_count as usize {
+        return;
+    }
+    let bits = input[index].to_bits();
+    output[index] = if bits == 0 || bits > INFINITY_BITS {
+        f32::from_bits(NAN_BITS)
+    } else if bits == INFINITY_BITS {
+        0.
+    } else if bits < MIN_POSITIVE_BITS {
+        native_rsqrt((2 * bits) as f32) * f32::from_bits(SUBNORMAL_SCALE_BITS)
+    } else {
+        native_rsqrt(input[index])
+    };
+}
+
+fn scaled_inverse_sqrt(index: usize, scale: f32, count: &Count, input: &[f32], output: &mut [f32]) {
+    if index >= count.element_count as usize {
+        return;
+    }
+    output[index] = inverse_sqrt_of(input[index], scale);
+}
+
+/// `1 / sqrt(max(x, epsilon))`, leaving NaN inputs NaN, and negative ones
+/// too unless `clamp.clamp_negative` is set. `-0.0` counts as zero.
+fn clamped_inverse_sqrt(
+    index: usize,
+    clamp: &Clamp,
+    count: &Count,
+    input: &[f32],
+    output: &mut [f32],
+) {
+    if index >= count.element_count as usize {
+        return;
+    }
+    let mut value = input[index];
+    // By bits, as NaN comparisons may be optimized away.
+    let is_nan = value.to_bits() & !(1 << 31) > INFINITY_BITS;
+    if !is_nan && value < clamp.epsilon && (value >= 0. || clamp.clamp_negative != 0) {
+        value = clamp.epsilon;
+    }
+    output[index] = inverse_sqrt_of(value, 1.);
+}
+
+/// `1 / sqrt(x)` for the element in column `id.x` of row `id.y`. Rows that
+/// aren't a multiple of the workgroup width wide leave the invocations past
+/// their end idle, like the rows past the last one.
+fn matrix_inverse_sqrt(id: UVec3, grid: &Grid, count: &Count, input: &[f32], output: &mut [f32]) {
+    if id.x >= grid.width {
+        return;
+    }
+    let index = id.y as usize * grid.width as usize + id.x as usize;
+    scaled_inverse_sqrt(index, 1., count, input, output);
+}
+
+/// [`scaled_inverse_sqrt`] with a scale of 1, also counting the NaNs it
+/// writes in `nan_count`, atomically across the whole dispatch. The queue
+/// family scope covers every invocation, and unlike the device scope needs
+/// no capability beyond the memory model's.
+fn counted_inverse_sqrt(
+    index: usize,
+    count: &Count,
+    input: &[f32],
+    output: &mut [f32],
+    nan_count: &mut u32,
+) {
+    if index >= count.element_count as usize {
+        return;
+    }
+    let result = inverse_sqrt_of(input[index], 1.);
+    output[index] = result;
+    if result.to_bits() & !(1 << 31) > INFINITY_BITS {
+        unsafe {
+            atomic_i_increment::<u32, { Scope::QueueFamily as u32 }, { Semantics::NONE.bits() }>(
+                nan_count,
+            )
+        };
+    }
+}
+
+/// [`inverse_sqrt_of`] with a scale of 1 for both halves packed into the
+/// word at `index`, the first element in the low bits. Every half converts
+/// to `f32` exactly, so only packing the results rounds.
+fn inverse_sqrt_f16x2(index: usize, count: &Count, input: &[u32], output: &mut [u32]) {
+    if index >= count.element_count as usize {
+        return;
+    }
+    let value: Vec2 = f16x2_to_vec2(input[index]);
+    let result = Vec2::new(inverse_sqrt_of(value.x, 1.), inverse_sqrt_of(value.y, 1.));
+    output[index] = vec2_to_f16x2(result);
+}
+
+/// Whether `bits` are those of a positive normal float, the only inputs
+/// whose inverse square root needs no special case.
+// Not `Range::contains`, whose `Ordering` needs 8-bit integers.
+#[allow(clippy::manual_range_contains)]
+fn is_positive_normal(bits: u32) -> bool {
+    bits >= MIN_POSITIVE_BITS && bits < INFINITY_BITS
+}
+
+/// [`scaled_inverse_sqrt`] with a scale of 1 over the four elements from
+/// `4 * index`: as one vector when all of them are before the end and
+/// positive normal floats, and lane by lane otherwise.
+fn inverse_sqrt_vec4(index: usize, count: &Count, input: &[f32], output: &mut [f32]) {
+    let first = 4 * index;
+    let element_count = count.element_count as usize;
+    if first + 4 <= element_count {
+        let value = Vec4::new(
+            input[first],
+            input[first + 1],
+            input[first + 2],
+            input[first + 3],
+        );
+        if is_positive_normal(value.x.to_bits())
+            && is_positive_normal(value.y.to_bits())
+            && is_positive_normal(value.z.to_bits())
+            && is_positive_normal(value.w.to_bits())
+        {
+            let root = Vec4::new(value.x.sqrt(), value.y.sqrt(), value.z.sqrt(), value.w.sqrt());
+            let result = Vec4::ONE / root;
+            output[first] = result.x;
+            output[first + 1] = result.y;
+            output[first + 2] = result.z;
+            output[first + 3] = result.w;
+            return;
+        }
+    }
+    let mut lane = 0;
+    while lane < 4 {
+        // Checks the element count itself.
+        scaled_inverse_sqrt(first + lane, 1., count, input, output);
+        lane += 1;
+    }
+}
+
+/// [`scaled_inverse_sqrt`] with a scale of 1 for `index` and every
+/// `threads`th element after it, so that a dispatch of any size covers the
+/// whole input.
+fn grid_stride_inverse_sqrt(
+    index: usize,
+    threads: usize,
+    count: &Count,
+    input: &[f32],
+    output: &mut [f32],
+) {
+    let mut index = index;
+    while index < count.element_count as usize {
+        scaled_inverse_sqrt(index, 1., count, input, output);
+        index += threads;
+    }
+}
+
+/// The initial guess of Quake III's fast inverse square root, subtracted
+/// from half the input's bits.
+const FAST_RSQRT_MAGIC: u32 = 0x5f37_59df;
+
+/// [`scaled_inverse_sqrt`] with a scale of 1, approximated by the bit hack
+/// and `iterations` Newton steps, one of which gets within about 0.2% for
+/// normal inputs. The same classes of input map to NaN and zero;
+/// subnormals get no exact path.
+fn fast_inverse_sqrt(
+    index: usize,
+    iterations: u32,
+    count: &Count,
+    input: &[f32],
+    output: &mut [f32],
 ) {
-    let index = id.x as usize;
-    if storage[index] == 0. {
-        storage[index] = f32::NAN;
+    if index >= count.element_count as usize {
+        return;
+    }
+    // Not `u32::min`, whose `Ordering` needs 8-bit integers.
+    let iterations = if iterations < MAX_NEWTON_ITERATIONS {
+        iterations
+    } else {
+        MAX_NEWTON_ITERATIONS
+    };
+    let bits = input[index].to_bits();
+    output[index] = if bits == 0 || bits > INFINITY_BITS {
+        f32::from_bits(NAN_BITS)
+    } else if bits == INFINITY_BITS {
+        0.
     } else {
-        storage[index] = 1. / storage[index].sqrt();
+        let half = 0.5 * input[index];
+        let mut guess = f32::from_bits(FAST_RSQRT_MAGIC - (bits >> 1));
+        let mut step = 0;
+        while step < iterations {
+            guess *= 1.5 - half * guess * guess;
+            step += 1;
+        }
+        guess
+    };
+}
+
+/// `sqrt(x)`, with the same classes of input as [`scaled_inverse_sqrt`]:
+/// NaN for negative inputs and NaN, while zeros and infinity map to
+/// themselves.
+fn sqrt(index: usize, count: &Count, input: &[f32], output: &mut [f32]) {
+    if index >= count.element_count as usize {
+        return;
+    }
+    let bits = input[index].to_bits();
+    output[index] = if bits == 0 || bits == INFINITY_BITS || bits == 1 << 31 {
+        input[index]
+    } else if bits > INFINITY_BITS {
+        f32::from_bits(NAN_BITS)
+    } else if bits < MIN_POSITIVE_BITS {
+        // `sqrt(m * 2^-149) = sqrt(2m) / 2^75`.
+        ((2 * bits) as f32).sqrt() / f32::from_bits(SUBNORMAL_SCALE_BITS)
+    } else {
+        input[index].sqrt()
+    };
+}
+
+/// [`scaled_inverse_sqrt`] with a scale of 1 in double precision. Classified
+/// by comparisons, as the bits would need 64-bit integers too; subnormals
+/// are left to the driver, which may flush them to zero.
+#[cfg(target_feature = "Float64")]
+fn inverse_sqrt_f64(index: usize, count: &Count, input: &[f64], output: &mut [f64]) {
+    if index >= count.element_count as usize {
+        return;
+    }
+    let value = input[index];
+    output[index] = if value.is_nan() || value <= 0. {
+        f64::NAN
+    } else if value == f64::INFINITY {
+        0.
+    } else {
+        1. / value.sqrt()
+    };
+}
+
+/// Scales the `[x, y, z]` triple at `index` by the inverse of its length,
+/// written through unchanged if that is zero. Non-finite components make
+/// the whole triple NaN.
+fn normalize3(index: usize, count: &Count, input: &[f32], output: &mut [f32]) {
+    if index >= count.element_count as usize {
+        return;
+    }
+    let first = 3 * index;
+    let (x, y, z) = (input[first], input[first + 1], input[first + 2]);
+    let length_squared = x * x + y * y + z * z;
+    let scale = if length_squared.to_bits() == 0 {
+        1.
+    } else {
+        inverse_sqrt_of(length_squared, 1.)
+    };
+    output[first] = x * scale;
+    output[first + 1] = y * scale;
+    output[first + 2] = z * scale;
+}
+
+/// Scales the `[re, im]` pair at `index` to unit magnitude like
+/// [`normalize3`], writing zero samples through unchanged.
+fn normalize_complex(index: usize, count: &Count, input: &[f32], output: &mut [f32]) {
+    if index >= count.element_count as usize {
+        return;
+    }
+    let first = 2 * index;
+    let (re, im) = (input[first], input[first + 1]);
+    let magnitude_squared = re * re + im * im;
+    let scale = if magnitude_squared.to_bits() == 0 {
+        1.
+    } else {
+        inverse_sqrt_of(magnitude_squared, 1.)
+    };
+    output[first] = re * scale;
+    output[first + 1] = im * scale;
+}
+
+/// Invocations of [`normalize_rows_cs`]'s workgroups, which share a row.
+const ROW_LANES: usize = WORKGROUP_SIZE as usize;
+
+/// Per-lane sums of squares of a row, and the scale lane 0 finds from
+/// their total, in workgroup memory.
+pub struct RowNorm {
+    sum: [f32; ROW_LANES],
+    scale: f32,
+}
+
+/// Scales row `row` of `input` to unit length. Each lane sums the squares
+/// of every [`ROW_LANES`]th element, the lanes combine their sums pairwise,
+/// and lane 0 takes the inverse square root of the total for all of them
+/// to scale by. Zero rows are written through unchanged, like the zero
+/// samples of [`normalize_complex`].
+fn normalize_row(
+    row: usize,
+    lane: usize,
+    rows: &Rows,
+    input: &[f32],
+    output: &mut [f32],
+    norm: &mut RowNorm,
+) {
+    let dim = rows.dim as usize;
+    let first = row * dim;
+    let mut sum = 0.;
+    let mut element = lane;
+    while element < dim {
+        let value = input[first + element];
+        sum += value * value;
+        element += ROW_LANES;
+    }
+    norm.sum[lane] = sum;
+
+    let mut stride = ROW_LANES / 2;
+    while stride > 0 {
+        unsafe { workgroup_memory_barrier_with_group_sync() };
+        if lane < stride {
+            norm.sum[lane] += norm.sum[lane + stride];
+        }
+        stride /= 2;
+    }
+    if lane == 0 {
+        let sum = norm.sum[0];
+        norm.scale = if sum.to_bits() == 0 {
+            1.
+        } else {
+            inverse_sqrt_of(sum, 1.)
+        };
+    }
+    unsafe { workgroup_memory_barrier_with_group_sync() };
+
+    let scale = norm.scale;
+    let mut element = lane;
+    while element < dim {
+        output[first + element] = input[first + element] * scale;
+        element += ROW_LANES;
+    }
+}
+
+/// `1 / sqrt(x / 255)` for each of the four bytes packed little-endian into
+/// an input word, written to four consecutive outputs. Zero bytes map to
+/// NaN like [`scaled_inverse_sqrt`].
+fn unorm8_inverse_sqrt(index: usize, count: &Count, input: &[u32], output: &mut [f32]) {
+    if index >= count.element_count as usize {
+        return;
+    }
+    let word = input[index];
+    let mut byte = 0;
+    while byte < 4 {
+        let value = ((word >> (8 * byte)) & 0xff) as f32 / 255.;
+        output[4 * index + byte as usize] = inverse_sqrt_of(value, 1.);
+        byte += 1;
+    }
+}
+
+/// Invocations of [`reduce_cs`]'s workgroups, which each reduce four
+/// elements.
+const REDUCE_LANES: usize = WORKGROUP_SIZE as usize;
+
+/// Per-lane accumulators of a reduction, in workgroup memory.
+pub struct Partials {
+    minimum: [f32; REDUCE_LANES],
+    maximum: [f32; REDUCE_LANES],
+    sum: [f32; REDUCE_LANES],
+    nan_count: [f32; REDUCE_LANES],
+}
+
+/// Folds the four elements from `4 * index` into lane `lane` of
+/// `partials`, then combines the lanes pairwise until lane 0 holds the
+/// workgroup's minimum, maximum and sum of the non-NaN elements, and how
+/// many were NaN, which it writes to the four floats at `4 * group`.
+/// Invocations past the end contribute nothing, but still take part in
+/// every barrier.
+fn reduce(
+    index: usize,
+    lane: usize,
+    group: usize,
+    count: &Count,
+    input: &[f32],
+    output: &mut [f32],
+    partials: &mut Partials,
+) {
+    let mut minimum = f32::from_bits(INFINITY_BITS);
+    let mut maximum = -f32::from_bits(INFINITY_BITS);
+    let mut sum = 0.;
+    let mut nan_count = 0.;
+    let mut element = 4 * index;
+    while element < 4 * index + 4 && element < count.element_count as usize {
+        let value = input[element];
+        // By bits, as NaN comparisons may be optimized away.
+        if value.to_bits() & !(1 << 31) > INFINITY_BITS {
+            nan_count += 1.;
+        } else {
+            if value < minimum {
+                minimum = value;
+            }
+            if value > maximum {
+                maximum = value;
+            }
+            sum += value;
+        }
+        element += 1;
+    }
+    partials.minimum[lane] = minimum;
+    partials.maximum[lane] = maximum;
+    partials.sum[lane] = sum;
+    partials.nan_count[lane] = nan_count;
+
+    let mut stride = REDUCE_LANES / 2;
+    while stride > 0 {
+        unsafe { workgroup_memory_barrier_with_group_sync() };
+        if lane < stride {
+            let other = lane + stride;
+            if partials.minimum[other] < partials.minimum[lane] {
+                partials.minimum[lane] = partials.minimum[other];
+            }
+            if partials.maximum[other] > partials.maximum[lane] {
+                partials.maximum[lane] = partials.maximum[other];
+            }
+            partials.sum[lane] += partials.sum[other];
+            partials.nan_count[lane] += partials.nan_count[other];
+        }
+        stride /= 2;
+    }
+
+    if lane == 0 {
+        output[4 * group] = partials.minimum[0];
+        output[4 * group + 1] = partials.maximum[0];
+        output[4 * group + 2] = partials.sum[0];
+        output[4 * group + 3] = partials.nan_count[0];
+    }
+}
+
+/// Per-lane largest relative error of a comparison and the element it was
+/// found at, in workgroup memory.
+pub struct Mismatches {
+    error: [f32; REDUCE_LANES],
+    index: [u32; REDUCE_LANES],
+}
+
+/// How far `value` is from `reference`, relative to it: zero if they are
+/// equal or both NaN, and infinite if only one is NaN or the quotient is.
+fn relative_error(value: f32, reference: f32) -> f32 {
+    // By bits, as NaN comparisons may be optimized away.
+    let is_nan = |x: f32| x.to_bits() & !(1 << 31) > INFINITY_BITS;
+    if value == reference || (is_nan(value) && is_nan(reference)) {
+        return 0.;
+    }
+    let error = (value - reference).abs() / reference.abs();
+    if is_nan(value) || is_nan(reference) || is_nan(error) {
+        return f32::from_bits(INFINITY_BITS);
+    }
+    error
+}
+
+/// Compares the four results from `4 * index` against the reference
+/// following the `count` results in `input`, then combines the lanes of
+/// `mismatches` like [`reduce`] until lane 0 holds the workgroup's largest
+/// relative error and the first element it was found at, which it writes to
+/// the two words at `2 * group`: the error's bits, and the index, or
+/// `u32::MAX` if every result matched.
+fn compare(
+    index: usize,
+    lane: usize,
+    group: usize,
+    count: &Count,
+    input: &[f32],
+    output: &mut [u32],
+    mismatches: &mut Mismatches,
+) {
+    let len = count.element_count as usize;
+    let mut error = 0.;
+    let mut found = u32::MAX;
+    let mut element = 4 * index;
+    while element < 4 * index + 4 && element < len {
+        let element_error = relative_error(input[element], input[len + element]);
+        if element_error > error {
+            error = element_error;
+            found = element as u32;
+        }
+        element += 1;
+    }
+    mismatches.error[lane] = error;
+    mismatches.index[lane] = found;
+
+    let mut stride = REDUCE_LANES / 2;
+    while stride > 0 {
+        unsafe { workgroup_memory_barrier_with_group_sync() };
+        if lane < stride {
+            // Higher lanes hold later elements, so ties keep the first.
+            let other = lane + stride;
+            if mismatches.error[other] > mismatches.error[lane] {
+                mismatches.error[lane] = mismatches.error[other];
+                mismatches.index[lane] = mismatches.index[other];
+            }
+        }
+        stride /= 2;
+    }
+
+    if lane == 0 {
+        output[2 * group] = mismatches.error[0].to_bits();
+        output[2 * group + 1] = mismatches.index[0];
+    }
+}
+
+/// Writes the index of each invocation within its workgroup, for the host
+/// to check that it agrees on [`WORKGROUP_SIZE`].
+fn workgroup_probe(index: usize, lane: u32, count: &Count, output: &mut [u32]) {
+    if index >= count.element_count as usize {
+        return;
+    }
+    output[index] = lane;
+}
+
+/// Steps after which a Collatz sequence is given up on, as 0 never reaches
+/// 1. Every start below 10 000 takes fewer than 300.
+const MAX_COLLATZ_STEPS: u32 = 1000;
+
+/// How many steps the Collatz sequence from each element takes to reach 1,
+/// or [`MAX_COLLATZ_STEPS`] if it doesn't within as many, or would
+/// overflow `u32` first.
+fn collatz(index: usize, count: &Count, input: &[u32], output: &mut [u32]) {
+    if index >= count.element_count as usize {
+        return;
+    }
+    let mut n = input[index];
+    let mut steps = 0;
+    while n != 1 && steps < MAX_COLLATZ_STEPS {
+        if n % 2 == 0 {
+            n /= 2;
+        } else if n >= 0x5555_5555 {
+            // `3n + 1` would wrap.
+            steps = MAX_COLLATZ_STEPS;
+        } else {
+            n = 3 * n + 1;
+        }
+        steps += 1;
+    }
+    output[index] = if steps < MAX_COLLATZ_STEPS {
+        steps
+    } else {
+        MAX_COLLATZ_STEPS
+    };
+}
+
+/// `2^74`, from its bits, which with [`SUBNORMAL_SCALE_BITS`] makes the
+/// `2^149` the reciprocal of a subnormal's mantissa is scaled by.
+const RECIPROCAL_SCALE_BITS: u32 = 0x6480_0000;
+
+/// `1 / x`, with both zeros and NaN mapped to NaN like
+/// [`scaled_inverse_sqrt`], and subnormals taken apart by bits so that
+/// drivers flushing them don't make them zeros.
+fn reciprocal(index: usize, count: &Count, input: &[f32], output: &mut [f32]) {
+    if index >= count.element_count as usize {
+        return;
+    }
+    let bits = input[index].to_bits();
+    let magnitude = bits & !(1 << 31);
+    output[index] = if magnitude == 0 || magnitude > INFINITY_BITS {
+        f32::from_bits(NAN_BITS)
+    } else if magnitude < MIN_POSITIVE_BITS {
+        // `1 / (m * 2^-149) = 2^75 / m * 2^74`, which overflows to
+        // infinity for all but the largest subnormals.
+        let result = f32::from_bits(SUBNORMAL_SCALE_BITS) / magnitude as f32
+            * f32::from_bits(RECIPROCAL_SCALE_BITS);
+        f32::from_bits(result.to_bits() | (bits & 1 << 31))
+    } else {
+        1. / input[index]
+    };
+}
+
+/// `1 / (x * x)`, with both zeros and NaN mapped to NaN like
+/// [`reciprocal`]. Squaring the reciprocal rather than the input keeps the
+/// square of a small input from underflowing, so results too large for an
+/// `f32` come out as `+inf`, those of subnormal inputs included.
+fn inverse_square(index: usize, count: &Count, input: &[f32], output: &mut [f32]) {
+    if index >= count.element_count as usize {
+        return;
+    }
+    let magnitude = input[index].to_bits() & !(1 << 31);
+    output[index] = if magnitude == 0 || magnitude > INFINITY_BITS {
+        f32::from_bits(NAN_BITS)
+    } else if magnitude < MIN_POSITIVE_BITS {
+        f32::from_bits(INFINITY_BITS)
+    } else {
+        let reciprocal = 1. / input[index];
+        reciprocal * reciprocal
+    };
+}
+
+/// Applies inverse sqrt to the elements `layout` selects and copies the
+/// others through unchanged.
+fn strided_inverse_sqrt(
+    index: usize,
+    layout: &Layout,
+    count: &Count,
+    input: &[f32],
+    output: &mut [f32],
+) {
+    if index >= count.element_count as usize {
+        return;
+    }
+    if index % layout.stride as usize == layout.offset as usize {
+        scaled_inverse_sqrt(index, 1., count, input, output);
+    } else {
+        output[index] = input[index];
+    }
+}
+
+compute_shader! {
+    pub fn main_cs(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(num_workgroups)] groups: UVec3,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        scaled_inverse_sqrt(linear_index(id, groups), 1., count, input, output);
+    }
+}
+
+compute_shader! {
+    pub fn main_cs_nan_count(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 4)] nan_count: &mut u32,
+    ) {
+        counted_inverse_sqrt(id.x as usize, count, input, output, nan_count);
+    }
+}
+
+compute_shader! {
+    pub fn main_cs_vec4(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        inverse_sqrt_vec4(id.x as usize, count, input, output);
+    }
+}
+
+compute_shader! {
+    pub fn normalize3_cs(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        normalize3(id.x as usize, count, input, output);
+    }
+}
+
+compute_shader! {
+    pub fn normalize_complex_cs(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        normalize_complex(id.x as usize, count, input, output);
+    }
+}
+
+compute_shader! {
+    pub fn normalize_rows_cs(
+        #[spirv(local_invocation_id)] local: UVec3,
+        #[spirv(workgroup_id)] group: UVec3,
+        #[spirv(push_constant)] rows: &Rows,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(workgroup)] norm: &mut RowNorm,
+    ) {
+        normalize_row(group.x as usize, local.x as usize, rows, input, output, norm);
+    }
+}
+
+compute_shader! {
+    pub fn normalize_rows_cs_uniform(
+        #[spirv(local_invocation_id)] local: UVec3,
+        #[spirv(workgroup_id)] group: UVec3,
+        #[spirv(uniform, descriptor_set = 0, binding = 2)] rows: &Rows,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(workgroup)] norm: &mut RowNorm,
+    ) {
+        normalize_row(group.x as usize, local.x as usize, rows, input, output, norm);
+    }
+}
+
+compute_shader! {
+    pub fn rsqrt_unorm8_cs(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[u32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        unorm8_inverse_sqrt(id.x as usize, count, input, output);
+    }
+}
+
+compute_shader! {
+    pub fn reduce_cs(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(local_invocation_id)] local: UVec3,
+        #[spirv(workgroup_id)] group: UVec3,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+        #[spirv(workgroup)] partials: &mut Partials,
+    ) {
+        reduce(
+            id.x as usize,
+            local.x as usize,
+            group.x as usize,
+            count,
+            input,
+            output,
+            partials,
+        );
+    }
+}
+
+compute_shader! {
+    pub fn compare_cs(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(local_invocation_id)] local: UVec3,
+        #[spirv(workgroup_id)] group: UVec3,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [u32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+        #[spirv(workgroup)] mismatches: &mut Mismatches,
+    ) {
+        compare(
+            id.x as usize,
+            local.x as usize,
+            group.x as usize,
+            count,
+            input,
+            output,
+            mismatches,
+        );
+    }
+}
+
+compute_shader! {
+    pub fn workgroup_probe_cs(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(local_invocation_id)] local: UVec3,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [u32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        workgroup_probe(id.x as usize, local.x, count, output);
+    }
+}
+
+compute_shader! {
+    pub fn collatz_cs(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(num_workgroups)] groups: UVec3,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[u32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [u32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        collatz(linear_index(id, groups), count, input, output);
+    }
+}
+
+compute_shader! {
+    pub fn main_cs_f16(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[u32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [u32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        inverse_sqrt_f16x2(id.x as usize, count, input, output);
+    }
+}
+
+compute_shader! {
+    #[cfg(target_feature = "Float64")]
+    pub fn main_cs_f64(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f64],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f64],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        inverse_sqrt_f64(id.x as usize, count, input, output);
+    }
+}
+
+compute_shader! {
+    pub fn main_cs_grid_stride(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(num_workgroups)] workgroups: UVec3,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        let threads = (workgroups.x * WORKGROUP_SIZE) as usize;
+        grid_stride_inverse_sqrt(id.x as usize, threads, count, input, output);
+    }
+}
+
+compute_shader! {
+    pub fn sqrt_cs(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(num_workgroups)] groups: UVec3,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        sqrt(linear_index(id, groups), count, input, output);
+    }
+}
+
+compute_shader! {
+    pub fn reciprocal_cs(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(num_workgroups)] groups: UVec3,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        reciprocal(linear_index(id, groups), count, input, output);
+    }
+}
+
+compute_shader! {
+    pub fn inv_square_cs(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(num_workgroups)] groups: UVec3,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        inverse_square(linear_index(id, groups), count, input, output);
+    }
+}
+
+compute_shader! {
+    pub fn fast_rsqrt_cs(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(num_workgroups)] groups: UVec3,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        fast_inverse_sqrt(linear_index(id, groups), 1, count, input, output);
+    }
+}
+
+compute_shader! {
+    pub fn rsqrt_fast_native_cs(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(num_workgroups)] groups: UVec3,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        native_inverse_sqrt(linear_index(id, groups), count, input, output);
+    }
+}
+
+compute_shader! {
+    pub fn rsqrt_newton_cs(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(push_constant)] refinement: &Refinement,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        fast_inverse_sqrt(id.x as usize, refinement.iterations, count, input, output);
+    }
+}
+
+compute_shader! {
+    pub fn rsqrt_newton_cs_uniform(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(uniform, descriptor_set = 0, binding = 2)] refinement: &Refinement,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        fast_inverse_sqrt(id.x as usize, refinement.iterations, count, input, output);
+    }
+}
+
+compute_shader! {
+    pub fn rsqrt_clamped_cs(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(push_constant)] clamp: &Clamp,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        clamped_inverse_sqrt(id.x as usize, clamp, count, input, output);
+    }
+}
+
+compute_shader! {
+    pub fn rsqrt_clamped_cs_uniform(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(uniform, descriptor_set = 0, binding = 2)] clamp: &Clamp,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        clamped_inverse_sqrt(id.x as usize, clamp, count, input, output);
+    }
+}
+
+compute_shader! {
+    pub fn main_cs_scaled(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(push_constant)] params: &Params,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        scaled_inverse_sqrt(id.x as usize, params.scale, count, input, output);
+    }
+}
+
+compute_shader! {
+    pub fn main_cs_scaled_uniform(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(uniform, descriptor_set = 0, binding = 2)] params: &Params,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        scaled_inverse_sqrt(id.x as usize, params.scale, count, input, output);
+    }
+}
+
+compute_shader_2d! {
+    pub fn main_cs_2d(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(push_constant)] grid: &Grid,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        matrix_inverse_sqrt(id, grid, count, input, output);
+    }
+}
+
+compute_shader_2d! {
+    pub fn main_cs_2d_uniform(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(uniform, descriptor_set = 0, binding = 2)] grid: &Grid,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        matrix_inverse_sqrt(id, grid, count, input, output);
+    }
+}
+
+compute_shader! {
+    pub fn main_cs_strided(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(push_constant)] layout: &Layout,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        strided_inverse_sqrt(id.x as usize, layout, count, input, output);
+    }
+}
+
+compute_shader! {
+    pub fn main_cs_strided_uniform(
+        #[spirv(global_invocation_id)] id: UVec3,
+        #[spirv(uniform, descriptor_set = 0, binding = 2)] layout: &Layout,
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 0)] input: &[f32],
+        #[spirv(storage_buffer, descriptor_set = 0, binding = 1)] output: &mut [f32],
+        #[spirv(uniform, descriptor_set = 0, binding = 3)] count: &Count,
+    ) {
+        strided_inverse_sqrt(id.x as usize, layout, count, input, output);
     }
 }
@@ -0,0 +1,8 @@
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn collatz_cs(
+    [[builtin(global_invocation_id)]] id: vec3<u32>,
+    [[builtin(num_workgroups)]] groups: vec3<u32>,
+) {
+    collatz(linear_index(id, groups));
+}
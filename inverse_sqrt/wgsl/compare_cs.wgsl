@@ -0,0 +1,9 @@
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn compare_cs(
+    [[builtin(global_invocation_id)]] id: vec3<u32>,
+    [[builtin(local_invocation_id)]] local: vec3<u32>,
+    [[builtin(workgroup_id)]] group: vec3<u32>,
+) {
+    compare(id.x, local.x, group.x);
+}
@@ -0,0 +1,8 @@
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn fast_rsqrt_cs(
+    [[builtin(global_invocation_id)]] id: vec3<u32>,
+    [[builtin(num_workgroups)]] groups: vec3<u32>,
+) {
+    fast_inverse_sqrt(linear_index(id, groups), 1u);
+}
@@ -0,0 +1,8 @@
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn inv_square_cs(
+    [[builtin(global_invocation_id)]] id: vec3<u32>,
+    [[builtin(num_workgroups)]] groups: vec3<u32>,
+) {
+    inverse_square(linear_index(id, groups));
+}
@@ -0,0 +1,447 @@
+// WGSL version of `src/lib.rs`, for devices without SPIR-V passthrough.
+// Each entry point is appended in its own module, with the same name and
+// bindings as its Rust counterpart; keep the two in sync. `build.rs`
+// replaces `WORKGROUP_SIZE`, `WORKGROUP_WIDTH` and `WORKGROUP_HEIGHT` with
+// the shader crate's values.
+
+struct Values {
+    data: [[stride(4)]] array<f32>;
+};
+
+[[group(0), binding(0)]]
+var<storage, read> input: Values;
+
+[[group(0), binding(1)]]
+var<storage, read_write> output: Values;
+
+// The same buffers as words, for kernels over packed halves or integers.
+struct Words {
+    data: [[stride(4)]] array<u32>;
+};
+
+[[group(0), binding(0)]]
+var<storage, read> input_words: Words;
+
+[[group(0), binding(1)]]
+var<storage, read_write> output_words: Words;
+
+struct Params {
+    scale: f32;
+};
+
+struct Count {
+    element_count: u32;
+};
+
+[[group(0), binding(3)]]
+var<uniform> count: Count;
+
+struct Refinement {
+    iterations: u32;
+};
+
+struct Layout {
+    offset: u32;
+    stride: u32;
+};
+
+struct Clamp {
+    epsilon: f32;
+    clamp_negative: u32;
+};
+
+struct Grid {
+    width: u32;
+};
+
+struct Rows {
+    dim: u32;
+};
+
+fn linear_index(id: vec3<u32>, groups: vec3<u32>) -> u32 {
+    return id.y * groups.x * u32(WORKGROUP_SIZE) + id.x;
+}
+
+fn inverse_sqrt_of(value: f32, scale: f32) -> f32 {
+    let bits = bitcast<u32>(value);
+    if (bits == 0u || bits > 0x7f800000u) {
+        // A quiet NaN from its bits, as GLSL has no NaN literal.
+        return bitcast<f32>(0x7fc00000u);
+    } else if (bits == 0x7f800000u) {
+        return 0.0;
+    } else if (bits < 0x00800000u) {
+        // Subnormal: 2^75 / sqrt(2 * mantissa).
+        return scale / sqrt(f32(2u * bits)) * bitcast<f32>(0x65000000u);
+    }
+    return scale / sqrt(value);
+}
+
+fn native_inverse_sqrt(index: u32) {
+    if (index >= count.element_count) {
+        return;
+    }
+    let value = input.data[index];
+    let bits = bitcast<u32>(value);
+    var result: f32;
+    if (bits == 0u || bits > 0x7f800000u) {
+        result = bitcast<f32>(0x7fc00000u);
+    } else if (bits == 0x7f800000u) {
+        result = 0.0;
+    } else if (bits < 0x00800000u) {
+        result = inverseSqrt(f32(2u * bits)) * bitcast<f32>(0x65000000u);
+    } else {
+        result = inverseSqrt(value);
+    }
+    output.data[index] = result;
+}
+
+fn scaled_inverse_sqrt(index: u32, scale: f32) {
+    if (index >= count.element_count) {
+        return;
+    }
+    output.data[index] = inverse_sqrt_of(input.data[index], scale);
+}
+
+fn clamped_inverse_sqrt(index: u32, epsilon: f32, clamp_negative: u32) {
+    if (index >= count.element_count) {
+        return;
+    }
+    var value = input.data[index];
+    let is_nan = (bitcast<u32>(value) & 0x7fffffffu) > 0x7f800000u;
+    if (!is_nan && value < epsilon && (value >= 0.0 || clamp_negative != 0u)) {
+        value = epsilon;
+    }
+    output.data[index] = inverse_sqrt_of(value, 1.0);
+}
+
+fn matrix_inverse_sqrt(id: vec3<u32>, width: u32) {
+    if (id.x >= width) {
+        return;
+    }
+    scaled_inverse_sqrt(id.y * width + id.x, 1.0);
+}
+
+fn is_positive_normal(value: f32) -> bool {
+    let bits = bitcast<u32>(value);
+    return bits >= 0x00800000u && bits < 0x7f800000u;
+}
+
+fn inverse_sqrt_vec4(index: u32) {
+    let first = 4u * index;
+    if (first + 4u <= count.element_count) {
+        let value = vec4<f32>(
+            input.data[first],
+            input.data[first + 1u],
+            input.data[first + 2u],
+            input.data[first + 3u],
+        );
+        if (is_positive_normal(value.x) && is_positive_normal(value.y)
+            && is_positive_normal(value.z) && is_positive_normal(value.w)) {
+            let result = vec4<f32>(1.0) / sqrt(value);
+            output.data[first] = result.x;
+            output.data[first + 1u] = result.y;
+            output.data[first + 2u] = result.z;
+            output.data[first + 3u] = result.w;
+            return;
+        }
+    }
+    for (var lane = 0u; lane < 4u; lane = lane + 1u) {
+        scaled_inverse_sqrt(first + lane, 1.0);
+    }
+}
+
+fn grid_stride_inverse_sqrt(index: u32, threads: u32) {
+    for (var i = index; i < count.element_count; i = i + threads) {
+        scaled_inverse_sqrt(i, 1.0);
+    }
+}
+
+fn fast_inverse_sqrt(index: u32, iterations: u32) {
+    if (index >= count.element_count) {
+        return;
+    }
+    // Capped like `MAX_NEWTON_ITERATIONS`.
+    let iterations = min(iterations, 8u);
+    let bits = bitcast<u32>(input.data[index]);
+    if (bits == 0u || bits > 0x7f800000u) {
+        output.data[index] = bitcast<f32>(0x7fc00000u);
+    } else if (bits == 0x7f800000u) {
+        output.data[index] = 0.0;
+    } else {
+        let half = 0.5 * input.data[index];
+        var guess = bitcast<f32>(0x5f3759dfu - (bits >> 1u));
+        for (var step = 0u; step < iterations; step = step + 1u) {
+            guess = guess * (1.5 - half * guess * guess);
+        }
+        output.data[index] = guess;
+    }
+}
+
+fn sqrt_of(index: u32) {
+    if (index >= count.element_count) {
+        return;
+    }
+    let bits = bitcast<u32>(input.data[index]);
+    if (bits == 0u || bits == 0x7f800000u || bits == 0x80000000u) {
+        output.data[index] = input.data[index];
+    } else if (bits > 0x7f800000u) {
+        output.data[index] = bitcast<f32>(0x7fc00000u);
+    } else if (bits < 0x00800000u) {
+        // Subnormal: sqrt(2 * mantissa) / 2^75.
+        output.data[index] = sqrt(f32(2u * bits)) / bitcast<f32>(0x65000000u);
+    } else {
+        output.data[index] = sqrt(input.data[index]);
+    }
+}
+
+fn inverse_sqrt_f16x2(index: u32) {
+    if (index >= count.element_count) {
+        return;
+    }
+    let value = unpack2x16float(input_words.data[index]);
+    let result = vec2<f32>(inverse_sqrt_of(value.x, 1.0), inverse_sqrt_of(value.y, 1.0));
+    output_words.data[index] = pack2x16float(result);
+}
+
+fn normalize3(index: u32) {
+    if (index >= count.element_count) {
+        return;
+    }
+    let first = 3u * index;
+    let value = vec3<f32>(input.data[first], input.data[first + 1u], input.data[first + 2u]);
+    let length_squared = dot(value, value);
+    var scale = 1.0;
+    if (bitcast<u32>(length_squared) != 0u) {
+        scale = inverse_sqrt_of(length_squared, 1.0);
+    }
+    let result = value * scale;
+    output.data[first] = result.x;
+    output.data[first + 1u] = result.y;
+    output.data[first + 2u] = result.z;
+}
+
+fn normalize_complex(index: u32) {
+    if (index >= count.element_count) {
+        return;
+    }
+    let first = 2u * index;
+    let value = vec2<f32>(input.data[first], input.data[first + 1u]);
+    let magnitude_squared = dot(value, value);
+    var scale = 1.0;
+    if (bitcast<u32>(magnitude_squared) != 0u) {
+        scale = inverse_sqrt_of(magnitude_squared, 1.0);
+    }
+    let result = value * scale;
+    output.data[first] = result.x;
+    output.data[first + 1u] = result.y;
+}
+
+var<workgroup> row_sum: array<f32, WORKGROUP_SIZE>;
+var<workgroup> row_scale: f32;
+
+fn normalize_row(row: u32, lane: u32, dim: u32) {
+    let first = row * dim;
+    var sum = 0.0;
+    for (var element = lane; element < dim; element = element + u32(WORKGROUP_SIZE)) {
+        let value = input.data[first + element];
+        sum = sum + value * value;
+    }
+    row_sum[lane] = sum;
+
+    for (var stride = u32(WORKGROUP_SIZE) / 2u; stride > 0u; stride = stride / 2u) {
+        workgroupBarrier();
+        if (lane < stride) {
+            row_sum[lane] = row_sum[lane] + row_sum[lane + stride];
+        }
+    }
+    if (lane == 0u) {
+        var scale = 1.0;
+        if (bitcast<u32>(row_sum[0]) != 0u) {
+            scale = inverse_sqrt_of(row_sum[0], 1.0);
+        }
+        row_scale = scale;
+    }
+    workgroupBarrier();
+
+    let scale = row_scale;
+    for (var element = lane; element < dim; element = element + u32(WORKGROUP_SIZE)) {
+        output.data[first + element] = input.data[first + element] * scale;
+    }
+}
+
+fn unorm8_inverse_sqrt(index: u32) {
+    if (index >= count.element_count) {
+        return;
+    }
+    let word = input_words.data[index];
+    for (var byte = 0u; byte < 4u; byte = byte + 1u) {
+        let value = f32((word >> (8u * byte)) & 0xffu) / 255.0;
+        output.data[4u * index + byte] = inverse_sqrt_of(value, 1.0);
+    }
+}
+
+var<workgroup> partial_minimum: array<f32, WORKGROUP_SIZE>;
+var<workgroup> partial_maximum: array<f32, WORKGROUP_SIZE>;
+var<workgroup> partial_sum: array<f32, WORKGROUP_SIZE>;
+var<workgroup> partial_nan_count: array<f32, WORKGROUP_SIZE>;
+
+fn reduce(index: u32, lane: u32, group: u32) {
+    var minimum = bitcast<f32>(0x7f800000u);
+    var maximum = -bitcast<f32>(0x7f800000u);
+    var sum = 0.0;
+    var nan_count = 0.0;
+    for (var element = 4u * index; element < 4u * index + 4u && element < count.element_count; element = element + 1u) {
+        let value = input.data[element];
+        if ((bitcast<u32>(value) & 0x7fffffffu) > 0x7f800000u) {
+            nan_count = nan_count + 1.0;
+        } else {
+            minimum = min(minimum, value);
+            maximum = max(maximum, value);
+            sum = sum + value;
+        }
+    }
+    partial_minimum[lane] = minimum;
+    partial_maximum[lane] = maximum;
+    partial_sum[lane] = sum;
+    partial_nan_count[lane] = nan_count;
+
+    for (var stride = u32(WORKGROUP_SIZE) / 2u; stride > 0u; stride = stride / 2u) {
+        workgroupBarrier();
+        if (lane < stride) {
+            let other = lane + stride;
+            partial_minimum[lane] = min(partial_minimum[lane], partial_minimum[other]);
+            partial_maximum[lane] = max(partial_maximum[lane], partial_maximum[other]);
+            partial_sum[lane] = partial_sum[lane] + partial_sum[other];
+            partial_nan_count[lane] = partial_nan_count[lane] + partial_nan_count[other];
+        }
+    }
+
+    if (lane == 0u) {
+        output.data[4u * group] = partial_minimum[0];
+        output.data[4u * group + 1u] = partial_maximum[0];
+        output.data[4u * group + 2u] = partial_sum[0];
+        output.data[4u * group + 3u] = partial_nan_count[0];
+    }
+}
+
+var<workgroup> mismatch_error: array<f32, WORKGROUP_SIZE>;
+var<workgroup> mismatch_index: array<u32, WORKGROUP_SIZE>;
+
+fn is_nan(value: f32) -> bool {
+    return (bitcast<u32>(value) & 0x7fffffffu) > 0x7f800000u;
+}
+
+fn relative_error(value: f32, reference: f32) -> f32 {
+    if (value == reference || (is_nan(value) && is_nan(reference))) {
+        return 0.0;
+    }
+    let error = abs(value - reference) / abs(reference);
+    if (is_nan(value) || is_nan(reference) || is_nan(error)) {
+        return bitcast<f32>(0x7f800000u);
+    }
+    return error;
+}
+
+fn compare(index: u32, lane: u32, group: u32) {
+    let len = count.element_count;
+    var error = 0.0;
+    var found = 0xffffffffu;
+    for (var element = 4u * index; element < 4u * index + 4u && element < len; element = element + 1u) {
+        let element_error = relative_error(input.data[element], input.data[len + element]);
+        if (element_error > error) {
+            error = element_error;
+            found = element;
+        }
+    }
+    mismatch_error[lane] = error;
+    mismatch_index[lane] = found;
+
+    for (var stride = u32(WORKGROUP_SIZE) / 2u; stride > 0u; stride = stride / 2u) {
+        workgroupBarrier();
+        if (lane < stride) {
+            let other = lane + stride;
+            if (mismatch_error[other] > mismatch_error[lane]) {
+                mismatch_error[lane] = mismatch_error[other];
+                mismatch_index[lane] = mismatch_index[other];
+            }
+        }
+    }
+
+    if (lane == 0u) {
+        output_words.data[2u * group] = bitcast<u32>(mismatch_error[0]);
+        output_words.data[2u * group + 1u] = mismatch_index[0];
+    }
+}
+
+fn workgroup_probe(index: u32, lane: u32) {
+    if (index >= count.element_count) {
+        return;
+    }
+    output_words.data[index] = lane;
+}
+
+fn collatz(index: u32) {
+    if (index >= count.element_count) {
+        return;
+    }
+    // Capped like `MAX_COLLATZ_STEPS`.
+    var n = input_words.data[index];
+    var steps = 0u;
+    loop {
+        if (n == 1u || steps >= 1000u) {
+            break;
+        }
+        if (n % 2u == 0u) {
+            n = n / 2u;
+        } else if (n >= 0x55555555u) {
+            steps = 1000u;
+        } else {
+            n = 3u * n + 1u;
+        }
+        steps = steps + 1u;
+    }
+    output_words.data[index] = min(steps, 1000u);
+}
+
+fn reciprocal(index: u32) {
+    if (index >= count.element_count) {
+        return;
+    }
+    let bits = bitcast<u32>(input.data[index]);
+    let magnitude = bits & 0x7fffffffu;
+    if (magnitude == 0u || magnitude > 0x7f800000u) {
+        output.data[index] = bitcast<f32>(0x7fc00000u);
+    } else if (magnitude < 0x00800000u) {
+        // Subnormal: 2^75 / mantissa * 2^74, with the input's sign.
+        let result = bitcast<f32>(0x65000000u) / f32(magnitude) * bitcast<f32>(0x64800000u);
+        output.data[index] = bitcast<f32>(bitcast<u32>(result) | (bits & 0x80000000u));
+    } else {
+        output.data[index] = 1.0 / input.data[index];
+    }
+}
+
+fn inverse_square(index: u32) {
+    if (index >= count.element_count) {
+        return;
+    }
+    let magnitude = bitcast<u32>(input.data[index]) & 0x7fffffffu;
+    if (magnitude == 0u || magnitude > 0x7f800000u) {
+        output.data[index] = bitcast<f32>(0x7fc00000u);
+    } else if (magnitude < 0x00800000u) {
+        output.data[index] = bitcast<f32>(0x7f800000u);
+    } else {
+        let reciprocal = 1.0 / input.data[index];
+        output.data[index] = reciprocal * reciprocal;
+    }
+}
+
+fn strided_inverse_sqrt(index: u32, offset: u32, stride: u32) {
+    if (index >= count.element_count) {
+        return;
+    }
+    if (index % stride == offset) {
+        scaled_inverse_sqrt(index, 1.0);
+    } else {
+        output.data[index] = input.data[index];
+    }
+}
@@ -0,0 +1,7 @@
+
+var<push_constant> grid: Grid;
+
+[[stage(compute), workgroup_size(WORKGROUP_WIDTH, WORKGROUP_HEIGHT)]]
+fn main_cs_2d([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    matrix_inverse_sqrt(id, grid.width);
+}
@@ -0,0 +1,8 @@
+
+[[group(0), binding(2)]]
+var<uniform> grid: Grid;
+
+[[stage(compute), workgroup_size(WORKGROUP_WIDTH, WORKGROUP_HEIGHT)]]
+fn main_cs_2d_uniform([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    matrix_inverse_sqrt(id, grid.width);
+}
@@ -0,0 +1,5 @@
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn main_cs_f16([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    inverse_sqrt_f16x2(id.x);
+}
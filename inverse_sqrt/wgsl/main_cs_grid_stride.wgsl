@@ -0,0 +1,8 @@
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn main_cs_grid_stride(
+    [[builtin(global_invocation_id)]] id: vec3<u32>,
+    [[builtin(num_workgroups)]] workgroups: vec3<u32>,
+) {
+    grid_stride_inverse_sqrt(id.x, workgroups.x * u32(WORKGROUP_SIZE));
+}
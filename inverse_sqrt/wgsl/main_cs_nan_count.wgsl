@@ -0,0 +1,20 @@
+
+struct NanCount {
+    value: atomic<u32>;
+};
+
+[[group(0), binding(4)]]
+var<storage, read_write> nan_count: NanCount;
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn main_cs_nan_count([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    let index = id.x;
+    if (index >= count.element_count) {
+        return;
+    }
+    let result = inverse_sqrt_of(input.data[index], 1.0);
+    output.data[index] = result;
+    if ((bitcast<u32>(result) & 0x7fffffffu) > 0x7f800000u) {
+        atomicAdd(&nan_count.value, 1u);
+    }
+}
@@ -0,0 +1,7 @@
+
+var<push_constant> params: Params;
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn main_cs_scaled([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    scaled_inverse_sqrt(id.x, params.scale);
+}
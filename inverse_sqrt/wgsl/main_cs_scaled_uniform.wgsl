@@ -0,0 +1,8 @@
+
+[[group(0), binding(2)]]
+var<uniform> params: Params;
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn main_cs_scaled_uniform([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    scaled_inverse_sqrt(id.x, params.scale);
+}
@@ -0,0 +1,7 @@
+
+var<push_constant> layout: Layout;
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn main_cs_strided([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    strided_inverse_sqrt(id.x, layout.offset, layout.stride);
+}
@@ -0,0 +1,8 @@
+
+[[group(0), binding(2)]]
+var<uniform> layout: Layout;
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn main_cs_strided_uniform([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    strided_inverse_sqrt(id.x, layout.offset, layout.stride);
+}
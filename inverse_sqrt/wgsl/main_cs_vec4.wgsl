@@ -0,0 +1,5 @@
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn main_cs_vec4([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    inverse_sqrt_vec4(id.x);
+}
@@ -0,0 +1,5 @@
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn normalize_complex_cs([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    normalize_complex(id.x);
+}
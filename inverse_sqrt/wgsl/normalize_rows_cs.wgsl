@@ -0,0 +1,10 @@
+
+var<push_constant> rows: Rows;
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn normalize_rows_cs(
+    [[builtin(local_invocation_id)]] local: vec3<u32>,
+    [[builtin(workgroup_id)]] group: vec3<u32>,
+) {
+    normalize_row(group.x, local.x, rows.dim);
+}
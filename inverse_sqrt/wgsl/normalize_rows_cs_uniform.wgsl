@@ -0,0 +1,11 @@
+
+[[group(0), binding(2)]]
+var<uniform> rows: Rows;
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn normalize_rows_cs_uniform(
+    [[builtin(local_invocation_id)]] local: vec3<u32>,
+    [[builtin(workgroup_id)]] group: vec3<u32>,
+) {
+    normalize_row(group.x, local.x, rows.dim);
+}
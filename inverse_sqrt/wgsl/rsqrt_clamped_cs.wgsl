@@ -0,0 +1,7 @@
+
+var<push_constant> clamp: Clamp;
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn rsqrt_clamped_cs([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    clamped_inverse_sqrt(id.x, clamp.epsilon, clamp.clamp_negative);
+}
@@ -0,0 +1,8 @@
+
+[[group(0), binding(2)]]
+var<uniform> clamp: Clamp;
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn rsqrt_clamped_cs_uniform([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    clamped_inverse_sqrt(id.x, clamp.epsilon, clamp.clamp_negative);
+}
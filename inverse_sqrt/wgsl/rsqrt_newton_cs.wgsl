@@ -0,0 +1,7 @@
+
+var<push_constant> refinement: Refinement;
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn rsqrt_newton_cs([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    fast_inverse_sqrt(id.x, refinement.iterations);
+}
@@ -0,0 +1,8 @@
+
+[[group(0), binding(2)]]
+var<uniform> refinement: Refinement;
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn rsqrt_newton_cs_uniform([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    fast_inverse_sqrt(id.x, refinement.iterations);
+}
@@ -0,0 +1,5 @@
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn rsqrt_unorm8_cs([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    unorm8_inverse_sqrt(id.x);
+}
@@ -0,0 +1,8 @@
+
+[[stage(compute), workgroup_size(WORKGROUP_SIZE)]]
+fn workgroup_probe_cs(
+    [[builtin(global_invocation_id)]] id: vec3<u32>,
+    [[builtin(local_invocation_id)]] local: vec3<u32>,
+) {
+    workgroup_probe(id.x, local.x);
+}
@@ -0,0 +1,62 @@
+use std::{env, fs, path::Path};
+
+/// Invocations per workgroup unless `DEMO_RSQRT_WORKGROUP_SIZE` says
+/// otherwise.
+const DEFAULT_WORKGROUP_SIZE: u32 = 64;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=DEMO_RSQRT_WORKGROUP_SIZE");
+    let size = match env::var("DEMO_RSQRT_WORKGROUP_SIZE") {
+        Ok(size) => size
+            .parse()
+            .unwrap_or_else(|_| panic!("DEMO_RSQRT_WORKGROUP_SIZE={size} is not a number")),
+        Err(_) => DEFAULT_WORKGROUP_SIZE,
+    };
+    // The reductions halve the workgroup until one lane is left, and 256
+    // is the most invocations wgpu's default limits allow.
+    assert!(
+        size.is_power_of_two() && (4..=256).contains(&size),
+        "DEMO_RSQRT_WORKGROUP_SIZE={size} is not a power of two from 4 to 256"
+    );
+
+    // Matrix kernels split the workgroup into a square, or one twice as
+    // wide as it is high.
+    let width = 1 << ((size.trailing_zeros() + 1) / 2);
+    let height = size / width;
+
+    // `threads` takes only a literal, so the attribute is generated along
+    // with the constant.
+    let generated = format!(
+        "/// Invocations per workgroup of every entry point, set at build time by\n\
+         /// `DEMO_RSQRT_WORKGROUP_SIZE`, {DEFAULT_WORKGROUP_SIZE} by default.\n\
+         pub const WORKGROUP_SIZE: u32 = {size};\n\
+         \n\
+         /// Declares a compute entry point of [`WORKGROUP_SIZE`] invocations.\n\
+         #[macro_export]\n\
+         macro_rules! compute_shader {{\n    \
+             ($entry_point:item) => {{\n        \
+                 #[spirv(compute(threads({size})))]\n        \
+                 $entry_point\n    \
+             }};\n\
+         }}\n\
+         \n\
+         /// Invocations along x of each workgroup of a matrix entry point, the\n\
+         /// workgroup of [`WORKGROUP_SIZE`] split into rows.\n\
+         pub const WORKGROUP_WIDTH: u32 = {width};\n\
+         \n\
+         /// Invocations along y of each workgroup of a matrix entry point.\n\
+         pub const WORKGROUP_HEIGHT: u32 = {height};\n\
+         \n\
+         /// Declares a compute entry point of [`WORKGROUP_WIDTH`] by\n\
+         /// [`WORKGROUP_HEIGHT`] invocations.\n\
+         #[macro_export]\n\
+         macro_rules! compute_shader_2d {{\n    \
+             ($entry_point:item) => {{\n        \
+                 #[spirv(compute(threads({width}, {height})))]\n        \
+                 $entry_point\n    \
+             }};\n\
+         }}\n"
+    );
+    let out_dir = env::var("OUT_DIR").unwrap();
+    fs::write(Path::new(&out_dir).join("workgroup_size.rs"), generated).unwrap();
+}
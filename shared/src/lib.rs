@@ -0,0 +1,10 @@
+//! Constants and parameter structs the shader crate and the host both
+//! build from, so that they agree on them.
+
+#![no_std]
+
+mod params;
+
+pub use params::*;
+
+include!(concat!(env!("OUT_DIR"), "/workgroup_size.rs"));
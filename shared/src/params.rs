@@ -0,0 +1,112 @@
+//! The structs the kernels take their parameters in, laid out the same for
+//! the shader and the host.
+//!
+//! Every field is a 4-byte scalar, so a struct is as large as its fields
+//! with no padding, in `repr(C)` and in the std140-like layout of a WGSL
+//! uniform alike. The assertions below keep it that way, as do the `Pod`
+//! derives on the host, where an uneven struct wouldn't compile.
+
+/// Per-dispatch parameters, passed as push constants or as a uniform buffer
+/// on devices without push constant support.
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
+#[repr(C)]
+pub struct Params {
+    pub scale: f32,
+}
+
+/// How many elements the dispatch covers, always passed as a uniform
+/// buffer. Workgroups are launched whole, so the last one runs past the end
+/// unless the count is a multiple of [`WORKGROUP_SIZE`](crate::WORKGROUP_SIZE).
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
+#[repr(C)]
+pub struct Count {
+    pub element_count: u32,
+}
+
+/// The shape of a row normalization: rows of `dim` elements, one workgroup
+/// each.
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
+#[repr(C)]
+pub struct Rows {
+    pub dim: u32,
+}
+
+/// How many Newton steps a refined dispatch takes after the bit hack's
+/// estimate, capped at [`MAX_NEWTON_ITERATIONS`].
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
+#[repr(C)]
+pub struct Refinement {
+    pub iterations: u32,
+}
+
+/// Steps past which refinement stops, bounding the shader's run time. Two
+/// already reach full `f32` precision for normal inputs.
+pub const MAX_NEWTON_ITERATIONS: u32 = 8;
+
+/// Which elements a strided dispatch applies to: `offset`, then every
+/// `stride`th one after it. `offset` is less than `stride`.
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
+#[repr(C)]
+pub struct Layout {
+    pub offset: u32,
+    pub stride: u32,
+}
+
+/// The floor a clamped dispatch raises its inputs to, and whether negative
+/// inputs are raised to it too rather than mapped to NaN.
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
+#[repr(C)]
+pub struct Clamp {
+    pub epsilon: f32,
+    pub clamp_negative: u32,
+}
+
+/// The shape of a matrix dispatch: rows of `width` elements, as many as the
+/// element count fills.
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
+#[repr(C)]
+pub struct Grid {
+    pub width: u32,
+}
+
+/// The workgroup counts of an indirect dispatch along x, y and z, as a
+/// pass that decides them writes them to the args buffer.
+#[derive(Copy, Clone)]
+#[cfg_attr(feature = "bytemuck", derive(bytemuck::Pod, bytemuck::Zeroable))]
+#[repr(C)]
+pub struct DispatchIndirect {
+    pub x: u32,
+    pub y: u32,
+    pub z: u32,
+}
+
+/// Fails the build unless each struct is as many 4-byte scalars as given,
+/// with nothing between them.
+macro_rules! assert_scalars {
+    ($($params:ty: $fields:expr),* $(,)?) => {
+        const _: () = {
+            $(
+                assert!(core::mem::size_of::<$params>() == 4 * $fields);
+                assert!(core::mem::align_of::<$params>() == 4);
+            )*
+        };
+    };
+}
+
+assert_scalars! {
+    Params: 1,
+    Count: 1,
+    Rows: 1,
+    Refinement: 1,
+    Layout: 2,
+    Clamp: 2,
+    Grid: 1,
+    DispatchIndirect: 3,
+}
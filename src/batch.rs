@@ -0,0 +1,180 @@
+use std::num::NonZeroU64;
+
+use crate::{
+    context::{align_to, count_entry, validate, DeviceState, DISPATCH_BUFFERS},
+    ComputeError, GpuContext, Kernel,
+};
+
+impl GpuContext {
+    /// Computes `1 / sqrt(x)` for each of `inputs`, in as few submissions as
+    /// the device's binding size allows.
+    ///
+    /// The inputs are packed into one storage buffer, each at an offset
+    /// aligned for dynamic bindings, and dispatched one after another from a
+    /// single bind group; the results are split back up in the same order.
+    /// Empty inputs yield empty outputs. Inputs too large for one dispatch,
+    /// or all of them on devices without dynamic storage bindings, run on
+    /// their own as through [`GpuContext::compute`]. A validation error
+    /// reports the index into the inputs as if they were concatenated.
+    pub async fn compute_many(&self, inputs: &[&[f32]]) -> Result<Vec<Vec<f32>>, ComputeError> {
+        if self.options.validate_input {
+            let mut base = 0;
+            for input in inputs {
+                if let Err(ComputeError::InvalidInput { index, value }) = validate(input) {
+                    return Err(ComputeError::InvalidInput {
+                        index: base + index,
+                        value,
+                    });
+                }
+                base += input.len();
+            }
+        }
+
+        let state = self.state();
+        let limits = state.device.limits();
+        // Each batch takes a storage, output and readback buffer of its
+        // packed size.
+        let max_size = (limits.max_storage_buffer_binding_size as u64)
+            .min(state.memory_budget() / DISPATCH_BUFFERS);
+        let max = if limits.max_dynamic_storage_buffers_per_pipeline_layout >= 2 {
+            state
+                .max_elements(&Kernel::InverseSqrt, 4)
+                .min((max_size / 4) as usize)
+        } else {
+            0
+        };
+        let alignment = limits.min_storage_buffer_offset_alignment as u64;
+
+        // Groups of inputs whose packed size fits a binding, each run as
+        // one batch.
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        let mut group_size = 0;
+        let mut outputs = vec![Vec::new(); inputs.len()];
+        for (index, input) in inputs.iter().enumerate() {
+            if input.is_empty() {
+                continue;
+            }
+            if input.len() > max {
+                outputs[index] = self.compute(input).await?;
+                continue;
+            }
+            let size = std::mem::size_of_val(*input) as u64;
+            let packed = align_to(group_size, alignment) + size;
+            match groups.last_mut() {
+                Some(group) if packed <= max_size => {
+                    group.push(index);
+                    group_size = packed;
+                }
+                _ => {
+                    groups.push(vec![index]);
+                    group_size = size;
+                }
+            }
+        }
+
+        for group in groups {
+            let batch = group.iter().map(|&index| inputs[index]).collect::<Vec<_>>();
+            let mut attempts = 0;
+            let results = loop {
+                let result = self.state().run_batch(&batch).await;
+                match self.check_readback(result) {
+                    Err(ComputeError::BufferAsync(_)) => self.recover(&mut attempts).await?,
+                    result => break result?,
+                }
+            };
+            for (index, mut output) in group.into_iter().zip(results) {
+                self.apply_zero_policy(inputs[index], &mut output);
+                outputs[index] = output;
+            }
+        }
+        Ok(outputs)
+    }
+}
+
+impl DeviceState {
+    /// Runs inverse sqrt over each of `inputs`, none of them empty or too
+    /// large for a single dispatch, in one compute pass.
+    ///
+    /// Every input gets a window as long as the longest one, starting at an
+    /// aligned offset into shared storage and output buffers, and its
+    /// element count at an offset of its own into a count buffer, so the
+    /// last workgroup of a job stops at the job's end.
+    async fn run_batch(&self, inputs: &[&[f32]]) -> Result<Vec<Vec<f32>>, ComputeError> {
+        let alignment = self.device.limits().min_storage_buffer_offset_alignment as u64;
+        let window = inputs
+            .iter()
+            .map(|input| std::mem::size_of_val(*input) as u64)
+            .max()
+            .unwrap();
+        let mut offsets = Vec::with_capacity(inputs.len());
+        let mut end = 0;
+        for input in inputs {
+            let offset = align_to(end, alignment);
+            offsets.push(offset);
+            end = offset + std::mem::size_of_val(*input) as u64;
+        }
+        let size = offsets.last().unwrap() + window;
+
+        let mut packed = vec![0f32; (size / 4) as usize];
+        for (input, &offset) in inputs.iter().zip(&offsets) {
+            packed[(offset / 4) as usize..][..input.len()].copy_from_slice(input);
+        }
+        let pipeline = self.batch_pipeline(&Kernel::InverseSqrt, 4)?;
+        let storage = self.create_storage_buffer(bytemuck::cast_slice(&packed))?;
+        let output = self.create_output_buffer(size)?;
+        let binding = |buffer| {
+            wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer,
+                offset: 0,
+                size: NonZeroU64::new(window),
+            })
+        };
+        let counts = inputs
+            .iter()
+            .map(|input| input.len() as u32)
+            .collect::<Vec<_>>();
+        let count_buffer = self.create_count_buffer(&counts)?;
+        let bind_group = self.scoped("bind group", || {
+            self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: self.options.label_for("bind group").as_deref(),
+                layout: &pipeline.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: binding(&storage),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: binding(&output),
+                    },
+                    count_entry(&count_buffer),
+                ],
+            })
+        })?;
+
+        let mut encoder = self.create_command_encoder();
+        {
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: self.options.label_for("compute pass").as_deref(),
+            });
+            cpass.set_pipeline(&pipeline.pipeline);
+            let count_stride = self.count_stride();
+            for (job, (input, &offset)) in inputs.iter().zip(&offsets).enumerate() {
+                // Packed sizes stay within a binding, so offsets fit.
+                let offset = offset as wgpu::DynamicOffset;
+                let count_offset = (job as u64 * count_stride) as wgpu::DynamicOffset;
+                cpass.set_bind_group(0, &bind_group, &[offset, offset, count_offset]);
+                let (x, y) = pipeline.dispatch_size(input.len() as u32, None);
+                cpass.dispatch(x, y, 1);
+            }
+        }
+        let results: Vec<f32> = self.read_back(encoder, &output, size).await?;
+
+        let outputs = inputs
+            .iter()
+            .zip(offsets)
+            .map(|(input, offset)| results[(offset / 4) as usize..][..input.len()].to_vec())
+            .collect();
+        Ok(outputs)
+    }
+}
@@ -0,0 +1,65 @@
+//! Rewrites the golden inverse square roots the regression test compares
+//! against, from a run on the default adapter.
+//!
+//! `cargo run --bin regen-golden [path]`, writing to
+//! `tests/golden/inverse_sqrt.txt` unless a path is given.
+
+use std::fmt::Write;
+
+use demo_wgpu_compute::ComputeOptions;
+
+const LEN: usize = 1024;
+const SEED: u32 = 0x2545_f491;
+
+/// `LEN` pseudo-random positive normal floats from `SEED`.
+fn inputs() -> Vec<f32> {
+    let mut state = SEED;
+    (0..LEN)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            // Exponents from 2^-60 to 2^67, any mantissa.
+            f32::from_bits((0x21 << 23) + state % (0x80 << 23))
+        })
+        .collect()
+}
+
+fn fail(message: impl std::fmt::Display) -> ! {
+    eprintln!("regen-golden: {message}");
+    std::process::exit(1);
+}
+
+#[tokio::main]
+async fn main() {
+    let path = std::env::args().nth(1).unwrap_or_else(|| {
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/inverse_sqrt.txt").to_owned()
+    });
+    let ctx = ComputeOptions::new()
+        .build()
+        .await
+        .unwrap_or_else(|err| fail(err));
+    let adapter = ctx.adapter_info();
+    let input = inputs();
+    let output = ctx.compute(&input).await.unwrap_or_else(|err| fail(err));
+
+    let mut golden = String::new();
+    writeln!(
+        golden,
+        "# Inverse square roots of {LEN} inputs from seed {SEED:#010x}"
+    )
+    .unwrap();
+    writeln!(golden, "# as f32 bits: input, then output.").unwrap();
+    writeln!(golden, "# Regenerate with `cargo run --bin regen-golden`.").unwrap();
+    writeln!(
+        golden,
+        "# adapter: {} ({:?})",
+        adapter.name, adapter.backend
+    )
+    .unwrap();
+    for (case, result) in input.iter().zip(output) {
+        writeln!(golden, "{:08x} {:08x}", case.to_bits(), result.to_bits()).unwrap();
+    }
+    std::fs::write(&path, golden).unwrap_or_else(|err| fail(format!("{path}: {err}")));
+    println!("wrote {LEN} golden results from {} to {path}", adapter.name);
+}
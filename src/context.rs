@@ -0,0 +1,2470 @@
+use std::{
+    collections::{
+        hash_map::{DefaultHasher, Entry},
+        HashMap, VecDeque,
+    },
+    future::Future,
+    hash::{Hash, Hasher},
+    num::NonZeroU64,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::Duration,
+};
+
+use bytemuck::Pod;
+use futures::FutureExt;
+use inverse_sqrt_shared::{Clamp, Count, Grid, Layout, Params, Refinement, Rows};
+use wgpu::{
+    util::DeviceExt, AdapterInfo, BindGroup, BindGroupLayout, BufferAsyncError, CommandEncoder,
+    ComputePipeline, Device, Limits, Queue, ShaderModule,
+};
+
+#[cfg(feature = "f16")]
+use crate::kernel::InverseSqrtF16;
+#[cfg(feature = "f64")]
+use crate::kernel::InverseSqrtF64;
+use crate::{
+    kernel::{
+        Clamped, Matrix, Normalize3, NormalizeComplex, NormalizeRows, Refined, Scaled, Strided,
+        Unorm8, WorkgroupProbe, COUNT_SIZE, MAX_PARAMS_SIZE, NAN_COUNTER_SIZE,
+    },
+    poller::Poller,
+    pool::{BufferPool, PoolStats},
+    timeout::{self, with_timeout},
+    AdapterSelector, ComputeError, ComputeOptions, GpuKernel, GpuVec, InitError, Kernel, NanPolicy,
+    Precision, Stats, ZeroPolicy, WORKGROUP_HEIGHT, WORKGROUP_SIZE, WORKGROUP_WIDTH,
+};
+
+/// Every adapter a context with the default backends could run on, in the
+/// order [`AdapterSelector::Index`] refers to.
+pub fn list_adapters() -> Vec<AdapterInfo> {
+    let backends = ComputeOptions::default().backends_or_default();
+    let instance = wgpu::Instance::new(backends);
+    instance
+        .enumerate_adapters(backends)
+        .map(|adapter| adapter.get_info())
+        .collect()
+}
+
+async fn select_adapter(
+    instance: &wgpu::Instance,
+    options: &ComputeOptions,
+    backends: wgpu::Backends,
+) -> Result<wgpu::Adapter, ComputeError> {
+    let (selector, source) = options.adapter_or_env();
+    log::info!("selecting adapter {selector:?} from {source}");
+    let position = |adapters: &[wgpu::Adapter]| match &selector {
+        AdapterSelector::Index(index) => Some(*index).filter(|&index| index < adapters.len()),
+        AdapterSelector::Name(name) => {
+            let name = name.to_lowercase();
+            adapters
+                .iter()
+                .position(|adapter| adapter.get_info().name.to_lowercase().contains(&name))
+        }
+        AdapterSelector::Auto => None,
+    };
+
+    if selector == AdapterSelector::Auto {
+        return instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: options.power_preference,
+                force_fallback_adapter: options.fallback_or_default(),
+                compatible_surface: None,
+            })
+            .await
+            .ok_or_else(|| {
+                InitError::NoAdapter {
+                    requested_backends: backends,
+                    available: instance
+                        .enumerate_adapters(backends)
+                        .map(|adapter| adapter.get_info())
+                        .collect(),
+                }
+                .into()
+            });
+    }
+
+    let mut adapters = instance.enumerate_adapters(backends).collect::<Vec<_>>();
+    match position(&adapters) {
+        Some(position) => Ok(adapters.swap_remove(position)),
+        None => Err(ComputeError::AdapterNotFound {
+            selector,
+            available: adapters
+                .iter()
+                .map(|adapter| adapter.get_info().name)
+                .collect(),
+        }),
+    }
+}
+
+/// The trace directory from the options, created and checked to be
+/// writable.
+#[cfg(feature = "trace")]
+fn trace_dir(options: &ComputeOptions) -> Result<Option<&std::path::Path>, InitError> {
+    let Some(path) = options.trace_dir.as_deref() else {
+        return Ok(None);
+    };
+    let probe = path.join(".write-test");
+    std::fs::create_dir_all(path)
+        .and_then(|()| std::fs::write(&probe, []))
+        .and_then(|()| std::fs::remove_file(&probe))
+        .map_err(|source| InitError::TraceDir {
+            path: path.to_owned(),
+            source,
+        })?;
+    Ok(Some(path))
+}
+
+#[cfg(not(feature = "trace"))]
+fn trace_dir(_options: &ComputeOptions) -> Result<Option<&std::path::Path>, InitError> {
+    Ok(None)
+}
+
+async fn init_device(
+    options: &ComputeOptions,
+) -> Result<(AdapterInfo, Device, Queue), ComputeError> {
+    let trace_dir = trace_dir(options)?;
+    let backends = options.backends_or_default();
+    let instance = wgpu::Instance::new(backends);
+    let adapter = with_timeout(
+        options.timeout,
+        "adapter request",
+        select_adapter(&instance, options, backends),
+    )
+    .await??;
+
+    let info = adapter.get_info();
+    log::info!(
+        "using {} adapter {:?} ({:?}, vendor {:#06x}, device {:#06x}) on {:?}",
+        if info.device_type == wgpu::DeviceType::Cpu {
+            "software"
+        } else {
+            "hardware"
+        },
+        info.name,
+        info.device_type,
+        info.vendor,
+        info.device,
+        info.backend
+    );
+
+    // Everything beyond the required features is optional, as software
+    // adapters often lack it: without passthrough the shader is translated,
+    // without timestamps reports use the wall clock, and the scaled kernel
+    // falls back to a uniform buffer without push constants. `compute_f64`
+    // fails without `f64` shaders.
+    let missing = options.required_features - adapter.features();
+    if !missing.is_empty() {
+        return Err(InitError::MissingFeatures {
+            adapter: info,
+            missing,
+        }
+        .into());
+    }
+    let downlevel = adapter.get_downlevel_properties();
+    if !downlevel
+        .flags
+        .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+    {
+        return Err(ComputeError::ComputeUnsupported { adapter: info });
+    }
+
+    let available = adapter.features() - options.disabled_features;
+    let mut optional = wgpu::Features::TIMESTAMP_QUERY;
+    if !options.robust {
+        optional |= wgpu::Features::SPIRV_SHADER_PASSTHROUGH;
+    }
+    if cfg!(feature = "f64") {
+        optional |= wgpu::Features::SHADER_FLOAT64;
+    }
+    let mut features = options.required_features | (available & optional);
+    let push_constants = available.contains(wgpu::Features::PUSH_CONSTANTS)
+        && adapter.limits().max_push_constant_size >= MAX_PARAMS_SIZE;
+    if push_constants {
+        features |= wgpu::Features::PUSH_CONSTANTS;
+    }
+
+    let request = |limits| {
+        let request = adapter.request_device(
+            &wgpu::DeviceDescriptor {
+                label: options.label_for("device").as_deref(),
+                features,
+                limits,
+            },
+            trace_dir,
+        );
+        with_timeout(options.timeout, "device request", request)
+    };
+    let defaults = || device_limits(options, &adapter, push_constants, false);
+    let requested = if options.use_adapter_limits {
+        match request(device_limits(options, &adapter, push_constants, true)).await? {
+            Err(err) => {
+                log::warn!("adapter limits could not be requested ({err}), using the defaults");
+                request(defaults()).await?
+            }
+            requested => requested,
+        }
+    } else {
+        request(defaults()).await?
+    };
+    let (device, queue) = match requested {
+        Ok(device) => device,
+        Err(source) => {
+            return Err(InitError::DeviceRequest {
+                adapter: info,
+                source,
+            }
+            .into())
+        }
+    };
+    Ok((info, device, queue))
+}
+
+/// The limits to request from `adapter`: everything it reports if
+/// `adapter_limits`, otherwise the defaults or, for adapters short of WebGPU
+/// compliance, the downlevel defaults. Chunking follows the device's limits,
+/// so inputs are split to fit either way.
+fn device_limits(
+    options: &ComputeOptions,
+    adapter: &wgpu::Adapter,
+    push_constants: bool,
+    adapter_limits: bool,
+) -> Limits {
+    let supported = adapter.limits();
+    let mut limits = if adapter_limits {
+        supported.clone()
+    } else if options.downlevel_limits || !adapter.get_downlevel_properties().is_webgpu_compliant()
+    {
+        log::info!("requesting downlevel limits");
+        Limits::downlevel_defaults()
+    } else {
+        Limits::default()
+    };
+    // The binding size is raised to the adapter's unless downlevel limits
+    // were asked for explicitly.
+    let max_binding_size = if options.downlevel_limits && !adapter_limits {
+        limits
+            .max_storage_buffer_binding_size
+            .min(supported.max_storage_buffer_binding_size)
+    } else {
+        supported.max_storage_buffer_binding_size
+    };
+    limits.max_storage_buffer_binding_size = options
+        .max_binding_size
+        .map_or(max_binding_size, |size| size.min(max_binding_size));
+    limits.max_compute_workgroups_per_dimension = limits
+        .max_compute_workgroups_per_dimension
+        .min(supported.max_compute_workgroups_per_dimension)
+        .min(options.max_workgroups.map_or(u32::MAX, |max| max.max(1)));
+    if push_constants {
+        limits.max_push_constant_size = limits.max_push_constant_size.max(MAX_PARAMS_SIZE);
+    }
+    limits
+}
+
+fn load_shader_module(
+    device: &Device,
+    options: &ComputeOptions,
+    kernel: &dyn GpuKernel,
+) -> ShaderModule {
+    let label = options.label_for("shader module");
+    // Robust mode skips passthrough even when the caller required it, so
+    // naga validates and bounds-checks every module.
+    if options.robust
+        || !device
+            .features()
+            .contains(wgpu::Features::SPIRV_SHADER_PASSTHROUGH)
+    {
+        let source = match kernel.wgsl() {
+            Some(wgsl) => {
+                log::info!("SPIR-V passthrough unavailable, using the WGSL shader");
+                wgpu::ShaderSource::Wgsl(wgsl.into())
+            }
+            None => {
+                log::info!("SPIR-V passthrough unavailable, translating the shader");
+                wgpu::util::make_spirv(kernel.spirv())
+            }
+        };
+        return device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+            label: label.as_deref(),
+            source,
+        });
+    }
+
+    let spirv = std::borrow::Cow::Owned(wgpu::util::make_spirv_raw(kernel.spirv()).into_owned());
+    let shader_binary = wgpu::ShaderModuleDescriptorSpirV {
+        label: label.as_deref(),
+        source: spirv,
+    };
+    unsafe { device.create_shader_module_spirv(&shader_binary) }
+}
+
+/// How a kernel receives its per-dispatch parameters, if it takes any.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum ParamsLayout {
+    None,
+    /// A push constant range of this many bytes.
+    PushConstants(u32),
+    /// A uniform buffer of this many bytes at binding 2.
+    Uniform(u64),
+}
+
+/// A compute pipeline together with the layout of its bindings.
+pub(crate) struct Pipeline {
+    pub(crate) bind_group_layout: BindGroupLayout,
+    pub(crate) pipeline: ComputePipeline,
+    /// Elements a workgroup covers.
+    workgroup_elements: u32,
+    /// Workgroups launched at most, for kernels that loop over the input.
+    max_workgroups: u32,
+    /// Workgroups along x past which the kernel's dispatches spill into y,
+    /// see [`GpuKernel::spills_into_y`].
+    spill_width: Option<u32>,
+    params: ParamsLayout,
+    /// See [`GpuKernel::element_count`].
+    pub(crate) element_count: bool,
+}
+
+impl Pipeline {
+    /// Workgroups to dispatch for `elements` elements. Rounds up, so a
+    /// partial workgroup covers the tail, without overflowing near
+    /// `u32::MAX`. Any elements get at least one workgroup, since a
+    /// dispatch of none would leave the output as it was.
+    pub(crate) fn workgroups(&self, elements: u32) -> u32 {
+        div_ceil(elements, self.workgroup_elements)
+            .min(self.max_workgroups)
+            .max(u32::from(elements > 0))
+    }
+
+    /// Workgroups to dispatch along x and y for `elements` elements: a row
+    /// of [`Pipeline::workgroups`], as many rows of them as it takes for a
+    /// kernel that spills into y, or for a kernel over rows of `row_len`
+    /// elements, enough to span a row along x and every row along y.
+    pub(crate) fn dispatch_size(&self, elements: u32, row_len: Option<u32>) -> (u32, u32) {
+        match row_len {
+            Some(row_len) if row_len > 0 => (
+                div_ceil(row_len, WORKGROUP_WIDTH),
+                div_ceil(div_ceil(elements, row_len), WORKGROUP_HEIGHT),
+            ),
+            _ => {
+                let workgroups = self.workgroups(elements);
+                match self.spill_width {
+                    Some(width) if workgroups > width => (width, div_ceil(workgroups, width)),
+                    _ => (workgroups, 1),
+                }
+            }
+        }
+    }
+}
+
+/// `n / d` rounded up, without overflowing near `u32::MAX`.
+fn div_ceil(n: u32, d: u32) -> u32 {
+    n / d + u32::from(n % d != 0)
+}
+
+/// Module, entry point, element size, parameters, whether the storage
+/// bindings take dynamic offsets and whether there is a NaN counter.
+type PipelineKey = (u64, String, u64, ParamsLayout, bool, bool);
+
+/// Shader modules keyed by a hash of their SPIR-V, and pipelines keyed by
+/// everything that goes into their layout.
+#[derive(Default)]
+struct Cache {
+    modules: HashMap<u64, ShaderModule>,
+    pipelines: HashMap<PipelineKey, Arc<Pipeline>>,
+}
+
+/// A device with its shader modules and pipelines cached.
+///
+/// Creating the context is the expensive part; [`GpuContext::compute`] only
+/// creates a bind group per dispatch, taking its storage, output and
+/// readback buffers from a pool that earlier calls returned theirs to, see
+/// [`ComputeOptions::buffer_pool_size`](crate::ComputeOptions::buffer_pool_size). The context is
+/// `Send + Sync`, so it can be shared between tasks behind an `Arc`.
+///
+/// If a readback fails, the device is assumed lost: the context creates a
+/// new one and retries, as configured by
+/// [`ComputeOptions::max_retries`](crate::ComputeOptions::max_retries).
+pub struct GpuContext {
+    pub(crate) options: ComputeOptions,
+    state: RwLock<Arc<DeviceState>>,
+    /// Readback buffer behind the last [`MappedResults`](crate::MappedResults),
+    /// the device it belongs to and its size, reused while it is large
+    /// enough.
+    pub(crate) mapped_readback: Option<(Arc<DeviceState>, wgpu::Buffer, wgpu::BufferAddress)>,
+    /// Buffers left over from earlier dispatches, for later ones to reuse.
+    pool: Mutex<BufferPool>,
+    buffer_allocations: AtomicUsize,
+    pub(crate) staging_allocations: AtomicUsize,
+    warmed: AtomicBool,
+    injected_faults: AtomicU32,
+}
+
+/// Storage, output and readback buffers of `capacity` bytes, shared by the chunks of
+/// a dispatch and kept for later calls, tied to the device they were
+/// created on.
+pub(crate) struct DispatchBuffers {
+    pub(crate) state: Arc<DeviceState>,
+    storage: wgpu::Buffer,
+    output: wgpu::Buffer,
+    readback: wgpu::Buffer,
+    pub(crate) capacity: wgpu::BufferAddress,
+}
+
+/// Workgroups a grid-stride kernel is dispatched with, or the device's
+/// limit if that is lower: enough to keep a large GPU busy.
+const GRID_STRIDE_WORKGROUPS: u32 = 1024;
+
+/// The smallest buffers kept for reuse, so runs of small calls share one
+/// allocation.
+const MIN_BUFFER_SIZE: wgpu::BufferAddress = 64 << 10;
+
+/// `offset` rounded up to a multiple of `alignment`.
+pub(crate) fn align_to(offset: u64, alignment: u64) -> u64 {
+    (offset + alignment - 1) / alignment * alignment
+}
+
+/// `size` rounded up to what copies, queue writes and mappings need.
+/// Readbacks of odd sizes copy and map this much and truncate on the host.
+pub(crate) fn copy_size(size: wgpu::BufferAddress) -> wgpu::BufferAddress {
+    align_to(size, wgpu::COPY_BUFFER_ALIGNMENT)
+}
+
+/// Size from which one-shot uploads write into a buffer mapped at creation
+/// instead of going through `queue.write_buffer`. Kept well under what a
+/// single dispatch covers at the default limits.
+const MAPPED_UPLOAD_SIZE: wgpu::BufferAddress = 4 << 20;
+
+/// The capacity to allocate for `size` bytes: the next power of two from
+/// [`MIN_BUFFER_SIZE`] up, so calls of similar sizes share buffers, but no
+/// more than `max`, what a binding can cover and the budget allows.
+fn size_class(size: wgpu::BufferAddress, max: wgpu::BufferAddress) -> wgpu::BufferAddress {
+    size.max(MIN_BUFFER_SIZE)
+        .next_power_of_two()
+        .min(max)
+        .max(size)
+}
+
+/// Storage, output and readback: the buffers a dispatch allocates per
+/// element.
+pub(crate) const DISPATCH_BUFFERS: u64 = 3;
+
+/// The device and everything created from it, replaced as a whole when the
+/// device is lost.
+pub(crate) struct DeviceState {
+    pub(crate) options: ComputeOptions,
+    pub(crate) adapter_info: AdapterInfo,
+    pub(crate) device: Arc<Device>,
+    pub(crate) queue: Queue,
+    poller: Poller,
+    /// Delay added to the next readback, see
+    /// [`GpuContext::inject_readback_delay`].
+    injected_delay: Mutex<Option<Duration>>,
+    cache: Mutex<Cache>,
+    /// Error scopes are a stack per device, so concurrent calls take turns.
+    error_scope: Mutex<()>,
+    /// Command buffers submitted through [`DeviceState::submit_commands`].
+    submissions: AtomicUsize,
+}
+
+impl DeviceState {
+    async fn new(options: &ComputeOptions) -> Result<Self, ComputeError> {
+        let (adapter_info, device, queue) = init_device(options).await?;
+        let device = Arc::new(device);
+
+        let state = DeviceState {
+            options: options.clone(),
+            adapter_info,
+            poller: Poller::new(device.clone()),
+            injected_delay: Mutex::default(),
+            device,
+            queue,
+            cache: Mutex::default(),
+            error_scope: Mutex::default(),
+            submissions: AtomicUsize::new(0),
+        };
+        state.pipeline(&Kernel::InverseSqrt, std::mem::size_of::<f32>() as u64)?;
+        Ok(state)
+    }
+}
+
+impl GpuContext {
+    /// Acquires an adapter and device and compiles the inverse sqrt pipeline.
+    pub async fn new() -> Result<Self, ComputeError> {
+        Self::with_options(ComputeOptions::default()).await
+    }
+
+    /// Options to configure a context with, finished by
+    /// [`ComputeOptions::build`].
+    ///
+    /// ```no_run
+    /// # async fn run() -> Result<(), demo_wgpu_compute::ComputeError> {
+    /// use demo_wgpu_compute::{Backends, GpuContext};
+    ///
+    /// let ctx = GpuContext::builder().backends(Backends::GL).build().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn builder() -> ComputeOptions {
+        ComputeOptions::default()
+    }
+
+    /// Like [`GpuContext::new`], but every dispatch on this context uses
+    /// `options`.
+    pub async fn with_options(options: ComputeOptions) -> Result<Self, ComputeError> {
+        let state = DeviceState::new(&options).await?;
+
+        Ok(GpuContext {
+            pool: Mutex::new(BufferPool::new(options.buffer_pool_size)),
+            options,
+            state: RwLock::new(Arc::new(state)),
+            mapped_readback: None,
+            warmed: AtomicBool::new(false),
+            buffer_allocations: AtomicUsize::new(0),
+            staging_allocations: AtomicUsize::new(0),
+            injected_faults: AtomicU32::new(0),
+        })
+    }
+
+    /// Computes `1 / sqrt(x)` for every element of `input`.
+    ///
+    /// Same contract as [`crate::inverse_sqrt`], without the device setup,
+    /// at the [`ComputeOptions::precision`] of the context.
+    pub async fn compute(&self, input: &[f32]) -> Result<Vec<f32>, ComputeError> {
+        self.compute_with_precision(input, self.options.precision)
+            .await
+    }
+
+    /// [`GpuContext::compute`] at `precision` rather than the one the
+    /// context was built with.
+    pub async fn compute_with_precision(
+        &self,
+        input: &[f32],
+        precision: Precision,
+    ) -> Result<Vec<f32>, ComputeError> {
+        self.compute_with(precision.kernel(), input).await
+    }
+
+    /// Runs `kernel` over every element of `input`.
+    ///
+    /// Input validation from the options applies as for
+    /// [`GpuContext::compute`], except to [`Kernel::Reciprocal`] and
+    /// [`Kernel::InverseSquare`], for which negative inputs are fine, and
+    /// so does the zero policy to the kernels that map zero to NaN. The NaN
+    /// policy applies to every kernel over floats.
+    pub async fn compute_with(
+        &self,
+        kernel: Kernel,
+        input: &[f32],
+    ) -> Result<Vec<f32>, ComputeError> {
+        let negative_allowed = matches!(kernel, Kernel::Reciprocal | Kernel::InverseSquare);
+        if self.options.validate_input && !negative_allowed {
+            validate(input)?;
+        }
+
+        let mut output = self.run_compute_shader(input, &kernel).await?;
+
+        match kernel {
+            // Sqrt maps zeros to themselves, and Collatz isn't over floats.
+            Kernel::Sqrt | Kernel::Collatz => {}
+            // Both zeros square to `+0`.
+            Kernel::InverseSquare => {
+                if let Some(replacement) = self.options.zero_policy.replace(0.) {
+                    for (result, &case) in output.iter_mut().zip(input) {
+                        if case == 0. {
+                            *result = replacement;
+                        }
+                    }
+                }
+            }
+            _ => self.apply_zero_policy(input, &mut output),
+        }
+        if kernel != Kernel::Collatz {
+            self.apply_nan_policy(&mut output)?;
+        }
+        Ok(output)
+    }
+
+    /// Computes `1 / sqrt(x)` for every element of `input` in double
+    /// precision.
+    ///
+    /// Needs `Features::SHADER_FLOAT64`, which is requested where the adapter
+    /// has it, and fails with [`ComputeError::F64Unsupported`] otherwise.
+    /// The zero policy applies; input validation, which checks `f32`s,
+    /// does not.
+    #[cfg(feature = "f64")]
+    pub async fn compute_f64(&self, input: &[f64]) -> Result<Vec<f64>, ComputeError> {
+        let state = self.state();
+        if !state
+            .device
+            .features()
+            .contains(wgpu::Features::SHADER_FLOAT64)
+        {
+            return Err(ComputeError::F64Unsupported {
+                adapter: state.adapter_info.clone(),
+            });
+        }
+
+        let mut output = self.run_compute_shader(input, &InverseSqrtF64).await?;
+
+        let zero_policy = self.options.zero_policy;
+        if zero_policy != ZeroPolicy::Nan {
+            for (result, &case) in output.iter_mut().zip(input) {
+                if case == 0. {
+                    *result = zero_policy.replace(case as f32).unwrap().into();
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    /// Computes `1 / sqrt(x)` for every element of `input` in half
+    /// precision.
+    ///
+    /// The shader reads the halves two to a 32-bit word, so an odd-length
+    /// input is padded by one, and computes in `f32` before rounding the
+    /// results back. The zero policy applies; input validation does not.
+    #[cfg(feature = "f16")]
+    pub async fn compute_f16(&self, input: &[half::f16]) -> Result<Vec<half::f16>, ComputeError> {
+        let words = input
+            .chunks(2)
+            .map(|pair| {
+                let high = pair.get(1).map_or(0, |high| high.to_bits());
+                u32::from(pair[0].to_bits()) | u32::from(high) << 16
+            })
+            .collect::<Vec<_>>();
+
+        let results = self.run_compute_shader(&words, &InverseSqrtF16).await?;
+
+        let mut output = results
+            .into_iter()
+            .flat_map(|word| [word as u16, (word >> 16) as u16])
+            .map(half::f16::from_bits)
+            .take(input.len())
+            .collect::<Vec<_>>();
+        check_result_len(input.len(), output.len())?;
+        let zero_policy = self.options.zero_policy;
+        if zero_policy != ZeroPolicy::Nan {
+            for (result, &case) in output.iter_mut().zip(input) {
+                if case == half::f16::ZERO {
+                    let replacement = zero_policy.replace(case.to_f32()).unwrap();
+                    *result = half::f16::from_f32(replacement);
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    /// Scales every `[x, y, z]` vector of `input` to unit length, with one
+    /// inverse square root per vector.
+    ///
+    /// Zero vectors are written through unchanged, or made NaN with
+    /// [`ComputeOptions::nan_zero_vectors`]. Vectors with non-finite
+    /// components come out NaN.
+    pub async fn normalize_vec3(&self, input: &[[f32; 3]]) -> Result<Vec<[f32; 3]>, ComputeError> {
+        let mut output = self.run_compute_shader(input, &Normalize3).await?;
+
+        if self.options.nan_zero_vectors {
+            for (result, [x, y, z]) in output.iter_mut().zip(input) {
+                if x * x + y * y + z * z == 0. {
+                    *result = [f32::NAN; 3];
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    /// [`GpuContext::normalize_vec3`] over vectors packed into a flat
+    /// slice, which fails with [`ComputeError::Misaligned`] unless its
+    /// length is a multiple of 3.
+    pub async fn normalize_vec3_flat(&self, input: &[f32]) -> Result<Vec<f32>, ComputeError> {
+        if input.len() % 3 != 0 {
+            return Err(ComputeError::Misaligned {
+                len: std::mem::size_of_val(input),
+                element_size: std::mem::size_of::<[f32; 3]>() as u64,
+            });
+        }
+        let output = self.normalize_vec3(bytemuck::cast_slice(input)).await?;
+        Ok(output.concat())
+    }
+
+    /// Scales every sample of interleaved complex `input`,
+    /// `[re0, im0, re1, im1, ...]`, to unit magnitude, with one inverse
+    /// square root per sample.
+    ///
+    /// Zero samples are written through unchanged, and samples with
+    /// non-finite parts come out NaN. An odd-length `input` fails with
+    /// [`ComputeError::Misaligned`].
+    pub async fn normalize_complex(&self, input: &[f32]) -> Result<Vec<f32>, ComputeError> {
+        if input.len() % 2 != 0 {
+            return Err(ComputeError::Misaligned {
+                len: std::mem::size_of_val(input),
+                element_size: std::mem::size_of::<[f32; 2]>() as u64,
+            });
+        }
+        let samples: &[[f32; 2]] = bytemuck::cast_slice(input);
+        let output = self.run_compute_shader(samples, &NormalizeComplex).await?;
+        Ok(output.concat())
+    }
+
+    /// Scales every row of `dim` consecutive elements of `input` to unit
+    /// L2 norm, one workgroup per row, so rows of any length take one
+    /// inverse square root each.
+    ///
+    /// Zero rows are written through unchanged, a NaN makes its whole row
+    /// NaN, and rows whose squares sum past `f32::MAX` come out zero, with
+    /// NaN for any infinite elements. A `dim` of zero, or one that doesn't divide
+    /// `input.len()`, fails with [`ComputeError::Misaligned`]. The rows run
+    /// in a single dispatch, so more of them than one can launch fail with
+    /// [`ComputeError::TooLarge`].
+    pub async fn normalize_rows(
+        &self,
+        input: &[f32],
+        dim: usize,
+    ) -> Result<Vec<f32>, ComputeError> {
+        if dim == 0 || input.len() % dim != 0 {
+            return Err(ComputeError::Misaligned {
+                len: std::mem::size_of_val(input),
+                element_size: (dim * std::mem::size_of::<f32>()) as u64,
+            });
+        }
+        if input.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let state = self.state();
+        let kernel = NormalizeRows {
+            push_constants: state.push_constants(),
+        };
+        let rows = input.len() / dim;
+        let limits = state.device.limits();
+        let max_rows = (limits.max_compute_workgroups_per_dimension as usize)
+            .min(limits.max_storage_buffer_binding_size as usize / (4 * dim));
+        if rows > max_rows {
+            return Err(ComputeError::TooLarge {
+                requested: input.len(),
+                max: max_rows * dim,
+            });
+        }
+        let size = std::mem::size_of_val(input) as wgpu::BufferAddress;
+        state.check_budget(DISPATCH_BUFFERS, size)?;
+        let pipeline = state.pipeline_with_params(&kernel, 4, kernel.params_layout())?;
+
+        let storage_buffer = state.create_storage_buffer(bytemuck::cast_slice(input))?;
+        let output_buffer = state.create_output_buffer(size)?;
+        let mut encoder = state.create_command_encoder();
+        state.encode_kernel_with_params(
+            &mut encoder,
+            &pipeline,
+            storage_buffer.as_entire_buffer_binding(),
+            output_buffer.as_entire_buffer_binding(),
+            rows as u32 * WORKGROUP_SIZE,
+            None,
+            None,
+            bytemuck::bytes_of(&Rows { dim: dim as u32 }),
+        )?;
+        state.read_back::<f32>(encoder, &output_buffer, size).await
+    }
+
+    /// Computes `1 / sqrt(x / 255)` for every byte of `input`, decoding
+    /// normalized bytes on the GPU so that a quarter of the `f32` size is
+    /// uploaded.
+    ///
+    /// The bytes go up four to a 32-bit word, the last one padded, in a
+    /// single dispatch, so inputs too large for one fail with
+    /// [`ComputeError::TooLarge`]. Zero bytes follow the zero policy.
+    pub async fn compute_unorm8(&self, input: &[u8]) -> Result<Vec<f32>, ComputeError> {
+        if input.is_empty() {
+            return Ok(Vec::new());
+        }
+        let words = input
+            .chunks(4)
+            .map(|bytes| {
+                let mut word = [0; 4];
+                word[..bytes.len()].copy_from_slice(bytes);
+                u32::from_le_bytes(word)
+            })
+            .collect::<Vec<_>>();
+
+        let state = self.state();
+        // Each word is written back as four floats, so the output binding
+        // is the one that limits.
+        let output_element_size = std::mem::size_of::<[f32; 4]>() as u64;
+        state.check_fits(&Unorm8, output_element_size, words.len())?;
+        let size = words.len() as u64 * output_element_size;
+        state.check_budget(DISPATCH_BUFFERS, size)?;
+        let pipeline = state.pipeline(&Unorm8, 4)?;
+
+        let storage_buffer = state.create_storage_buffer(bytemuck::cast_slice(&words))?;
+        let output_buffer = state.create_output_buffer(size)?;
+        let mut encoder = state.create_command_encoder();
+        state.encode_kernel(
+            &mut encoder,
+            &pipeline,
+            &storage_buffer,
+            &output_buffer,
+            words.len() as u32,
+        )?;
+        let mut output = state
+            .read_back::<f32>(encoder, &output_buffer, size)
+            .await?;
+        output.truncate(input.len());
+
+        if let Some(replacement) = self.options.zero_policy.replace(0.) {
+            for (result, &byte) in output.iter_mut().zip(input) {
+                if byte == 0 {
+                    *result = replacement;
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    /// The largest relative error of [`Kernel::FastInverseSqrt`] against
+    /// [`Kernel::InverseSqrt`] over `input`, running both. Elements the
+    /// precise kernel maps to NaN or zero are skipped, so an input with
+    /// none left gives `0.0`.
+    pub async fn fast_inverse_sqrt_error(&self, input: &[f32]) -> Result<f32, ComputeError> {
+        let fast = self.run_compute_shader(input, &Kernel::FastInverseSqrt);
+        let precise = self.run_compute_shader(input, &Kernel::InverseSqrt);
+        let (fast, precise) = futures::future::try_join(fast, precise).await?;
+
+        let error = fast
+            .iter()
+            .zip(&precise)
+            .filter(|(_, precise)| precise.is_normal())
+            .map(|(fast, precise)| ((fast - precise) / precise).abs())
+            .fold(0., f32::max);
+        Ok(error)
+    }
+
+    /// Computes `scale / sqrt(x)` for every element of `input`.
+    ///
+    /// The scale is applied by the shader, through push constants where
+    /// the device supports them and a uniform buffer otherwise. Zeros
+    /// follow the zero policy.
+    pub async fn compute_scaled(
+        &self,
+        input: &[f32],
+        scale: f32,
+    ) -> Result<Vec<f32>, ComputeError> {
+        if self.options.validate_input {
+            validate(input)?;
+        }
+
+        let kernel = Scaled {
+            push_constants: self.state().push_constants(),
+        };
+        let mut output = Vec::with_capacity(input.len());
+        self.dispatch_chunked(
+            bytemuck::cast_slice(input),
+            4,
+            &kernel,
+            kernel.params_layout(),
+            bytemuck::bytes_of(&Params { scale }),
+            |results| output.extend_from_slice(bytemuck::cast_slice(results)),
+        )
+        .await?;
+
+        self.apply_zero_policy(input, &mut output);
+        Ok(output)
+    }
+
+    /// Computes `1 / sqrt(max(x, epsilon))` for every element of `input`,
+    /// so zeros and inputs below `epsilon` come out as the finite
+    /// `1 / sqrt(epsilon)` rather than infinities or NaNs.
+    ///
+    /// Negative inputs are clamped too, unless
+    /// [`ComputeOptions::clamp_negative`] is off, which maps them to NaN.
+    /// NaN inputs stay NaN, and an `epsilon` of zero or below clamps
+    /// nothing the plain kernel maps to a number. The epsilon reaches the
+    /// shader like [`GpuContext::compute_scaled`]'s scale. Input validation
+    /// rejects NaNs, and the negatives left unclamped; the zero policy
+    /// doesn't apply.
+    pub async fn compute_clamped(
+        &self,
+        input: &[f32],
+        epsilon: f32,
+    ) -> Result<Vec<f32>, ComputeError> {
+        if self.options.validate_input {
+            let clamp_negative = self.options.clamp_negative;
+            let invalid = input
+                .iter()
+                .position(|x| x.is_nan() || (!clamp_negative && *x < 0.));
+            if let Some(index) = invalid {
+                return Err(ComputeError::InvalidInput {
+                    index,
+                    value: input[index],
+                });
+            }
+        }
+
+        let kernel = Clamped {
+            push_constants: self.state().push_constants(),
+        };
+        let clamp = Clamp {
+            epsilon,
+            clamp_negative: u32::from(self.options.clamp_negative),
+        };
+        let mut output = Vec::with_capacity(input.len());
+        self.dispatch_chunked(
+            bytemuck::cast_slice(input),
+            4,
+            &kernel,
+            kernel.params_layout(),
+            bytemuck::bytes_of(&clamp),
+            |results| output.extend_from_slice(bytemuck::cast_slice(results)),
+        )
+        .await?;
+        Ok(output)
+    }
+
+    /// Computes `1 / sqrt(x)` for every element of `input` from the bit
+    /// hack of [`Kernel::FastInverseSqrt`] and `iterations` Newton steps,
+    /// trading accuracy for speed: none gives the raw estimate, one the
+    /// fast kernel's results, and two about full precision. The shader
+    /// stops after 8 steps, whatever `iterations` asks for.
+    ///
+    /// The iteration count reaches the shader like
+    /// [`GpuContext::compute_scaled`]'s scale. Validation and the zero
+    /// policy apply as for [`GpuContext::compute`].
+    pub async fn compute_rsqrt_refined(
+        &self,
+        input: &[f32],
+        iterations: u32,
+    ) -> Result<Vec<f32>, ComputeError> {
+        if self.options.validate_input {
+            validate(input)?;
+        }
+
+        let kernel = Refined {
+            push_constants: self.state().push_constants(),
+        };
+        let mut output = Vec::with_capacity(input.len());
+        self.dispatch_chunked(
+            bytemuck::cast_slice(input),
+            4,
+            &kernel,
+            kernel.params_layout(),
+            bytemuck::bytes_of(&Refinement { iterations }),
+            |results| output.extend_from_slice(bytemuck::cast_slice(results)),
+        )
+        .await?;
+
+        self.apply_zero_policy(input, &mut output);
+        Ok(output)
+    }
+
+    /// Computes `1 / sqrt(x)` for every element of a `width` by `height`
+    /// matrix stored row by row in `input`, dispatching workgroups of
+    /// [`WORKGROUP_WIDTH`] by [`WORKGROUP_HEIGHT`] invocations over its
+    /// columns and rows rather than over a flat array, so kernels like it
+    /// can work on 2D neighbourhoods.
+    ///
+    /// The width reaches the shader like [`GpuContext::compute_scaled`]'s
+    /// scale; validation and the zero policy apply as for
+    /// [`GpuContext::compute`]. Inputs too large for one dispatch are split
+    /// between rows. An input that isn't `width * height` elements long
+    /// fails with [`ComputeError::InvalidShape`], and a row wider than a
+    /// dispatch reaches with [`ComputeError::TooLarge`].
+    pub async fn compute_2d(
+        &self,
+        input: &[f32],
+        width: u32,
+        height: u32,
+    ) -> Result<Vec<f32>, ComputeError> {
+        if width as u64 * height as u64 != input.len() as u64 {
+            return Err(ComputeError::InvalidShape {
+                len: input.len(),
+                width,
+                height,
+            });
+        }
+        if self.options.validate_input {
+            validate(input)?;
+        }
+        if input.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let state = self.state();
+        let max_width = state.device.limits().max_compute_workgroups_per_dimension as usize
+            * WORKGROUP_WIDTH as usize;
+        if width as usize > max_width {
+            return Err(ComputeError::TooLarge {
+                requested: width as usize,
+                max: max_width,
+            });
+        }
+        let kernel = Matrix {
+            push_constants: state.push_constants(),
+            width,
+        };
+        // Whole rows per chunk, so each one starts at column 0.
+        let mut chunk_len = self.chunk_len(&kernel, 4, input.len())?;
+        if chunk_len < input.len() {
+            chunk_len -= chunk_len % width as usize;
+            if chunk_len == 0 {
+                return Err(ComputeError::TooLarge {
+                    requested: width as usize,
+                    max: state.max_elements(&kernel, 4),
+                });
+            }
+        }
+        let mut output = Vec::with_capacity(input.len());
+        self.dispatch_in_chunks(
+            bytemuck::cast_slice(input),
+            4,
+            chunk_len,
+            &kernel,
+            kernel.params_layout(),
+            bytemuck::bytes_of(&Grid { width }),
+            |results| output.extend_from_slice(bytemuck::cast_slice(results)),
+        )
+        .await?;
+
+        self.apply_zero_policy(input, &mut output);
+        Ok(output)
+    }
+
+    /// Computes `1 / sqrt(x)` for the element at `offset` and every
+    /// `stride`th one after it, e.g. the `w` of each `[x, y, z, w]` with an
+    /// offset of 3 and a stride of 4, and returns `input` with only those
+    /// elements replaced.
+    ///
+    /// The layout reaches the shader like [`GpuContext::compute_scaled`]'s
+    /// scale. Input validation and the zero policy apply to the selected
+    /// elements only. `offset` must be less than `stride`, or this fails
+    /// with [`ComputeError::InvalidLayout`].
+    pub async fn compute_strided(
+        &self,
+        input: &[f32],
+        offset: usize,
+        stride: usize,
+    ) -> Result<Vec<f32>, ComputeError> {
+        let layout = u32::try_from(offset).ok().zip(u32::try_from(stride).ok());
+        let layout = match layout {
+            Some((offset, stride)) if offset < stride => Layout { offset, stride },
+            _ => return Err(ComputeError::InvalidLayout { offset, stride }),
+        };
+        let selected = |index| index % stride == offset;
+        if self.options.validate_input {
+            let invalid = input
+                .iter()
+                .enumerate()
+                .position(|(index, x)| selected(index) && (x.is_nan() || *x < 0.));
+            if let Some(index) = invalid {
+                return Err(ComputeError::InvalidInput {
+                    index,
+                    value: input[index],
+                });
+            }
+        }
+
+        if input.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let kernel = Strided {
+            push_constants: self.state().push_constants(),
+        };
+        // Whole strides per chunk, so each one starts on the same lane.
+        let mut chunk_len = self.chunk_len(&kernel, 4, input.len())?;
+        if chunk_len < input.len() {
+            chunk_len -= chunk_len % stride;
+            if chunk_len == 0 {
+                return Err(ComputeError::TooLarge {
+                    requested: stride,
+                    max: self.state().max_elements(&kernel, 4),
+                });
+            }
+        }
+        let mut output = Vec::with_capacity(input.len());
+        self.dispatch_in_chunks(
+            bytemuck::cast_slice(input),
+            4,
+            chunk_len,
+            &kernel,
+            kernel.params_layout(),
+            bytemuck::bytes_of(&layout),
+            |results| output.extend_from_slice(bytemuck::cast_slice(results)),
+        )
+        .await?;
+
+        for (index, result) in output.iter_mut().enumerate() {
+            if selected(index) && input[index] == 0. {
+                if let Some(replacement) = self.options.zero_policy.replace(input[index]) {
+                    *result = replacement;
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    /// Uploads `input` as is, so kernels can run over it with
+    /// [`GpuVec::map`] while it stays on the GPU.
+    ///
+    /// Nothing is validated until a kernel runs. An input over the memory
+    /// budget fails with [`ComputeError::OutOfBudget`].
+    pub fn upload(&self, input: &[f32]) -> Result<GpuVec<'_>, ComputeError> {
+        let state = self.state();
+        state.check_budget(1, std::mem::size_of_val(input) as u64)?;
+        let buffer = state.create_storage_buffer(bytemuck::cast_slice(input))?;
+        Ok(GpuVec::new(state, buffer, input.len()))
+    }
+
+    /// Uploads `input` and runs inverse sqrt over it, leaving the results
+    /// on the GPU.
+    ///
+    /// Input validation applies, but the zero policy does not: zeros come
+    /// out as NaN. The results stay in one buffer, so an input too large
+    /// for a single dispatch fails with [`ComputeError::TooLarge`], and one
+    /// over the memory budget with [`ComputeError::OutOfBudget`].
+    pub fn compute_gpu(&self, input: &[f32]) -> Result<GpuVec<'_>, ComputeError> {
+        if self.options.validate_input {
+            validate(input)?;
+        }
+
+        let state = self.state();
+        state.check_fits(&Kernel::InverseSqrt, 4, input.len())?;
+        // Storage and output; nothing is read back yet.
+        state.check_budget(2, std::mem::size_of_val(input) as u64)?;
+        let buffer = state.create_storage_buffer(bytemuck::cast_slice(input))?;
+        GpuVec::new(state, buffer, input.len()).apply(Kernel::InverseSqrt)
+    }
+
+    /// Computes `1 / sqrt(x)` for every element of `input` and reduces the
+    /// results to their [`Stats`] on the GPU, without reading them back.
+    ///
+    /// Same limits as [`GpuContext::compute_gpu`]. The zero policy doesn't
+    /// apply to results left on the GPU, so zeros count as NaN.
+    pub async fn compute_stats(&self, input: &[f32]) -> Result<Stats, ComputeError> {
+        if input.is_empty() {
+            return Ok(Stats::from_partials(&[], 0));
+        }
+        self.compute_gpu(input)?.stats().await
+    }
+
+    /// Computes `1 / sqrt(x)` for every element of `data`, in place.
+    ///
+    /// The slice is uploaded as is and the mapped readback is copied
+    /// straight back into it, so no intermediate buffers are allocated on
+    /// the host.
+    pub async fn compute_into(&self, data: &mut [f32]) -> Result<(), ComputeError> {
+        if self.options.validate_input {
+            validate(data)?;
+        }
+
+        let chunk_len = self.chunk_len(&Kernel::InverseSqrt, 4, data.len())?;
+        let mut buffers = None;
+        // Empty data still goes through one dispatch, which rejects it.
+        let empty = data.is_empty().then_some(&mut [][..]);
+        for chunk in data.chunks_mut(chunk_len).chain(empty) {
+            let buffers = self
+                .dispatch(
+                    bytemuck::cast_slice(chunk),
+                    4,
+                    &Kernel::InverseSqrt,
+                    ParamsLayout::None,
+                    &[],
+                    &mut buffers,
+                )
+                .await?;
+            let size = std::mem::size_of_val(chunk) as wgpu::BufferAddress;
+            let mapped = buffers.readback.slice(..size).get_mapped_range();
+            let results: &[f32] = bytemuck::cast_slice(&mapped);
+
+            match self.options.zero_policy {
+                ZeroPolicy::Nan => chunk.copy_from_slice(results),
+                policy => {
+                    for (case, &result) in chunk.iter_mut().zip(results) {
+                        *case = match policy.replace(*case) {
+                            Some(replacement) if *case == 0. => replacement,
+                            _ => result,
+                        };
+                    }
+                }
+            }
+            drop(mapped);
+            buffers.readback.unmap();
+        }
+        self.keep_buffers(buffers);
+        self.apply_nan_policy(data)
+    }
+
+    /// Computes `1 / sqrt(x)` for every element of `input` into `out`,
+    /// which has to be just as long, or this fails with
+    /// [`ComputeError::LengthMismatch`] before anything is uploaded.
+    ///
+    /// Each mapped chunk of results is copied straight into `out`, so a
+    /// caller that keeps its results in a long-lived slice never has an
+    /// output `Vec` allocated for it. Validation and the zero and NaN
+    /// policies apply as for [`GpuContext::compute`].
+    pub async fn compute_read_into(
+        &self,
+        input: &[f32],
+        out: &mut [f32],
+    ) -> Result<(), ComputeError> {
+        if out.len() != input.len() {
+            return Err(ComputeError::LengthMismatch {
+                input: input.len(),
+                output: out.len(),
+            });
+        }
+        if self.options.validate_input {
+            validate(input)?;
+        }
+
+        let mut written = 0;
+        self.dispatch_chunked(
+            bytemuck::cast_slice(input),
+            4,
+            &Kernel::InverseSqrt,
+            ParamsLayout::None,
+            &[],
+            |results| {
+                let results: &[f32] = bytemuck::cast_slice(results);
+                out[written..written + results.len()].copy_from_slice(results);
+                written += results.len();
+            },
+        )
+        .await?;
+
+        self.apply_zero_policy(input, out);
+        self.apply_nan_policy(out)
+    }
+
+    /// Runs `kernel` over `input`, one invocation per element, and reads the
+    /// results back.
+    ///
+    /// The kernel's entry point must take a read-only storage buffer of `T`
+    /// at set 0, binding 0, and write its results to one at binding 1.
+    pub async fn run_compute_shader<T: Pod>(
+        &self,
+        input: &[T],
+        kernel: &impl GpuKernel,
+    ) -> Result<Vec<T>, ComputeError> {
+        self.run_compute_shader_bytes(bytemuck::cast_slice(input), kernel)
+            .await
+    }
+
+    /// [`GpuContext::run_compute_shader`] over the bytes of `T`s, which
+    /// need not be aligned for `T`, as they are only ever copied.
+    ///
+    /// `input.len()` must be a multiple of the size of `T`, or this fails
+    /// with [`ComputeError::Misaligned`] before anything is uploaded.
+    pub async fn run_compute_shader_bytes<T: Pod>(
+        &self,
+        input: &[u8],
+        kernel: &impl GpuKernel,
+    ) -> Result<Vec<T>, ComputeError> {
+        // wgpu rejects a zero-sized binding, so there is nothing to dispatch.
+        if input.is_empty() {
+            return Ok(Vec::new());
+        }
+        let element_size = std::mem::size_of::<T>();
+        let mut output = Vec::with_capacity(input.len() / element_size);
+        self.dispatch_chunked(
+            input,
+            element_size as u64,
+            kernel,
+            ParamsLayout::None,
+            &[],
+            |results| extend_from_bytes(&mut output, results),
+        )
+        .await?;
+        check_result_len(input.len() / element_size, output.len())?;
+        Ok(output)
+    }
+
+    /// Runs `kernel` over the raw bytes of `input`, one invocation per
+    /// 32-bit word, and returns the storage buffer's bytes.
+    ///
+    /// `input.len()` must be a non-zero multiple of 4. Anything else fails
+    /// with [`ComputeError::EmptyInput`] or [`ComputeError::Misaligned`]
+    /// before any work is submitted.
+    pub async fn run_kernel(
+        &self,
+        kernel: &impl GpuKernel,
+        input: &[u8],
+    ) -> Result<Vec<u8>, ComputeError> {
+        if input.is_empty() {
+            return Err(ComputeError::EmptyInput);
+        }
+        let mut output = Vec::with_capacity(input.len());
+        self.dispatch_chunked(input, 4, kernel, ParamsLayout::None, &[], |results| {
+            output.extend_from_slice(results)
+        })
+        .await?;
+        Ok(output)
+    }
+
+    /// The options this context was created with.
+    pub fn options(&self) -> &ComputeOptions {
+        &self.options
+    }
+
+    /// Compiles the pipelines of the built-in kernels and runs one dummy
+    /// dispatch of one workgroup.
+    ///
+    /// [`GpuContext::new`] only compiles the inverse sqrt pipeline. Many
+    /// drivers defer further work, such as the final shader compile and
+    /// memory allocation, to the first dispatch, so without a warmup that
+    /// cost lands on the first real compute. Calling it again once it has
+    /// succeeded does nothing.
+    pub async fn warmup(&self) -> Result<(), ComputeError> {
+        if self.is_warmed() {
+            return Ok(());
+        }
+
+        let state = self.state();
+        let scaled = Scaled {
+            push_constants: state.push_constants(),
+        };
+        state.pipeline_with_params(&scaled, 4, scaled.params_layout())?;
+        self.run_compute_shader(&[1f32; WORKGROUP_SIZE as usize], &Kernel::InverseSqrt)
+            .await?;
+
+        self.warmed.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Checks with a probe dispatch of two workgroups that the shaders run
+    /// [`WORKGROUP_SIZE`] invocations per workgroup, as the dispatch math
+    /// assumes.
+    ///
+    /// Both are built from `DEMO_RSQRT_WORKGROUP_SIZE`, so they only
+    /// disagree if the shaders are stale, which fails with
+    /// [`ComputeError::Validation`].
+    pub async fn check_workgroup_size(&self) -> Result<(), ComputeError> {
+        let workgroup_size = WORKGROUP_SIZE as usize;
+        let lanes = self
+            .run_compute_shader(&vec![0u32; 2 * workgroup_size], &WorkgroupProbe)
+            .await?;
+        let agree = lanes
+            .iter()
+            .enumerate()
+            .all(|(index, &lane)| lane as usize == index % workgroup_size);
+        if !agree {
+            return Err(ComputeError::Validation {
+                stage: "workgroup size probe",
+                message: format!(
+                    "the shaders don't run {WORKGROUP_SIZE} invocations per workgroup, \
+                     rebuild them"
+                ),
+            });
+        }
+        Ok(())
+    }
+
+    /// Whether [`GpuContext::warmup`] has completed on this context.
+    pub fn is_warmed(&self) -> bool {
+        self.warmed.load(Ordering::Relaxed)
+    }
+
+    /// The adapter this context's device was created on: its name, PCI
+    /// vendor and device IDs, type and backend. wgpu doesn't report the
+    /// driver version.
+    pub fn adapter_info(&self) -> AdapterInfo {
+        self.state().adapter_info.clone()
+    }
+
+    /// The limits this context's device was created with, raised to the
+    /// adapter's by
+    /// [`ComputeOptions::use_adapter_limits`](crate::ComputeOptions::use_adapter_limits).
+    pub fn limits(&self) -> Limits {
+        self.state().device.limits()
+    }
+
+    /// Makes the next `count` readbacks fail as if the device had been
+    /// lost, to exercise recovery.
+    #[doc(hidden)]
+    pub fn inject_readback_failures(&self, count: u32) {
+        self.injected_faults.store(count, Ordering::Relaxed);
+    }
+
+    /// Holds the next readback back by `delay`, as a slow or wedged driver
+    /// would, to exercise [`ComputeOptions::timeout`](crate::ComputeOptions::timeout).
+    #[doc(hidden)]
+    pub fn inject_readback_delay(&self, delay: Duration) {
+        *self.state().injected_delay.lock().unwrap() = Some(delay);
+    }
+
+    /// The current device.
+    pub(crate) fn state(&self) -> Arc<DeviceState> {
+        self.state.read().unwrap().clone()
+    }
+
+    /// The result of a readback, replaced by a failure if one was injected.
+    pub(crate) fn check_readback<T>(
+        &self,
+        result: Result<T, ComputeError>,
+    ) -> Result<T, ComputeError> {
+        let injected = self
+            .injected_faults
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+            .is_ok();
+        if injected {
+            return Err(ComputeError::BufferAsync(BufferAsyncError));
+        }
+        result
+    }
+
+    /// Replaces a lost device after a failed readback, or gives up with
+    /// [`ComputeError::DeviceLost`] once `attempts` reaches the configured
+    /// number of retries.
+    pub(crate) async fn recover(&self, attempts: &mut u32) -> Result<(), ComputeError> {
+        if *attempts >= self.options.max_retries {
+            return Err(ComputeError::DeviceLost);
+        }
+        *attempts += 1;
+        timeout::sleep(self.options.retry_backoff * *attempts).await;
+
+        let state = DeviceState::new(&self.options).await?;
+        *self.state.write().unwrap() = Arc::new(state);
+        self.warmed.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+
+    pub(crate) fn apply_zero_policy(&self, input: &[f32], output: &mut [f32]) {
+        if self.options.zero_policy == ZeroPolicy::Nan {
+            return;
+        }
+        for (result, &case) in output.iter_mut().zip(input) {
+            if case == 0. {
+                *result = self.options.zero_policy.replace(case).unwrap();
+            }
+        }
+    }
+
+    /// Applies the [`NanPolicy`] to `output`, after the zero policy.
+    pub(crate) fn apply_nan_policy(&self, output: &mut [f32]) -> Result<(), ComputeError> {
+        match self.options.nan_policy {
+            NanPolicy::Propagate => {}
+            NanPolicy::ZeroFill => {
+                for result in output.iter_mut().filter(|result| result.is_nan()) {
+                    *result = 0.;
+                }
+            }
+            NanPolicy::Error => {
+                if let Some(first_index) = output.iter().position(|result| result.is_nan()) {
+                    return Err(ComputeError::NanInOutput { first_index });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// How many elements each dispatch over `elements` should cover: all of
+    /// them if they fit the device's limits, otherwise as many as fit if
+    /// splitting is enabled.
+    pub(crate) fn chunk_len(
+        &self,
+        kernel: &dyn GpuKernel,
+        element_size: u64,
+        elements: usize,
+    ) -> Result<usize, ComputeError> {
+        let state = self.state();
+        let mut max = state.max_elements(kernel, element_size);
+        if let Some(max_chunk_len) = self.options.max_chunk_len {
+            max = max.min(max_chunk_len.max(1));
+        }
+        // A single dispatch takes one set of buffers, a chunked run two.
+        let required = DISPATCH_BUFFERS * copy_size(elements as u64 * element_size);
+        let budget = state.memory_budget();
+        if elements <= max && required <= budget {
+            Ok(elements.max(1))
+        } else if self.options.split_large_inputs {
+            let by_budget = budget / (2 * DISPATCH_BUFFERS * element_size);
+            if by_budget == 0 {
+                return Err(ComputeError::OutOfBudget {
+                    required: 2 * DISPATCH_BUFFERS * element_size,
+                    budget,
+                });
+            }
+            Ok(max.min(by_budget as usize))
+        } else if elements > max {
+            Err(ComputeError::TooLarge {
+                requested: elements,
+                max,
+            })
+        } else {
+            Err(ComputeError::OutOfBudget { required, budget })
+        }
+    }
+
+    /// Runs `kernel` over `input` in as many dispatches as the device's
+    /// limits require, passing the results of each to `read` in order.
+    ///
+    /// Two sets of buffers take turns, so the next chunk is already
+    /// submitted while the results of the current one are mapped and read.
+    async fn dispatch_chunked(
+        &self,
+        input: &[u8],
+        element_size: u64,
+        kernel: &dyn GpuKernel,
+        params_layout: ParamsLayout,
+        params: &[u8],
+        read: impl FnMut(&[u8]),
+    ) -> Result<(), ComputeError> {
+        if input.len() as u64 % element_size != 0 {
+            return Err(ComputeError::Misaligned {
+                len: input.len(),
+                element_size,
+            });
+        }
+        let elements = input.len() / element_size as usize;
+        let chunk_len = self.chunk_len(kernel, element_size, elements)?;
+        self.dispatch_in_chunks(
+            input,
+            element_size,
+            chunk_len,
+            kernel,
+            params_layout,
+            params,
+            read,
+        )
+        .await
+    }
+
+    /// Like [`GpuContext::dispatch_chunked`], with chunks of `chunk_len`
+    /// elements, which have to fit a single dispatch.
+    #[allow(clippy::too_many_arguments)]
+    async fn dispatch_in_chunks(
+        &self,
+        input: &[u8],
+        element_size: u64,
+        chunk_len: usize,
+        kernel: &dyn GpuKernel,
+        params_layout: ParamsLayout,
+        params: &[u8],
+        mut read: impl FnMut(&[u8]),
+    ) -> Result<(), ComputeError> {
+        // An empty input still goes through one dispatch, which rejects it.
+        let empty = input.is_empty().then_some(&[][..]);
+        let chunks = input
+            .chunks(chunk_len * element_size as usize)
+            .chain(empty)
+            .collect::<Vec<_>>();
+
+        // The first chunk is the largest, so the buffers it takes from the
+        // pool serve every chunk. Chunk `i` runs on `slots[i % 2]`.
+        let mut slots = [None, None];
+        let mut in_flight = VecDeque::with_capacity(2);
+        let mut submitted = 0;
+        let mut done = 0;
+        let mut read_len = 0;
+        let mut attempts = 0;
+        while done < chunks.len() {
+            while submitted < chunks.len() && submitted < done + 2 {
+                let state = self.state();
+                in_flight.push_back(self.submit(
+                    &state,
+                    chunks[submitted],
+                    element_size,
+                    kernel,
+                    params_layout,
+                    params,
+                    &mut slots[submitted % 2],
+                )?);
+                submitted += 1;
+            }
+
+            let mapped = in_flight.pop_front().unwrap().await;
+            match self.check_readback(mapped) {
+                Err(ComputeError::BufferAsync(_)) => {
+                    // Both slots may still be mapping, so neither is reused.
+                    in_flight.clear();
+                    slots = [None, None];
+                    submitted = done;
+                    self.recover(&mut attempts).await?;
+                    continue;
+                }
+                result => result?,
+            }
+            let buffers = slots[done % 2].as_ref().unwrap();
+            let size = chunks[done].len() as wgpu::BufferAddress;
+            read(&buffers.readback.slice(..copy_size(size)).get_mapped_range()[..size as usize]);
+            buffers.readback.unmap();
+            read_len += size as usize;
+            done += 1;
+        }
+        for buffers in slots {
+            self.keep_buffers(buffers);
+        }
+        let element_size = element_size as usize;
+        check_result_len(input.len() / element_size, read_len / element_size)
+    }
+
+    /// Returns `buffers` to the pool for later dispatches.
+    fn keep_buffers(&self, buffers: Option<DispatchBuffers>) {
+        if let Some(buffers) = buffers {
+            self.pool.lock().unwrap().give_back(buffers);
+        }
+    }
+
+    /// Hits and misses of the buffer pool since the context was created,
+    /// and the bytes it holds now.
+    pub fn pool_stats(&self) -> PoolStats {
+        self.pool.lock().unwrap().stats()
+    }
+
+    /// How many times storage and readback buffers have been allocated for
+    /// a dispatch, to check that they are reused.
+    #[doc(hidden)]
+    pub fn buffer_allocations(&self) -> usize {
+        self.buffer_allocations.load(Ordering::Relaxed)
+    }
+
+    /// How many command buffers have been submitted to the current device,
+    /// to check that rejected inputs never reach the GPU.
+    #[doc(hidden)]
+    pub fn submissions(&self) -> usize {
+        self.state().submissions.load(Ordering::Relaxed)
+    }
+
+    /// How many staging buffers streams have allocated, to check that they
+    /// stay within [`ComputeOptions::staging_buffers`].
+    #[doc(hidden)]
+    pub fn staging_allocations(&self) -> usize {
+        self.staging_allocations.load(Ordering::Relaxed)
+    }
+
+    /// Runs `kernel` over `input` with `params`, reusing `buffers` if they
+    /// belong to the current device, and returns them with the results
+    /// mapped at the start of the readback buffer. Recovers from a lost
+    /// device.
+    async fn dispatch<'b>(
+        &self,
+        input: &[u8],
+        element_size: u64,
+        kernel: &dyn GpuKernel,
+        params_layout: ParamsLayout,
+        params: &[u8],
+        buffers: &'b mut Option<DispatchBuffers>,
+    ) -> Result<&'b DispatchBuffers, ComputeError> {
+        let mut attempts = 0;
+        loop {
+            let state = self.state();
+            let mapped = self
+                .submit(
+                    &state,
+                    input,
+                    element_size,
+                    kernel,
+                    params_layout,
+                    params,
+                    buffers,
+                )?
+                .await;
+            match self.check_readback(mapped) {
+                Err(ComputeError::BufferAsync(_)) => {
+                    *buffers = None;
+                    self.recover(&mut attempts).await?;
+                }
+                Err(error) => {
+                    // The readback may still be mapping, so it can't be reused.
+                    *buffers = None;
+                    return Err(error);
+                }
+                Ok(()) => return Ok(buffers.as_ref().unwrap()),
+            }
+        }
+    }
+
+    /// Uploads `input` into `buffers`, swapping them for pooled or new ones
+    /// if they belong to another device or are too small, and submits `kernel` over it and a
+    /// copy of the results to the readback buffer.
+    ///
+    /// Returns a future that resolves once the results are mapped, so more
+    /// work can be submitted in the meantime.
+    #[allow(clippy::too_many_arguments)]
+    fn submit(
+        &self,
+        state: &Arc<DeviceState>,
+        input: &[u8],
+        element_size: u64,
+        kernel: &dyn GpuKernel,
+        params_layout: ParamsLayout,
+        params: &[u8],
+        buffers: &mut Option<DispatchBuffers>,
+    ) -> Result<impl Future<Output = Result<(), ComputeError>>, ComputeError> {
+        debug_assert_eq!(input.len() as u64 % element_size, 0);
+        let elements = (input.len() as u64 / element_size) as u32;
+        let size = input.len() as wgpu::BufferAddress;
+        let padded = copy_size(size);
+        let pipeline = state.pipeline_with_params(kernel, element_size, params_layout)?;
+        let reused = match buffers.take() {
+            Some(reused)
+                if Arc::ptr_eq(&reused.state, state) && reused.capacity >= padded && size > 0 =>
+            {
+                reused
+            }
+            unfit => {
+                if let Some(unfit) = unfit {
+                    if Arc::ptr_eq(&unfit.state, state) {
+                        self.keep_buffers(Some(unfit));
+                    }
+                }
+                // Empty input gets empty buffers, so wgpu still rejects
+                // the zero-sized binding.
+                let capacity = if size == 0 {
+                    0
+                } else {
+                    let max_binding_size = state.device.limits().max_storage_buffer_binding_size;
+                    // Room for the two sets a chunked run takes turns with.
+                    let per_buffer = state.memory_budget() / (2 * DISPATCH_BUFFERS);
+                    let per_buffer = per_buffer - per_buffer % wgpu::COPY_BUFFER_ALIGNMENT;
+                    size_class(padded, per_buffer.min(max_binding_size as u64))
+                };
+                let pooled = if size == 0 {
+                    None
+                } else {
+                    self.pool.lock().unwrap().check_out(state, capacity)
+                };
+                match pooled {
+                    Some(pooled) => pooled,
+                    None => {
+                        self.buffer_allocations.fetch_add(1, Ordering::Relaxed);
+                        DispatchBuffers {
+                            storage: state.create_empty_storage_buffer(capacity)?,
+                            output: state.create_output_buffer(capacity)?,
+                            readback: state.create_readback_buffer(capacity)?,
+                            state: state.clone(),
+                            capacity,
+                        }
+                    }
+                }
+            }
+        };
+        // Uploads go through the queue rather than a mapping or a copy in
+        // this encoder: wgpu runs a `write_buffer` after every submission
+        // before it and ahead of the next, so it can't overtake the dispatch
+        // or the readback copy of the chunk that last used these buffers.
+        // Their readback is mapped and unmapped before they're reused.
+        if padded == size {
+            state.queue.write_buffer(&reused.storage, 0, input);
+        } else {
+            let mut input = input.to_vec();
+            input.resize(padded as usize, 0);
+            state.queue.write_buffer(&reused.storage, 0, &input);
+        }
+
+        let mut encoder = state.create_command_encoder();
+        let binding = |buffer| wgpu::BufferBinding {
+            buffer,
+            offset: 0,
+            size: NonZeroU64::new(size),
+        };
+        state.encode_kernel_with_params(
+            &mut encoder,
+            &pipeline,
+            binding(&reused.storage),
+            binding(&reused.output),
+            elements,
+            kernel.row_len(),
+            None,
+            params,
+        )?;
+        let mapped = state.submit_read_back(encoder, &reused.output, &reused.readback, size);
+        *buffers = Some(reused);
+        Ok(mapped)
+    }
+}
+
+impl DeviceState {
+    /// The most elements of `element_size` bytes a single dispatch of
+    /// `kernel` can cover, limited by the storage buffer binding size and
+    /// the number of workgroups.
+    pub(crate) fn max_elements(&self, kernel: &dyn GpuKernel, element_size: u64) -> usize {
+        let limits = self.device.limits();
+        let by_binding = limits.max_storage_buffer_binding_size as u64 / element_size;
+        let by_workgroups = if kernel.grid_stride() {
+            u32::MAX as u64
+        } else if let Some(row_len) = kernel.row_len() {
+            limits.max_compute_workgroups_per_dimension as u64
+                * WORKGROUP_HEIGHT as u64
+                * row_len as u64
+        } else {
+            let rows = if kernel.spills_into_y() {
+                limits.max_compute_workgroups_per_dimension as u64
+            } else {
+                1
+            };
+            rows * limits.max_compute_workgroups_per_dimension as u64
+                * kernel.workgroup_size() as u64
+                * kernel.elements_per_invocation() as u64
+        };
+        by_binding.min(by_workgroups) as usize
+    }
+
+    /// Bytes of buffers a call may allocate, see
+    /// [`ComputeOptions::memory_budget`].
+    pub(crate) fn memory_budget(&self) -> u64 {
+        self.options.memory_budget.unwrap_or_else(|| {
+            let max_binding_size = self.device.limits().max_storage_buffer_binding_size;
+            2 * DISPATCH_BUFFERS * max_binding_size as u64
+        })
+    }
+
+    /// [`ComputeError::OutOfBudget`] if `buffers` buffers of `size` bytes
+    /// each don't fit the memory budget.
+    pub(crate) fn check_budget(
+        &self,
+        buffers: u64,
+        size: wgpu::BufferAddress,
+    ) -> Result<(), ComputeError> {
+        let required = buffers * copy_size(size);
+        let budget = self.memory_budget();
+        if required > budget {
+            return Err(ComputeError::OutOfBudget { required, budget });
+        }
+        Ok(())
+    }
+
+    /// [`ComputeError::TooLarge`] if `elements` don't fit a single dispatch.
+    pub(crate) fn check_fits(
+        &self,
+        kernel: &dyn GpuKernel,
+        element_size: u64,
+        elements: usize,
+    ) -> Result<(), ComputeError> {
+        let max = self.max_elements(kernel, element_size);
+        if elements > max {
+            return Err(ComputeError::TooLarge {
+                requested: elements,
+                max,
+            });
+        }
+        Ok(())
+    }
+
+    /// Runs `create` in a validation error scope, turning an error it
+    /// raises into [`ComputeError::Validation`] for `stage`.
+    pub(crate) fn scoped<T>(
+        &self,
+        stage: &'static str,
+        create: impl FnOnce() -> T,
+    ) -> Result<T, ComputeError> {
+        let _scope = self.error_scope.lock().unwrap();
+        self.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let value = create();
+        // Native error scopes resolve as soon as they are popped.
+        match self.device.pop_error_scope().now_or_never().flatten() {
+            Some(err) => Err(ComputeError::Validation {
+                stage,
+                message: err.to_string(),
+            }),
+            None => Ok(value),
+        }
+    }
+
+    pub(crate) fn push_constants(&self) -> bool {
+        self.device
+            .features()
+            .contains(wgpu::Features::PUSH_CONSTANTS)
+    }
+
+    pub(crate) fn pipeline(
+        &self,
+        kernel: &dyn GpuKernel,
+        element_size: u64,
+    ) -> Result<Arc<Pipeline>, ComputeError> {
+        self.pipeline_with_params(kernel, element_size, ParamsLayout::None)
+    }
+
+    pub(crate) fn pipeline_with_params(
+        &self,
+        kernel: &dyn GpuKernel,
+        element_size: u64,
+        params: ParamsLayout,
+    ) -> Result<Arc<Pipeline>, ComputeError> {
+        self.cached_pipeline(kernel, element_size, params, false, false)
+    }
+
+    /// Like [`DeviceState::pipeline`], but with dynamic offsets on both
+    /// storage bindings, so one bind group can serve many jobs packed into
+    /// the same buffers.
+    pub(crate) fn batch_pipeline(
+        &self,
+        kernel: &dyn GpuKernel,
+        element_size: u64,
+    ) -> Result<Arc<Pipeline>, ComputeError> {
+        self.cached_pipeline(kernel, element_size, ParamsLayout::None, true, false)
+    }
+
+    /// Like [`DeviceState::pipeline`], with a `u32` storage buffer at
+    /// binding 4 for the kernel to count NaNs in.
+    pub(crate) fn counting_pipeline(
+        &self,
+        kernel: &dyn GpuKernel,
+        element_size: u64,
+    ) -> Result<Arc<Pipeline>, ComputeError> {
+        self.cached_pipeline(kernel, element_size, ParamsLayout::None, false, true)
+    }
+
+    fn cached_pipeline(
+        &self,
+        kernel: &dyn GpuKernel,
+        element_size: u64,
+        params: ParamsLayout,
+        dynamic_offsets: bool,
+        nan_counter: bool,
+    ) -> Result<Arc<Pipeline>, ComputeError> {
+        // The module is built from either source, so kernels sharing their
+        // SPIR-V but not their WGSL need modules of their own.
+        let mut hasher = DefaultHasher::new();
+        kernel.spirv().hash(&mut hasher);
+        kernel.wgsl().hash(&mut hasher);
+        let module_key = hasher.finish();
+        let key = (
+            module_key,
+            kernel.entry_point().to_owned(),
+            element_size,
+            params,
+            dynamic_offsets,
+            nan_counter,
+        );
+
+        let mut cache = self.cache.lock().unwrap();
+        if let Some(pipeline) = cache.pipelines.get(&key) {
+            return Ok(pipeline.clone());
+        }
+
+        let storage = |binding, read_only| wgpu::BindGroupLayoutEntry {
+            binding,
+            count: None,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                has_dynamic_offset: dynamic_offsets,
+                min_binding_size: NonZeroU64::new(element_size),
+                ty: wgpu::BufferBindingType::Storage { read_only },
+            },
+        };
+        let mut entries = vec![storage(0, true), storage(1, false)];
+        if let ParamsLayout::Uniform(size) = params {
+            entries.push(wgpu::BindGroupLayoutEntry {
+                binding: 2,
+                count: None,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    has_dynamic_offset: false,
+                    min_binding_size: NonZeroU64::new(size),
+                    ty: wgpu::BufferBindingType::Uniform,
+                },
+            });
+        }
+        if kernel.element_count() {
+            entries.push(wgpu::BindGroupLayoutEntry {
+                binding: 3,
+                count: None,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    has_dynamic_offset: dynamic_offsets,
+                    min_binding_size: NonZeroU64::new(COUNT_SIZE),
+                    ty: wgpu::BufferBindingType::Uniform,
+                },
+            });
+        }
+        if nan_counter {
+            entries.push(wgpu::BindGroupLayoutEntry {
+                binding: 4,
+                count: None,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    has_dynamic_offset: false,
+                    min_binding_size: NonZeroU64::new(NAN_COUNTER_SIZE),
+                    ty: wgpu::BufferBindingType::Storage { read_only: false },
+                },
+            });
+        }
+        let bind_group_layout = self.scoped("bind group layout", || {
+            self.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: self.options.label_for("bind group layout").as_deref(),
+                    entries: &entries,
+                })
+        })?;
+
+        let push_constant_ranges = match params {
+            ParamsLayout::PushConstants(size) => vec![wgpu::PushConstantRange {
+                stages: wgpu::ShaderStages::COMPUTE,
+                range: 0..size,
+            }],
+            _ => Vec::new(),
+        };
+        let pipeline_layout = self
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: self.options.label_for("pipeline layout").as_deref(),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &push_constant_ranges,
+            });
+
+        if let Entry::Vacant(entry) = cache.modules.entry(module_key) {
+            entry.insert(self.scoped("shader module", || {
+                load_shader_module(&self.device, &self.options, kernel)
+            })?);
+        }
+        let pipeline = self.scoped("pipeline", || {
+            self.device
+                .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: self.options.label_for("pipeline").as_deref(),
+                    layout: Some(&pipeline_layout),
+                    module: &cache.modules[&module_key],
+                    entry_point: kernel.entry_point(),
+                })
+        })?;
+
+        let pipeline = Arc::new(Pipeline {
+            bind_group_layout,
+            pipeline,
+            workgroup_elements: kernel.workgroup_size() * kernel.elements_per_invocation(),
+            max_workgroups: if kernel.grid_stride() {
+                GRID_STRIDE_WORKGROUPS
+                    .min(self.device.limits().max_compute_workgroups_per_dimension)
+            } else {
+                u32::MAX
+            },
+            spill_width: kernel
+                .spills_into_y()
+                .then(|| self.device.limits().max_compute_workgroups_per_dimension),
+            params,
+            element_count: kernel.element_count(),
+        });
+        cache.pipelines.insert(key, pipeline.clone());
+        Ok(pipeline)
+    }
+
+    /// An uninitialized input buffer of `size` bytes, filled with
+    /// `queue.write_buffer`.
+    pub(crate) fn create_empty_storage_buffer(
+        &self,
+        size: wgpu::BufferAddress,
+    ) -> Result<wgpu::Buffer, ComputeError> {
+        self.create_input_buffer(size, false)
+    }
+
+    fn create_input_buffer(
+        &self,
+        size: wgpu::BufferAddress,
+        mapped_at_creation: bool,
+    ) -> Result<wgpu::Buffer, ComputeError> {
+        self.scoped("storage buffer", || {
+            self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(
+                    self.options
+                        .label_for("storage buffer")
+                        .as_deref()
+                        .unwrap_or("Vector Input"),
+                ),
+                size,
+                // Copied from when an uploaded `GpuVec` is read back.
+                usage: wgpu::BufferUsages::STORAGE
+                    | wgpu::BufferUsages::COPY_DST
+                    | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation,
+            })
+        })
+    }
+
+    /// A buffer of `size` bytes for a kernel to write its results to, to be
+    /// copied to a readback buffer or passed to the next kernel.
+    pub(crate) fn create_output_buffer(
+        &self,
+        size: wgpu::BufferAddress,
+    ) -> Result<wgpu::Buffer, ComputeError> {
+        self.scoped("output buffer", || {
+            self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: self.options.label_for("output buffer").as_deref(),
+                size,
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            })
+        })
+    }
+
+    /// A storage buffer holding `contents`.
+    ///
+    /// Small contents are uploaded through the queue. From
+    /// [`MAPPED_UPLOAD_SIZE`] up they are copied straight into the buffer
+    /// mapped at creation, which saves the queue its staging copy.
+    pub(crate) fn create_storage_buffer(
+        &self,
+        contents: &[u8],
+    ) -> Result<wgpu::Buffer, ComputeError> {
+        let size = contents.len() as wgpu::BufferAddress;
+        if size < MAPPED_UPLOAD_SIZE {
+            let buffer = self.create_empty_storage_buffer(size)?;
+            self.queue.write_buffer(&buffer, 0, contents);
+            return Ok(buffer);
+        }
+
+        // A buffer mapped at creation must be a whole number of copy units.
+        let buffer = self.create_input_buffer(copy_size(size), true)?;
+        buffer.slice(..).get_mapped_range_mut()[..contents.len()].copy_from_slice(contents);
+        buffer.unmap();
+        Ok(buffer)
+    }
+
+    pub(crate) fn create_command_encoder(&self) -> CommandEncoder {
+        self.device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: self.options.label_for("command encoder").as_deref(),
+            })
+    }
+
+    /// Binds all of `input` and `output` and records `pipeline` over their
+    /// first `elements` elements.
+    pub(crate) fn encode_kernel(
+        &self,
+        encoder: &mut CommandEncoder,
+        pipeline: &Pipeline,
+        input: &wgpu::Buffer,
+        output: &wgpu::Buffer,
+        elements: u32,
+    ) -> Result<(), ComputeError> {
+        self.encode_kernel_with_params(
+            encoder,
+            pipeline,
+            input.as_entire_buffer_binding(),
+            output.as_entire_buffer_binding(),
+            elements,
+            None,
+            None,
+            &[],
+        )
+    }
+
+    /// Like [`GpuContext::encode_kernel`], binding only `input` and
+    /// `output`, passing `params` the way the pipeline expects them and
+    /// dispatching over rows of `row_len` elements, if given, see
+    /// [`GpuKernel::row_len`]. A pipeline from
+    /// [`DeviceState::counting_pipeline`] needs a `nan_counter`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn encode_kernel_with_params(
+        &self,
+        encoder: &mut CommandEncoder,
+        pipeline: &Pipeline,
+        input: wgpu::BufferBinding,
+        output: wgpu::BufferBinding,
+        elements: u32,
+        row_len: Option<u32>,
+        nan_counter: Option<&wgpu::Buffer>,
+        params: &[u8],
+    ) -> Result<(), ComputeError> {
+        let bind_group =
+            self.kernel_bind_group(pipeline, input, output, elements, nan_counter, params)?;
+        let push_constants = match pipeline.params {
+            ParamsLayout::PushConstants(_) => params,
+            _ => &[],
+        };
+        self.record_dispatch(
+            encoder,
+            pipeline,
+            &bind_group,
+            push_constants,
+            elements,
+            row_len,
+        );
+        Ok(())
+    }
+
+    /// Like [`GpuContext::encode_kernel`], with as many workgroups as the
+    /// first three `u32`s of `args` hold once the GPU gets to the dispatch,
+    /// see [`IndirectArgs`](crate::IndirectArgs). The kernel still checks
+    /// its invocations against `elements`.
+    pub(crate) fn encode_kernel_indirect(
+        &self,
+        encoder: &mut CommandEncoder,
+        pipeline: &Pipeline,
+        input: &wgpu::Buffer,
+        output: &wgpu::Buffer,
+        elements: u32,
+        args: &wgpu::Buffer,
+    ) -> Result<(), ComputeError> {
+        let bind_group = self.kernel_bind_group(
+            pipeline,
+            input.as_entire_buffer_binding(),
+            output.as_entire_buffer_binding(),
+            elements,
+            None,
+            &[],
+        )?;
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: self.options.label_for("compute pass").as_deref(),
+        });
+        cpass.set_bind_group(0, &bind_group, &[]);
+        cpass.set_pipeline(&pipeline.pipeline);
+        cpass.dispatch_indirect(args, 0);
+        Ok(())
+    }
+
+    /// The bind group [`GpuContext::encode_kernel_with_params`] dispatches
+    /// with, creating the uniform and count buffers the pipeline takes.
+    fn kernel_bind_group(
+        &self,
+        pipeline: &Pipeline,
+        input: wgpu::BufferBinding,
+        output: wgpu::BufferBinding,
+        elements: u32,
+        nan_counter: Option<&wgpu::Buffer>,
+        params: &[u8],
+    ) -> Result<BindGroup, ComputeError> {
+        let uniform_buffer = match pipeline.params {
+            ParamsLayout::Uniform(size) => {
+                let buffer = self.scoped("uniform buffer", || {
+                    self.device.create_buffer(&wgpu::BufferDescriptor {
+                        label: self.options.label_for("uniform buffer").as_deref(),
+                        size,
+                        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                        mapped_at_creation: false,
+                    })
+                })?;
+                // Lands before the encoder's dispatch, as writes are
+                // queued ahead of the next submission.
+                self.queue.write_buffer(&buffer, 0, params);
+                Some(buffer)
+            }
+            _ => None,
+        };
+        let count_buffer = if pipeline.element_count {
+            Some(self.create_count_buffer(&[elements])?)
+        } else {
+            None
+        };
+
+        let mut entries = vec![
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(input),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Buffer(output),
+            },
+        ];
+        if let Some(uniform_buffer) = &uniform_buffer {
+            entries.push(wgpu::BindGroupEntry {
+                binding: 2,
+                resource: uniform_buffer.as_entire_binding(),
+            });
+        }
+        if let Some(count_buffer) = &count_buffer {
+            entries.push(count_entry(count_buffer));
+        }
+        if let Some(nan_counter) = nan_counter {
+            entries.push(wgpu::BindGroupEntry {
+                binding: 4,
+                resource: nan_counter.as_entire_binding(),
+            });
+        }
+        self.scoped("bind group", || {
+            self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: self.options.label_for("bind group").as_deref(),
+                layout: &pipeline.bind_group_layout,
+                entries: &entries,
+            })
+        })
+    }
+
+    /// Appends a copy of the first `size` bytes of `buffer` to
+    /// `encoder`, submits it and returns the elements copied out of a
+    /// temporary readback buffer, which is released before returning.
+    pub(crate) async fn read_back<T: bytemuck::Pod>(
+        &self,
+        encoder: CommandEncoder,
+        buffer: &wgpu::Buffer,
+        size: wgpu::BufferAddress,
+    ) -> Result<Vec<T>, ComputeError> {
+        let readback_buffer = self.create_readback_buffer(copy_size(size))?;
+        self.read_back_into(encoder, buffer, &readback_buffer, size)
+            .await?;
+        let mapped = readback_buffer.slice(..copy_size(size)).get_mapped_range();
+        let output = bytemuck::cast_slice(&mapped[..size as usize]).to_vec();
+        drop(mapped);
+        readback_buffer.unmap();
+        readback_buffer.destroy();
+        Ok(output)
+    }
+
+    /// Like [`GpuContext::read_back`], but copies into and maps the first
+    /// `size` bytes of an existing, unmapped `readback_buffer`, leaving it
+    /// to the caller to unmap it once the results are copied out. Odd sizes
+    /// are rounded up with [`copy_size`], which both buffers must cover.
+    pub(crate) async fn read_back_into(
+        &self,
+        encoder: CommandEncoder,
+        buffer: &wgpu::Buffer,
+        readback_buffer: &wgpu::Buffer,
+        size: wgpu::BufferAddress,
+    ) -> Result<(), ComputeError> {
+        self.submit_read_back(encoder, buffer, readback_buffer, size)
+            .await
+    }
+
+    /// Submits `encoder` to the queue, counting it for
+    /// [`GpuContext::submissions`].
+    pub(crate) fn submit_commands(&self, encoder: CommandEncoder) {
+        self.submissions.fetch_add(1, Ordering::Relaxed);
+        self.queue.submit(Some(encoder.finish()));
+    }
+
+    /// Submits what [`GpuContext::read_back_into`] does and returns a
+    /// future that resolves once `readback_buffer` is mapped, without
+    /// borrowing either buffer in the meantime.
+    ///
+    /// The future also waits for the queue to report the submission done,
+    /// so a mapped buffer always holds its own copy, however many other
+    /// submissions are in flight and whichever order the poller runs their
+    /// callbacks in. Pooled buffers only go back to the pool once mapped,
+    /// so their reuse is gated on it too.
+    pub(crate) fn submit_read_back(
+        &self,
+        mut encoder: CommandEncoder,
+        buffer: &wgpu::Buffer,
+        readback_buffer: &wgpu::Buffer,
+        size: wgpu::BufferAddress,
+    ) -> impl Future<Output = Result<(), ComputeError>> {
+        let size = copy_size(size);
+        encoder.copy_buffer_to_buffer(buffer, 0, readback_buffer, 0, size);
+
+        self.submit_commands(encoder);
+        let work_done = self.queue.on_submitted_work_done();
+        let buffer_future = readback_buffer.slice(..size).map_async(wgpu::MapMode::Read);
+        self.poll();
+
+        let delay = self.injected_delay.lock().unwrap().take();
+        let timeout = self.options.timeout;
+        async move {
+            let mapped = async {
+                if let Some(delay) = delay {
+                    timeout::sleep(delay).await;
+                }
+                work_done.await;
+                buffer_future.await
+            };
+            with_timeout(timeout, "buffer mapping", mapped).await??;
+            Ok(())
+        }
+    }
+
+    /// Has the poller thread drive the work submitted so far, resolving
+    /// pending maps.
+    pub(crate) fn poll(&self) {
+        self.poller.kick();
+    }
+
+    /// A uniform buffer holding `counts` for kernels that take an element
+    /// count, each at its own multiple of the uniform offset alignment, so
+    /// that dynamic offsets can pick one.
+    pub(crate) fn create_count_buffer(&self, counts: &[u32]) -> Result<wgpu::Buffer, ComputeError> {
+        let stride = self.count_stride() as usize;
+        let mut contents = vec![0; stride * (counts.len() - 1) + COUNT_SIZE as usize];
+        for (index, count) in counts.iter().enumerate() {
+            let count = Count {
+                element_count: *count,
+            };
+            contents[index * stride..][..COUNT_SIZE as usize]
+                .copy_from_slice(bytemuck::bytes_of(&count));
+        }
+        self.scoped("count buffer", || {
+            self.device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: self.options.label_for("count buffer").as_deref(),
+                    contents: &contents,
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                })
+        })
+    }
+
+    /// Bytes between the counts of a [`DeviceState::create_count_buffer`].
+    pub(crate) fn count_stride(&self) -> u64 {
+        self.device.limits().min_uniform_buffer_offset_alignment as u64
+    }
+
+    pub(crate) fn create_readback_buffer(
+        &self,
+        size: wgpu::BufferAddress,
+    ) -> Result<wgpu::Buffer, ComputeError> {
+        self.scoped("readback buffer", || {
+            self.device.create_buffer(&wgpu::BufferDescriptor {
+                label: self.options.label_for("readback buffer").as_deref(),
+                size,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        })
+    }
+
+    /// Records one compute pass covering `elements` invocations, over rows
+    /// of `row_len` if given.
+    pub(crate) fn record_dispatch(
+        &self,
+        encoder: &mut CommandEncoder,
+        pipeline: &Pipeline,
+        bind_group: &BindGroup,
+        push_constants: &[u8],
+        elements: u32,
+        row_len: Option<u32>,
+    ) {
+        let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: self.options.label_for("compute pass").as_deref(),
+        });
+        cpass.set_bind_group(0, bind_group, &[]);
+        cpass.set_pipeline(&pipeline.pipeline);
+        if !push_constants.is_empty() {
+            cpass.set_push_constants(0, push_constants);
+        }
+        let (x, y) = pipeline.dispatch_size(elements, row_len);
+        cpass.dispatch(x, y, 1);
+    }
+}
+
+/// Binds the first count of `count_buffer` for a kernel that takes one.
+pub(crate) fn count_entry(count_buffer: &wgpu::Buffer) -> wgpu::BindGroupEntry<'_> {
+    wgpu::BindGroupEntry {
+        binding: 3,
+        resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+            buffer: count_buffer,
+            offset: 0,
+            size: NonZeroU64::new(COUNT_SIZE),
+        }),
+    }
+}
+
+/// Appends the `T`s in `bytes` to `output`, copying them one by one if the
+/// mapping they were read from isn't aligned for `T`: wgpu only promises
+/// [`wgpu::MAP_ALIGNMENT`].
+fn extend_from_bytes<T: Pod>(output: &mut Vec<T>, bytes: &[u8]) {
+    match bytemuck::try_cast_slice(bytes) {
+        Ok(elements) => output.extend_from_slice(elements),
+        Err(_) => output.extend(
+            bytes
+                .chunks_exact(std::mem::size_of::<T>())
+                .map(bytemuck::pod_read_unaligned::<T>),
+        ),
+    }
+}
+
+/// Checks that `output` results were read back for `input` elements, as
+/// every call returns one result per element with any padding cut off.
+pub(crate) fn check_result_len(input: usize, output: usize) -> Result<(), ComputeError> {
+    debug_assert_eq!(input, output, "results read back for {input} elements");
+    if input != output {
+        return Err(ComputeError::ResultLength { input, output });
+    }
+    Ok(())
+}
+
+pub(crate) fn validate(input: &[f32]) -> Result<(), ComputeError> {
+    match input.iter().position(|x| x.is_nan() || *x < 0.) {
+        Some(index) => Err(ComputeError::InvalidInput {
+            index,
+            value: input[index],
+        }),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,260 @@
+use std::{fmt, io, path::PathBuf};
+
+use wgpu::{AdapterInfo, Backends, BufferAsyncError, Features, RequestDeviceError};
+
+use crate::AdapterSelector;
+
+/// Why a device could not be created.
+#[derive(Debug)]
+pub enum InitError {
+    /// No adapter satisfying the request could be found on
+    /// `requested_backends`. `available` lists every adapter on those
+    /// backends, which the power preference or fallback setting ruled out.
+    NoAdapter {
+        requested_backends: Backends,
+        available: Vec<AdapterInfo>,
+    },
+    /// `adapter` was found but refused to create a device.
+    DeviceRequest {
+        adapter: AdapterInfo,
+        source: RequestDeviceError,
+    },
+    /// The directory given to `ComputeOptions::trace_dir` could not be
+    /// created or written to.
+    TraceDir { path: PathBuf, source: io::Error },
+    /// `adapter` lacks features required by
+    /// [`ComputeOptions::required_features`](crate::ComputeOptions::required_features).
+    MissingFeatures {
+        adapter: AdapterInfo,
+        missing: Features,
+    },
+}
+
+fn describe(adapter: &AdapterInfo) -> String {
+    format!(
+        "{} ({:?}, {:?})",
+        adapter.name, adapter.backend, adapter.device_type
+    )
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InitError::NoAdapter {
+                requested_backends,
+                available,
+            } => {
+                write!(
+                    f,
+                    "failed to find an appropriate adapter on {requested_backends:?}"
+                )?;
+                if available.is_empty() {
+                    write!(f, ", no adapters available")
+                } else {
+                    let available = available.iter().map(describe).collect::<Vec<_>>();
+                    write!(f, ", available: {}", available.join(", "))
+                }
+            }
+            InitError::DeviceRequest { adapter, source } => write!(
+                f,
+                "failed to create device on {}: {source}",
+                describe(adapter)
+            ),
+            InitError::TraceDir { path, source } => write!(
+                f,
+                "trace directory {} is not writable: {source}",
+                path.display()
+            ),
+            InitError::MissingFeatures { adapter, missing } => write!(
+                f,
+                "{} lacks required features {missing:?}",
+                describe(adapter)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for InitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InitError::DeviceRequest { source, .. } => Some(source),
+            InitError::TraceDir { source, .. } => Some(source),
+            InitError::NoAdapter { .. } | InitError::MissingFeatures { .. } => None,
+        }
+    }
+}
+
+/// Everything that can go wrong while running the kernel.
+#[derive(Debug)]
+pub enum ComputeError {
+    /// Creating the device failed.
+    Init(InitError),
+    /// No adapter matched the [`AdapterSelector`](crate::AdapterSelector).
+    AdapterNotFound {
+        selector: AdapterSelector,
+        available: Vec<String>,
+    },
+    /// `adapter` cannot run compute shaders at all.
+    ComputeUnsupported { adapter: AdapterInfo },
+    /// `adapter` lacks `Features::SHADER_FLOAT64`, which
+    /// [`GpuContext::compute_f64`](crate::GpuContext::compute_f64) needs.
+    F64Unsupported { adapter: AdapterInfo },
+    /// Mapping the readback buffer failed.
+    BufferAsync(BufferAsyncError),
+    /// Input validation is enabled and `value` at `index` has no real
+    /// inverse square root.
+    InvalidInput { index: usize, value: f32 },
+    /// [`NanPolicy::Error`](crate::NanPolicy::Error) is set and the result
+    /// at `first_index` is the first that came out NaN.
+    NanInOutput { first_index: usize },
+    /// A strided layout selects no elements: `stride` is zero, or `offset`
+    /// is not less than it.
+    InvalidLayout { offset: usize, stride: usize },
+    /// An input of `len` elements doesn't hold a `width` by `height`
+    /// matrix.
+    InvalidShape { len: usize, width: u32, height: u32 },
+    /// An input of `len` bytes is not a whole number of the kernel's
+    /// `element_size`-byte elements.
+    Misaligned { len: usize, element_size: u64 },
+    /// A low-level entry point such as
+    /// [`GpuContext::run_kernel`](crate::GpuContext::run_kernel) was given
+    /// no input, which has nothing to bind.
+    EmptyInput,
+    /// The slice to read `input` elements of results into, or to compare
+    /// them against, holds `output`.
+    LengthMismatch { input: usize, output: usize },
+    /// The results read back held `output` elements for `input` input
+    /// elements, which is a bug in this crate rather than in the call.
+    ResultLength { input: usize, output: usize },
+    /// wgpu rejected an object created at `stage`, e.g. `"bind group"`,
+    /// with `message`.
+    Validation {
+        stage: &'static str,
+        message: String,
+    },
+    /// Waiting for the `stage`, e.g. `"buffer mapping"`, took longer than
+    /// [`ComputeOptions::timeout`](crate::ComputeOptions::timeout).
+    Timeout { stage: &'static str },
+    /// `requested` elements don't fit the device's limits for a single
+    /// dispatch, which cover at most `max`, and the input could not be
+    /// split, either because
+    /// [`ComputeOptions::split_large_inputs`](crate::ComputeOptions::split_large_inputs)
+    /// is off or because the results have to stay in one buffer.
+    TooLarge { requested: usize, max: usize },
+    /// The buffers for the input would take `required` bytes, more than the
+    /// [`ComputeOptions::memory_budget`](crate::ComputeOptions::memory_budget)
+    /// of `budget`.
+    OutOfBudget { required: u64, budget: u64 },
+    /// The computation was cancelled through its
+    /// [`ComputeHandle`](crate::ComputeHandle) after `completed` elements.
+    Cancelled { completed: usize },
+    /// Readbacks kept failing after recreating the device as many times as
+    /// [`ComputeOptions::max_retries`](crate::ComputeOptions::max_retries)
+    /// allows.
+    DeviceLost,
+}
+
+impl fmt::Display for ComputeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComputeError::Init(err) => write!(f, "{err}"),
+            ComputeError::AdapterNotFound {
+                selector,
+                available,
+            } => write!(
+                f,
+                "no adapter matches {selector:?}, available: {}",
+                available.join(", ")
+            ),
+            ComputeError::ComputeUnsupported { adapter } => {
+                write!(f, "{} does not support compute shaders", describe(adapter))
+            }
+            ComputeError::F64Unsupported { adapter } => {
+                write!(f, "{} does not support f64 in shaders", describe(adapter))
+            }
+            ComputeError::BufferAsync(err) => write!(f, "failed to map readback buffer: {err}"),
+            ComputeError::InvalidInput { index, value } => {
+                write!(
+                    f,
+                    "input {value} at index {index} has no real inverse square root"
+                )
+            }
+            ComputeError::NanInOutput { first_index } => {
+                write!(f, "result at index {first_index} is NaN")
+            }
+            ComputeError::InvalidLayout { offset, stride } => {
+                write!(f, "offset {offset} is not a lane of stride {stride}")
+            }
+            ComputeError::InvalidShape { len, width, height } => {
+                write!(f, "{len} elements don't make a {width}x{height} matrix")
+            }
+            ComputeError::Misaligned { len, element_size } => write!(
+                f,
+                "input of {len} bytes is not a whole number of {element_size}-byte elements"
+            ),
+            ComputeError::EmptyInput => write!(f, "input is empty"),
+            ComputeError::LengthMismatch { input, output } => write!(
+                f,
+                "{input} input elements don't match an output slice of {output}"
+            ),
+            ComputeError::ResultLength { input, output } => {
+                write!(f, "read back {output} results for {input} input elements")
+            }
+            ComputeError::Validation { stage, message } => {
+                write!(f, "validation failed creating the {stage}: {message}")
+            }
+            ComputeError::Timeout { stage } => write!(f, "timed out waiting for the {stage}"),
+            ComputeError::TooLarge { requested, max } => write!(
+                f,
+                "{requested} elements exceed the device limit of {max} elements per dispatch"
+            ),
+            ComputeError::OutOfBudget { required, budget } => write!(
+                f,
+                "{required} bytes of buffers exceed the memory budget of {budget} bytes; \
+                 split the input, e.g. with compute_stream"
+            ),
+            ComputeError::DeviceLost => write!(f, "device lost and could not be recovered"),
+            ComputeError::Cancelled { completed } => {
+                write!(f, "cancelled after {completed} elements")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ComputeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ComputeError::AdapterNotFound { .. }
+            | ComputeError::ComputeUnsupported { .. }
+            | ComputeError::F64Unsupported { .. }
+            | ComputeError::InvalidInput { .. }
+            | ComputeError::NanInOutput { .. }
+            | ComputeError::InvalidLayout { .. }
+            | ComputeError::InvalidShape { .. }
+            | ComputeError::Misaligned { .. }
+            | ComputeError::EmptyInput
+            | ComputeError::LengthMismatch { .. }
+            | ComputeError::ResultLength { .. }
+            | ComputeError::Validation { .. }
+            | ComputeError::Timeout { .. }
+            | ComputeError::TooLarge { .. }
+            | ComputeError::OutOfBudget { .. }
+            | ComputeError::Cancelled { .. }
+            | ComputeError::DeviceLost => None,
+            ComputeError::Init(err) => Some(err),
+            ComputeError::BufferAsync(err) => Some(err),
+        }
+    }
+}
+
+impl From<InitError> for ComputeError {
+    fn from(err: InitError) -> Self {
+        ComputeError::Init(err)
+    }
+}
+
+impl From<BufferAsyncError> for ComputeError {
+    fn from(err: BufferAsyncError) -> Self {
+        ComputeError::BufferAsync(err)
+    }
+}
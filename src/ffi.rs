@@ -0,0 +1,113 @@
+//! C interface over a process-wide [`GpuContext`].
+//!
+//! Enabled by the `ffi` feature. Build the shared library with
+//!
+//! ```text
+//! cargo rustc --release --lib --features ffi --crate-type cdylib
+//! ```
+//!
+//! and declare the functions from `include/rsqrt_gpu.h`. Every function
+//! returns one of the `RSQRT_GPU_*` codes and never unwinds into the caller.
+
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{Mutex, MutexGuard},
+};
+
+use crate::{ComputeError, GpuContext};
+
+/// The call succeeded.
+pub const RSQRT_GPU_OK: i32 = 0;
+/// No adapter or device could be acquired.
+pub const RSQRT_GPU_NO_ADAPTER: i32 = -1;
+/// The dispatch or the readback failed.
+pub const RSQRT_GPU_DISPATCH_FAILED: i32 = -2;
+/// A pointer was null or the length was zero.
+pub const RSQRT_GPU_INVALID_ARGUMENTS: i32 = -3;
+/// [`rsqrt_gpu_init`] has not been called.
+pub const RSQRT_GPU_NOT_INITIALIZED: i32 = -4;
+
+static CONTEXT: Mutex<Option<GpuContext>> = Mutex::new(None);
+
+fn context() -> MutexGuard<'static, Option<GpuContext>> {
+    // A panic while holding the lock leaves the context as it was, so the
+    // poison flag carries no information.
+    CONTEXT.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Runs `f`, turning a panic into [`RSQRT_GPU_DISPATCH_FAILED`].
+fn guarded(f: impl FnOnce() -> i32) -> i32 {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(RSQRT_GPU_DISPATCH_FAILED)
+}
+
+fn error_code(err: ComputeError) -> i32 {
+    match err {
+        ComputeError::Init(_)
+        | ComputeError::AdapterNotFound { .. }
+        | ComputeError::ComputeUnsupported { .. }
+        | ComputeError::F64Unsupported { .. } => RSQRT_GPU_NO_ADAPTER,
+        ComputeError::InvalidInput { .. }
+        | ComputeError::NanInOutput { .. }
+        | ComputeError::InvalidLayout { .. }
+        | ComputeError::InvalidShape { .. }
+        | ComputeError::Misaligned { .. }
+        | ComputeError::EmptyInput
+        | ComputeError::LengthMismatch { .. } => RSQRT_GPU_INVALID_ARGUMENTS,
+        _ => RSQRT_GPU_DISPATCH_FAILED,
+    }
+}
+
+/// Creates the global context. Calling it again while initialized is a
+/// no-op.
+#[no_mangle]
+pub extern "C" fn rsqrt_gpu_init() -> i32 {
+    guarded(|| {
+        let mut context = context();
+        if context.is_none() {
+            match futures::executor::block_on(GpuContext::new()) {
+                Ok(ctx) => *context = Some(ctx),
+                Err(err) => return error_code(err),
+            }
+        }
+        RSQRT_GPU_OK
+    })
+}
+
+/// Drops the global context and its device.
+#[no_mangle]
+pub extern "C" fn rsqrt_gpu_shutdown() -> i32 {
+    guarded(|| {
+        context().take();
+        RSQRT_GPU_OK
+    })
+}
+
+/// Computes `1 / sqrt(x)` for the `len` floats at `input` and writes the
+/// results to `output`.
+///
+/// # Safety
+///
+/// `input` must be valid for reading and `output` for writing `len` floats.
+/// The two ranges may be the same but must not otherwise overlap.
+#[no_mangle]
+pub unsafe extern "C" fn rsqrt_gpu(input: *const f32, output: *mut f32, len: usize) -> i32 {
+    if input.is_null() || output.is_null() || len == 0 {
+        return RSQRT_GPU_INVALID_ARGUMENTS;
+    }
+
+    guarded(|| {
+        let context = context();
+        let Some(ctx) = context.as_ref() else {
+            return RSQRT_GPU_NOT_INITIALIZED;
+        };
+
+        let input = std::slice::from_raw_parts(input, len);
+        match futures::executor::block_on(ctx.compute(input)) {
+            Ok(results) => {
+                std::ptr::copy(results.as_ptr(), output, len);
+                RSQRT_GPU_OK
+            }
+            Err(err) => error_code(err),
+        }
+    })
+}
@@ -0,0 +1,56 @@
+//! A process-wide [`GpuContext`] behind [`crate::inverse_sqrt`].
+//!
+//! Enabled by the `global-context` feature. The first call creates the
+//! context and every later call reuses it, until [`shutdown`] drops it.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc, Mutex, MutexGuard,
+};
+
+use once_cell::sync::Lazy;
+
+use crate::{ComputeError, GpuContext};
+
+static CONTEXT: Mutex<Option<Arc<GpuContext>>> = Mutex::new(None);
+/// Held while the context is created, across awaits.
+static CREATING: Lazy<futures::lock::Mutex<()>> = Lazy::new(Default::default);
+static INITIALIZATIONS: AtomicUsize = AtomicUsize::new(0);
+
+fn lock() -> MutexGuard<'static, Option<Arc<GpuContext>>> {
+    // Nothing panics while the context is being replaced, so the poison
+    // flag carries no information.
+    CONTEXT.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// The global context, created on first use.
+///
+/// Concurrent first calls wait for the one creating the context instead of
+/// creating devices of their own.
+pub(crate) async fn context() -> Result<Arc<GpuContext>, ComputeError> {
+    if let Some(ctx) = lock().as_ref() {
+        return Ok(ctx.clone());
+    }
+
+    let _creating = CREATING.lock().await;
+    // Another call may have created it while this one waited.
+    if let Some(ctx) = lock().as_ref() {
+        return Ok(ctx.clone());
+    }
+    let ctx = Arc::new(GpuContext::new().await?);
+    INITIALIZATIONS.fetch_add(1, Ordering::Relaxed);
+    *lock() = Some(ctx.clone());
+    Ok(ctx)
+}
+
+/// Drops the global context. Calls already running keep theirs until they
+/// finish, and the next call creates a new one.
+pub fn shutdown() {
+    lock().take();
+}
+
+/// How many times the global context has been created.
+#[doc(hidden)]
+pub fn initializations() -> usize {
+    INITIALIZATIONS.load(Ordering::Relaxed)
+}
@@ -0,0 +1,181 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use crate::{
+    context::DeviceState,
+    kernel::{Compare, Reduce},
+    stats::{Stats, REDUCE_GROUP_ELEMENTS},
+    ComputeError, GpuContext, Kernel, VerifyReport,
+};
+
+/// Results left on the GPU, so further kernels can run over them without a
+/// round trip through the host.
+///
+/// Created by [`GpuContext::upload`] or [`GpuContext::compute_gpu`]. The buffer stays on the device
+/// it was created on; it is not carried over if the context recovers from
+/// a lost device.
+pub struct GpuVec<'a> {
+    state: Arc<DeviceState>,
+    buffer: wgpu::Buffer,
+    len: usize,
+    ctx: PhantomData<&'a GpuContext>,
+}
+
+impl<'a> GpuVec<'a> {
+    pub(crate) fn new(state: Arc<DeviceState>, buffer: wgpu::Buffer, len: usize) -> Self {
+        GpuVec {
+            state,
+            buffer,
+            len,
+            ctx: PhantomData,
+        }
+    }
+
+    /// Number of elements in the buffer.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the buffer holds no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Runs `kernel` over the buffer, writing its results to a new buffer
+    /// that replaces this one.
+    ///
+    /// The dispatch is submitted right away; nothing is read back.
+    pub fn apply(self, kernel: Kernel) -> Result<GpuVec<'a>, ComputeError> {
+        self.map(kernel)
+    }
+
+    /// Runs `kernels` over the buffer one after another, each over the
+    /// results of the one before, recorded into a single command buffer.
+    ///
+    /// Each kernel gets a compute pass of its own, taking turns between
+    /// this buffer and one new one. wgpu puts the barriers a pass's
+    /// bindings need between passes of one encoder, so every kernel reads
+    /// what the one before it wrote, on every backend. Within a single pass,
+    /// how dispatches are ordered is less consistent across backends.
+    /// The chain is submitted right away; nothing is read back.
+    pub fn apply_chain(self, kernels: &[Kernel]) -> Result<GpuVec<'a>, ComputeError> {
+        if kernels.is_empty() {
+            return Ok(self);
+        }
+        let state = &self.state;
+        let spare = state.create_output_buffer((self.len * 4) as wgpu::BufferAddress)?;
+        let buffers = [&self.buffer, &spare];
+        let mut encoder = state.create_command_encoder();
+        for (pass, kernel) in kernels.iter().enumerate() {
+            let pipeline = state.pipeline(kernel, 4)?;
+            state.encode_kernel(
+                &mut encoder,
+                &pipeline,
+                buffers[pass % 2],
+                buffers[(pass + 1) % 2],
+                self.len as u32,
+            )?;
+        }
+        state.submit_commands(encoder);
+
+        let buffer = if kernels.len() % 2 == 0 {
+            self.buffer
+        } else {
+            spare
+        };
+        Ok(GpuVec::new(self.state, buffer, self.len))
+    }
+
+    /// Runs `kernel` over the buffer, writing its results to a new buffer
+    /// and leaving this one as it was.
+    ///
+    /// Kernels read their input and write their output through separate
+    /// bindings, so one source can feed any number of kernels.
+    pub fn map(&self, kernel: Kernel) -> Result<GpuVec<'a>, ComputeError> {
+        let state = &self.state;
+        let output = state.create_output_buffer((self.len * 4) as wgpu::BufferAddress)?;
+        let mut encoder = state.create_command_encoder();
+        let pipeline = state.pipeline(&kernel, 4)?;
+        state.encode_kernel(
+            &mut encoder,
+            &pipeline,
+            &self.buffer,
+            &output,
+            self.len as u32,
+        )?;
+        state.submit_commands(encoder);
+        Ok(GpuVec::new(state.clone(), output, self.len))
+    }
+
+    /// Reduces the buffer to its [`Stats`] on the GPU, reading back only
+    /// one partial per four workgroups of elements.
+    pub async fn stats(&self) -> Result<Stats, ComputeError> {
+        let state = &self.state;
+        let groups = (self.len + REDUCE_GROUP_ELEMENTS - 1) / REDUCE_GROUP_ELEMENTS;
+        if groups == 0 {
+            return Ok(Stats::from_partials(&[], 0));
+        }
+        let size = (groups * std::mem::size_of::<[f32; 4]>()) as wgpu::BufferAddress;
+        let partials = state.create_output_buffer(size)?;
+        let mut encoder = state.create_command_encoder();
+        let pipeline = state.pipeline(&Reduce, 4)?;
+        state.encode_kernel(
+            &mut encoder,
+            &pipeline,
+            &self.buffer,
+            &partials,
+            self.len as u32,
+        )?;
+        let partials = state
+            .read_back::<[f32; 4]>(encoder, &partials, size)
+            .await?;
+        Ok(Stats::from_partials(&partials, self.len))
+    }
+
+    /// Compares the buffer against `reference` on the GPU, reading back
+    /// only one partial per four workgroups of elements to find the
+    /// largest relative error and where it is.
+    ///
+    /// A `reference` of a different length fails with
+    /// [`ComputeError::LengthMismatch`].
+    pub async fn verify_against(&self, reference: &[f32]) -> Result<VerifyReport, ComputeError> {
+        if reference.len() != self.len {
+            return Err(ComputeError::LengthMismatch {
+                input: self.len,
+                output: reference.len(),
+            });
+        }
+        let state = &self.state;
+        let groups = (self.len + REDUCE_GROUP_ELEMENTS - 1) / REDUCE_GROUP_ELEMENTS;
+        if groups == 0 {
+            return Ok(VerifyReport::from_partials(&[]));
+        }
+        // The results and the reference share one binding, the reference
+        // following the results.
+        state.check_fits(&Compare, 8, self.len)?;
+        let size = (self.len * 4) as wgpu::BufferAddress;
+        let pair = state.create_empty_storage_buffer(2 * size)?;
+        state
+            .queue
+            .write_buffer(&pair, size, bytemuck::cast_slice(reference));
+        let partials_size = (groups * std::mem::size_of::<[u32; 2]>()) as wgpu::BufferAddress;
+        let partials = state.create_output_buffer(partials_size)?;
+        let mut encoder = state.create_command_encoder();
+        encoder.copy_buffer_to_buffer(&self.buffer, 0, &pair, 0, size);
+        let pipeline = state.pipeline(&Compare, 4)?;
+        state.encode_kernel(&mut encoder, &pipeline, &pair, &partials, self.len as u32)?;
+        let partials = state
+            .read_back::<[u32; 2]>(encoder, &partials, partials_size)
+            .await?;
+        Ok(VerifyReport::from_partials(&partials))
+    }
+
+    /// Waits for every kernel applied so far and copies the results to the
+    /// host.
+    pub async fn read_back(&self) -> Result<Vec<f32>, ComputeError> {
+        let state = &self.state;
+        let size = (self.len * 4) as wgpu::BufferAddress;
+        state
+            .read_back(state.create_command_encoder(), &self.buffer, size)
+            .await
+    }
+}
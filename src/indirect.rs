@@ -0,0 +1,108 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use inverse_sqrt_shared::DispatchIndirect;
+use wgpu::util::DeviceExt;
+
+use crate::{
+    context::{validate, DeviceState, DISPATCH_BUFFERS},
+    ComputeError, GpuContext, Kernel,
+};
+
+/// The workgroup counts of an indirect dispatch, in a buffer on the GPU, so
+/// an earlier pass can decide how many workgroups a later one runs without
+/// a round trip through the host.
+///
+/// Created by [`GpuContext::indirect_args`] with one workgroup along `y`
+/// and `z` and none along `x`. Write the counts from the host with
+/// [`IndirectArgs::write`], or bind [`IndirectArgs::buffer`] as storage
+/// in a pass of your own, which writes a [`DispatchIndirect`] at offset
+/// 0. Like [`GpuVec`](crate::GpuVec), the buffer stays on the device it
+/// was created on.
+pub struct IndirectArgs<'a> {
+    state: Arc<DeviceState>,
+    buffer: wgpu::Buffer,
+    ctx: PhantomData<&'a GpuContext>,
+}
+
+impl IndirectArgs<'_> {
+    /// Sets the workgroups along x, y and z of the dispatches submitted
+    /// from now on.
+    pub fn write(&self, x: u32, y: u32, z: u32) {
+        let args = DispatchIndirect { x, y, z };
+        self.state
+            .queue
+            .write_buffer(&self.buffer, 0, bytemuck::bytes_of(&args));
+    }
+
+    /// The args buffer, usable as indirect arguments, as storage and as a
+    /// copy destination.
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+}
+
+impl GpuContext {
+    /// Creates the buffer of workgroup counts
+    /// [`GpuContext::compute_indirect`] dispatches with.
+    pub fn indirect_args(&self) -> Result<IndirectArgs<'_>, ComputeError> {
+        let state = self.state();
+        let args = DispatchIndirect { x: 0, y: 1, z: 1 };
+        let buffer = state.scoped("indirect args buffer", || {
+            state
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: state.options.label_for("indirect args buffer").as_deref(),
+                    contents: bytemuck::bytes_of(&args),
+                    usage: wgpu::BufferUsages::INDIRECT
+                        | wgpu::BufferUsages::STORAGE
+                        | wgpu::BufferUsages::COPY_DST,
+                })
+        })?;
+        Ok(IndirectArgs {
+            state,
+            buffer,
+            ctx: PhantomData,
+        })
+    }
+
+    /// Like [`GpuContext::compute`], in a single dispatch of as many
+    /// workgroups as `args` holds when the GPU gets to it, on the device
+    /// `args` was created on.
+    ///
+    /// Workgroups cover [`WORKGROUP_SIZE`](crate::WORKGROUP_SIZE) elements
+    /// each, along x first and then in rows of x along y. Invocations past
+    /// the end of `input` do nothing, and elements no workgroup covers are
+    /// left at zero. The zero policy does not apply, as the host doesn't
+    /// know which elements were covered; input validation does.
+    pub async fn compute_indirect(
+        &self,
+        input: &[f32],
+        args: &IndirectArgs<'_>,
+    ) -> Result<Vec<f32>, ComputeError> {
+        if self.options.validate_input {
+            validate(input)?;
+        }
+        if input.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let state = &args.state;
+        state.check_fits(&Kernel::InverseSqrt, 4, input.len())?;
+        let size = std::mem::size_of_val(input) as wgpu::BufferAddress;
+        state.check_budget(DISPATCH_BUFFERS, size)?;
+        let pipeline = state.pipeline(&Kernel::InverseSqrt, 4)?;
+
+        let storage_buffer = state.create_storage_buffer(bytemuck::cast_slice(input))?;
+        let output_buffer = state.create_output_buffer(size)?;
+        let mut encoder = state.create_command_encoder();
+        state.encode_kernel_indirect(
+            &mut encoder,
+            &pipeline,
+            &storage_buffer,
+            &output_buffer,
+            input.len() as u32,
+            &args.buffer,
+        )?;
+        state.read_back::<f32>(encoder, &output_buffer, size).await
+    }
+}
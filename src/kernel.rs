@@ -0,0 +1,785 @@
+use inverse_sqrt_shared::{Clamp, Count, Grid, Layout, Params, Refinement, Rows};
+
+use crate::{context::ParamsLayout, WORKGROUP_SIZE};
+
+/// Size of the shader's `Params` struct.
+pub(crate) const PARAMS_SIZE: u32 = std::mem::size_of::<Params>() as u32;
+
+/// Size of the shader's `Count` struct.
+pub(crate) const COUNT_SIZE: u64 = std::mem::size_of::<Count>() as u64;
+
+/// Size of the `u32` NaN counter a counting kernel takes at binding 4.
+pub(crate) const NAN_COUNTER_SIZE: u64 = std::mem::size_of::<u32>() as u64;
+
+/// Size of the shader's `Refinement` struct.
+pub(crate) const REFINEMENT_SIZE: u32 = std::mem::size_of::<Refinement>() as u32;
+
+/// Size of the shader's `Layout` struct.
+pub(crate) const LAYOUT_SIZE: u32 = std::mem::size_of::<Layout>() as u32;
+
+/// Size of the shader's `Clamp` struct.
+pub(crate) const CLAMP_SIZE: u32 = std::mem::size_of::<Clamp>() as u32;
+
+/// Size of the shader's `Grid` struct.
+pub(crate) const GRID_SIZE: u32 = std::mem::size_of::<Grid>() as u32;
+
+/// Size of the shader's `Rows` struct.
+pub(crate) const ROWS_SIZE: u32 = std::mem::size_of::<Rows>() as u32;
+
+/// The most push constant bytes a built-in kernel takes, which devices
+/// with push constants are asked for.
+pub(crate) const MAX_PARAMS_SIZE: u32 = {
+    let mut max = PARAMS_SIZE;
+    if LAYOUT_SIZE > max {
+        max = LAYOUT_SIZE;
+    }
+    if REFINEMENT_SIZE > max {
+        max = REFINEMENT_SIZE;
+    }
+    if CLAMP_SIZE > max {
+        max = CLAMP_SIZE;
+    }
+    if GRID_SIZE > max {
+        max = GRID_SIZE;
+    }
+    if ROWS_SIZE > max {
+        max = ROWS_SIZE;
+    }
+    max
+};
+
+/// A compute kernel that the context can build a pipeline for.
+///
+/// The entry point must take a read-only input storage buffer at set 0,
+/// binding 0, and a storage buffer of the same length to write its results
+/// to at binding 1, and run one invocation per element. Pipelines are
+/// cached by the content of [`GpuKernel::spirv`] and the entry point, so
+/// implementations are cheap to construct on every call. Kernels are shared
+/// with the context across threads, hence `Sync`.
+pub trait GpuKernel: Sync {
+    /// The SPIR-V module containing the entry point. It is passed to the
+    /// driver as is where the device supports SPIR-V passthrough, and
+    /// translated, with validation, otherwise.
+    fn spirv(&self) -> &[u8];
+    /// Name of the entry point within [`GpuKernel::spirv`].
+    fn entry_point(&self) -> &str;
+    /// The same kernel in WGSL, with the same entry point and bindings,
+    /// used instead of translating [`GpuKernel::spirv`] on devices without
+    /// SPIR-V passthrough.
+    fn wgsl(&self) -> Option<&str> {
+        None
+    }
+    /// Invocations per workgroup, as declared by the entry point.
+    fn workgroup_size(&self) -> u32;
+    /// Elements each invocation covers, so that dispatches launch one
+    /// workgroup per `workgroup_size * elements_per_invocation` elements.
+    fn elements_per_invocation(&self) -> u32 {
+        1
+    }
+    /// Whether each invocation loops over the elements a whole dispatch
+    /// apart, so that any number of workgroups covers the input. The
+    /// context then launches a fixed number of them, however large the
+    /// input.
+    fn grid_stride(&self) -> bool {
+        false
+    }
+    /// Whether the entry point takes the number of elements in the
+    /// dispatch as a uniform `u32` at binding 3, to return early from the
+    /// invocations of the last workgroup past the end. The context sets it
+    /// on every dispatch, each chunk of a split input included.
+    fn element_count(&self) -> bool {
+        false
+    }
+    /// For an entry point over a matrix of rows this many elements long,
+    /// declared with [`WORKGROUP_WIDTH`](crate::WORKGROUP_WIDTH) by
+    /// [`WORKGROUP_HEIGHT`](crate::WORKGROUP_HEIGHT) invocations: dispatches then span the rows
+    /// along x and the rows themselves along y, and split inputs only
+    /// between rows.
+    fn row_len(&self) -> Option<u32> {
+        None
+    }
+    /// Whether the entry point takes its element index from both
+    /// dimensions of the dispatch, as `id.y * num_workgroups.x *
+    /// workgroup_size + id.x`, so that inputs past
+    /// `max_compute_workgroups_per_dimension` workgroups spill into y
+    /// instead of being split into more dispatches. Indices past the
+    /// element count must return early, as the last row may be partial.
+    fn spills_into_y(&self) -> bool {
+        false
+    }
+}
+
+macro_rules! wgsl {
+    ($entry_point:literal) => {
+        concat!(
+            include_str!(concat!(env!("OUT_DIR"), "/wgsl/inverse_sqrt.wgsl")),
+            include_str!(concat!(env!("OUT_DIR"), "/wgsl/", $entry_point, ".wgsl")),
+        )
+    };
+}
+
+/// A compute kernel built into the crate's shader modules.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Kernel {
+    /// `1 / sqrt(x)`, with zero mapped to NaN.
+    InverseSqrt,
+    /// [`Kernel::InverseSqrt`] with every invocation covering four
+    /// consecutive elements as one vector, for more memory bandwidth, and
+    /// the same results.
+    InverseSqrtVec4,
+    /// [`Kernel::InverseSqrt`] with every invocation looping over the
+    /// input, so that inputs of any length fit one bounded dispatch.
+    InverseSqrtGridStride,
+    /// `1 / sqrt(x)` approximated by the Quake III bit hack and one Newton
+    /// step, within about 0.2% of [`Kernel::InverseSqrt`] for normal
+    /// inputs, with the same NaNs. Subnormal inputs are not refined.
+    FastInverseSqrt,
+    /// `1 / sqrt(x)` by the shading language's own `inversesqrt`, which
+    /// some GPUs answer with a fast estimate less accurate than
+    /// [`Kernel::InverseSqrt`], with the same NaNs. See
+    /// [`Precision::Fast`](crate::Precision::Fast).
+    NativeInverseSqrt,
+    /// `sqrt(x)`, with negative inputs mapped to NaN like
+    /// [`Kernel::InverseSqrt`]. Zeros map to themselves, so the
+    /// [`ZeroPolicy`](crate::ZeroPolicy) doesn't apply.
+    Sqrt,
+    /// `1 / x`, with zeros mapped to NaN like [`Kernel::InverseSqrt`],
+    /// subject to the [`ZeroPolicy`](crate::ZeroPolicy). Negative inputs
+    /// have reciprocals of their own.
+    Reciprocal,
+    /// `1 / (x * x)`, with zeros mapped to NaN and negative inputs allowed
+    /// like [`Kernel::Reciprocal`]. Results too large for an `f32` are
+    /// `+inf`.
+    InverseSquare,
+    /// The number of steps the Collatz sequence from each `u32` element
+    /// takes to reach 1, capped at 1000, which 0 and sequences that would
+    /// overflow get. Run it through
+    /// [`GpuContext::run_compute_shader`](crate::GpuContext::run_compute_shader)
+    /// over `u32`s.
+    Collatz,
+}
+
+impl GpuKernel for Kernel {
+    fn spirv(&self) -> &[u8] {
+        match self {
+            Kernel::InverseSqrt => include_bytes!(env!("main_cs.spv")),
+            Kernel::InverseSqrtVec4 => include_bytes!(env!("main_cs_vec4.spv")),
+            Kernel::InverseSqrtGridStride => include_bytes!(env!("main_cs_grid_stride.spv")),
+            Kernel::FastInverseSqrt => include_bytes!(env!("fast_rsqrt_cs.spv")),
+            Kernel::NativeInverseSqrt => include_bytes!(env!("rsqrt_fast_native_cs.spv")),
+            Kernel::Sqrt => include_bytes!(env!("sqrt_cs.spv")),
+            Kernel::Reciprocal => include_bytes!(env!("reciprocal_cs.spv")),
+            Kernel::InverseSquare => include_bytes!(env!("inv_square_cs.spv")),
+            Kernel::Collatz => include_bytes!(env!("collatz_cs.spv")),
+        }
+    }
+
+    fn entry_point(&self) -> &str {
+        match self {
+            Kernel::InverseSqrt => "main_cs",
+            Kernel::InverseSqrtVec4 => "main_cs_vec4",
+            Kernel::InverseSqrtGridStride => "main_cs_grid_stride",
+            Kernel::FastInverseSqrt => "fast_rsqrt_cs",
+            Kernel::NativeInverseSqrt => "rsqrt_fast_native_cs",
+            Kernel::Sqrt => "sqrt_cs",
+            Kernel::Reciprocal => "reciprocal_cs",
+            Kernel::InverseSquare => "inv_square_cs",
+            Kernel::Collatz => "collatz_cs",
+        }
+    }
+
+    fn wgsl(&self) -> Option<&str> {
+        match self {
+            Kernel::InverseSqrt => Some(wgsl!("main_cs")),
+            Kernel::InverseSqrtVec4 => Some(wgsl!("main_cs_vec4")),
+            Kernel::InverseSqrtGridStride => Some(wgsl!("main_cs_grid_stride")),
+            Kernel::FastInverseSqrt => Some(wgsl!("fast_rsqrt_cs")),
+            Kernel::NativeInverseSqrt => Some(wgsl!("rsqrt_fast_native_cs")),
+            Kernel::Sqrt => Some(wgsl!("sqrt_cs")),
+            Kernel::Reciprocal => Some(wgsl!("reciprocal_cs")),
+            Kernel::InverseSquare => Some(wgsl!("inv_square_cs")),
+            Kernel::Collatz => Some(wgsl!("collatz_cs")),
+        }
+    }
+
+    fn workgroup_size(&self) -> u32 {
+        WORKGROUP_SIZE
+    }
+
+    fn elements_per_invocation(&self) -> u32 {
+        match self {
+            Kernel::InverseSqrtVec4 => 4,
+            _ => 1,
+        }
+    }
+
+    fn grid_stride(&self) -> bool {
+        *self == Kernel::InverseSqrtGridStride
+    }
+
+    fn element_count(&self) -> bool {
+        true
+    }
+
+    fn spills_into_y(&self) -> bool {
+        !matches!(
+            self,
+            Kernel::InverseSqrtVec4 | Kernel::InverseSqrtGridStride
+        )
+    }
+}
+
+/// `scale / sqrt(x)`, reading `Params` from push constants or, on devices
+/// without them, from a uniform buffer at binding 2.
+pub(crate) struct Scaled {
+    pub(crate) push_constants: bool,
+}
+
+impl Scaled {
+    pub(crate) fn params_layout(&self) -> ParamsLayout {
+        if self.push_constants {
+            ParamsLayout::PushConstants(PARAMS_SIZE)
+        } else {
+            ParamsLayout::Uniform(PARAMS_SIZE as u64)
+        }
+    }
+}
+
+impl GpuKernel for Scaled {
+    fn spirv(&self) -> &[u8] {
+        if self.push_constants {
+            include_bytes!(env!("main_cs_scaled.spv"))
+        } else {
+            include_bytes!(env!("main_cs_scaled_uniform.spv"))
+        }
+    }
+
+    fn entry_point(&self) -> &str {
+        if self.push_constants {
+            "main_cs_scaled"
+        } else {
+            "main_cs_scaled_uniform"
+        }
+    }
+
+    fn wgsl(&self) -> Option<&str> {
+        Some(if self.push_constants {
+            wgsl!("main_cs_scaled")
+        } else {
+            wgsl!("main_cs_scaled_uniform")
+        })
+    }
+
+    fn workgroup_size(&self) -> u32 {
+        WORKGROUP_SIZE
+    }
+
+    fn element_count(&self) -> bool {
+        true
+    }
+}
+
+/// `1 / sqrt(max(x, epsilon))`, passing the `Clamp` like [`Scaled`]'s
+/// parameters.
+pub(crate) struct Clamped {
+    pub(crate) push_constants: bool,
+}
+
+impl Clamped {
+    pub(crate) fn params_layout(&self) -> ParamsLayout {
+        if self.push_constants {
+            ParamsLayout::PushConstants(CLAMP_SIZE)
+        } else {
+            ParamsLayout::Uniform(CLAMP_SIZE as u64)
+        }
+    }
+}
+
+impl GpuKernel for Clamped {
+    fn spirv(&self) -> &[u8] {
+        if self.push_constants {
+            include_bytes!(env!("rsqrt_clamped_cs.spv"))
+        } else {
+            include_bytes!(env!("rsqrt_clamped_cs_uniform.spv"))
+        }
+    }
+
+    fn entry_point(&self) -> &str {
+        if self.push_constants {
+            "rsqrt_clamped_cs"
+        } else {
+            "rsqrt_clamped_cs_uniform"
+        }
+    }
+
+    fn wgsl(&self) -> Option<&str> {
+        Some(if self.push_constants {
+            wgsl!("rsqrt_clamped_cs")
+        } else {
+            wgsl!("rsqrt_clamped_cs_uniform")
+        })
+    }
+
+    fn workgroup_size(&self) -> u32 {
+        WORKGROUP_SIZE
+    }
+
+    fn element_count(&self) -> bool {
+        true
+    }
+}
+
+/// `1 / sqrt(x)` over a matrix with rows of `width` elements, passing the
+/// width like [`Scaled`]'s parameters.
+pub(crate) struct Matrix {
+    pub(crate) push_constants: bool,
+    pub(crate) width: u32,
+}
+
+impl Matrix {
+    pub(crate) fn params_layout(&self) -> ParamsLayout {
+        if self.push_constants {
+            ParamsLayout::PushConstants(GRID_SIZE)
+        } else {
+            ParamsLayout::Uniform(GRID_SIZE as u64)
+        }
+    }
+}
+
+impl GpuKernel for Matrix {
+    fn spirv(&self) -> &[u8] {
+        if self.push_constants {
+            include_bytes!(env!("main_cs_2d.spv"))
+        } else {
+            include_bytes!(env!("main_cs_2d_uniform.spv"))
+        }
+    }
+
+    fn entry_point(&self) -> &str {
+        if self.push_constants {
+            "main_cs_2d"
+        } else {
+            "main_cs_2d_uniform"
+        }
+    }
+
+    fn wgsl(&self) -> Option<&str> {
+        Some(if self.push_constants {
+            wgsl!("main_cs_2d")
+        } else {
+            wgsl!("main_cs_2d_uniform")
+        })
+    }
+
+    fn workgroup_size(&self) -> u32 {
+        WORKGROUP_SIZE
+    }
+
+    fn element_count(&self) -> bool {
+        true
+    }
+
+    fn row_len(&self) -> Option<u32> {
+        Some(self.width)
+    }
+}
+
+/// `1 / sqrt(x)` for the elements a `Layout` selects, copying the others
+/// through, with the layout passed like [`Scaled`]'s parameters.
+pub(crate) struct Strided {
+    pub(crate) push_constants: bool,
+}
+
+impl Strided {
+    pub(crate) fn params_layout(&self) -> ParamsLayout {
+        if self.push_constants {
+            ParamsLayout::PushConstants(LAYOUT_SIZE)
+        } else {
+            ParamsLayout::Uniform(LAYOUT_SIZE as u64)
+        }
+    }
+}
+
+impl GpuKernel for Strided {
+    fn spirv(&self) -> &[u8] {
+        if self.push_constants {
+            include_bytes!(env!("main_cs_strided.spv"))
+        } else {
+            include_bytes!(env!("main_cs_strided_uniform.spv"))
+        }
+    }
+
+    fn entry_point(&self) -> &str {
+        if self.push_constants {
+            "main_cs_strided"
+        } else {
+            "main_cs_strided_uniform"
+        }
+    }
+
+    fn wgsl(&self) -> Option<&str> {
+        Some(if self.push_constants {
+            wgsl!("main_cs_strided")
+        } else {
+            wgsl!("main_cs_strided_uniform")
+        })
+    }
+
+    fn workgroup_size(&self) -> u32 {
+        WORKGROUP_SIZE
+    }
+
+    fn element_count(&self) -> bool {
+        true
+    }
+}
+
+/// [`Kernel::InverseSqrt`] that also counts the NaNs it writes in an
+/// atomic `u32` storage buffer at binding 4. Its pipeline comes from
+/// `DeviceState::counting_pipeline`.
+pub(crate) struct CountedInverseSqrt;
+
+impl GpuKernel for CountedInverseSqrt {
+    fn spirv(&self) -> &[u8] {
+        include_bytes!(env!("main_cs_nan_count.spv"))
+    }
+
+    fn entry_point(&self) -> &str {
+        "main_cs_nan_count"
+    }
+
+    fn wgsl(&self) -> Option<&str> {
+        Some(wgsl!("main_cs_nan_count"))
+    }
+
+    fn workgroup_size(&self) -> u32 {
+        WORKGROUP_SIZE
+    }
+
+    fn element_count(&self) -> bool {
+        true
+    }
+}
+
+/// `[x, y, z]` triples scaled to unit length, one invocation per triple,
+/// so dispatched with 12-byte elements.
+pub(crate) struct Normalize3;
+
+impl GpuKernel for Normalize3 {
+    fn spirv(&self) -> &[u8] {
+        include_bytes!(env!("normalize3_cs.spv"))
+    }
+
+    fn entry_point(&self) -> &str {
+        "normalize3_cs"
+    }
+
+    fn wgsl(&self) -> Option<&str> {
+        Some(wgsl!("normalize3_cs"))
+    }
+
+    fn workgroup_size(&self) -> u32 {
+        WORKGROUP_SIZE
+    }
+
+    fn element_count(&self) -> bool {
+        true
+    }
+}
+
+/// `[re, im]` pairs scaled to unit magnitude, one invocation per pair, so
+/// dispatched with 8-byte elements.
+pub(crate) struct NormalizeComplex;
+
+impl GpuKernel for NormalizeComplex {
+    fn spirv(&self) -> &[u8] {
+        include_bytes!(env!("normalize_complex_cs.spv"))
+    }
+
+    fn entry_point(&self) -> &str {
+        "normalize_complex_cs"
+    }
+
+    fn wgsl(&self) -> Option<&str> {
+        Some(wgsl!("normalize_complex_cs"))
+    }
+
+    fn workgroup_size(&self) -> u32 {
+        WORKGROUP_SIZE
+    }
+
+    fn element_count(&self) -> bool {
+        true
+    }
+}
+
+/// Rows scaled to unit length, one workgroup per row that its invocations
+/// loop over, taking the row length from push constants or, without them,
+/// from a uniform buffer at binding 2. Dispatches launch one workgroup per
+/// [`WORKGROUP_SIZE`] elements, so they are sized by rows times that.
+pub(crate) struct NormalizeRows {
+    pub(crate) push_constants: bool,
+}
+
+impl NormalizeRows {
+    pub(crate) fn params_layout(&self) -> ParamsLayout {
+        if self.push_constants {
+            ParamsLayout::PushConstants(ROWS_SIZE)
+        } else {
+            ParamsLayout::Uniform(ROWS_SIZE as u64)
+        }
+    }
+}
+
+impl GpuKernel for NormalizeRows {
+    fn spirv(&self) -> &[u8] {
+        if self.push_constants {
+            include_bytes!(env!("normalize_rows_cs.spv"))
+        } else {
+            include_bytes!(env!("normalize_rows_cs_uniform.spv"))
+        }
+    }
+
+    fn entry_point(&self) -> &str {
+        if self.push_constants {
+            "normalize_rows_cs"
+        } else {
+            "normalize_rows_cs_uniform"
+        }
+    }
+
+    fn wgsl(&self) -> Option<&str> {
+        Some(if self.push_constants {
+            wgsl!("normalize_rows_cs")
+        } else {
+            wgsl!("normalize_rows_cs_uniform")
+        })
+    }
+
+    fn workgroup_size(&self) -> u32 {
+        WORKGROUP_SIZE
+    }
+}
+
+/// Bytes packed four to a word, each decoded as `x / 255` before its
+/// inverse square root, one invocation per word writing four floats.
+pub(crate) struct Unorm8;
+
+impl GpuKernel for Unorm8 {
+    fn spirv(&self) -> &[u8] {
+        include_bytes!(env!("rsqrt_unorm8_cs.spv"))
+    }
+
+    fn entry_point(&self) -> &str {
+        "rsqrt_unorm8_cs"
+    }
+
+    fn wgsl(&self) -> Option<&str> {
+        Some(wgsl!("rsqrt_unorm8_cs"))
+    }
+
+    fn workgroup_size(&self) -> u32 {
+        WORKGROUP_SIZE
+    }
+
+    fn element_count(&self) -> bool {
+        true
+    }
+}
+
+/// Per-workgroup minimum, maximum, sum and NaN count of the input, as four
+/// floats per [`REDUCE_GROUP_ELEMENTS`](crate::stats::REDUCE_GROUP_ELEMENTS)
+/// elements.
+///
+/// The lanes are combined through workgroup memory on every adapter: wgpu
+/// has no feature to detect subgroup operations with, and neither naga nor
+/// `spirv-std` can express them yet.
+pub(crate) struct Reduce;
+
+impl GpuKernel for Reduce {
+    fn spirv(&self) -> &[u8] {
+        include_bytes!(env!("reduce_cs.spv"))
+    }
+
+    fn entry_point(&self) -> &str {
+        "reduce_cs"
+    }
+
+    fn wgsl(&self) -> Option<&str> {
+        Some(wgsl!("reduce_cs"))
+    }
+
+    fn workgroup_size(&self) -> u32 {
+        WORKGROUP_SIZE
+    }
+
+    fn elements_per_invocation(&self) -> u32 {
+        4
+    }
+
+    fn element_count(&self) -> bool {
+        true
+    }
+}
+
+/// Per-workgroup largest relative error of the results in the first half
+/// of the input against the reference in the second, and the index it was
+/// found at, as two `u32`s per
+/// [`REDUCE_GROUP_ELEMENTS`](crate::stats::REDUCE_GROUP_ELEMENTS) results.
+pub(crate) struct Compare;
+
+impl GpuKernel for Compare {
+    fn spirv(&self) -> &[u8] {
+        include_bytes!(env!("compare_cs.spv"))
+    }
+
+    fn entry_point(&self) -> &str {
+        "compare_cs"
+    }
+
+    fn wgsl(&self) -> Option<&str> {
+        Some(wgsl!("compare_cs"))
+    }
+
+    fn workgroup_size(&self) -> u32 {
+        WORKGROUP_SIZE
+    }
+
+    fn elements_per_invocation(&self) -> u32 {
+        4
+    }
+
+    fn element_count(&self) -> bool {
+        true
+    }
+}
+
+/// Each invocation's index within its workgroup, over `u32`s.
+pub(crate) struct WorkgroupProbe;
+
+impl GpuKernel for WorkgroupProbe {
+    fn spirv(&self) -> &[u8] {
+        include_bytes!(env!("workgroup_probe_cs.spv"))
+    }
+
+    fn entry_point(&self) -> &str {
+        "workgroup_probe_cs"
+    }
+
+    fn wgsl(&self) -> Option<&str> {
+        Some(wgsl!("workgroup_probe_cs"))
+    }
+
+    fn workgroup_size(&self) -> u32 {
+        WORKGROUP_SIZE
+    }
+
+    fn element_count(&self) -> bool {
+        true
+    }
+}
+
+/// The bit hack's estimate of `1 / sqrt(x)` refined by a number of Newton
+/// steps, which is passed like [`Scaled`]'s parameters.
+pub(crate) struct Refined {
+    pub(crate) push_constants: bool,
+}
+
+impl Refined {
+    pub(crate) fn params_layout(&self) -> ParamsLayout {
+        if self.push_constants {
+            ParamsLayout::PushConstants(REFINEMENT_SIZE)
+        } else {
+            ParamsLayout::Uniform(REFINEMENT_SIZE as u64)
+        }
+    }
+}
+
+impl GpuKernel for Refined {
+    fn spirv(&self) -> &[u8] {
+        if self.push_constants {
+            include_bytes!(env!("rsqrt_newton_cs.spv"))
+        } else {
+            include_bytes!(env!("rsqrt_newton_cs_uniform.spv"))
+        }
+    }
+
+    fn entry_point(&self) -> &str {
+        if self.push_constants {
+            "rsqrt_newton_cs"
+        } else {
+            "rsqrt_newton_cs_uniform"
+        }
+    }
+
+    fn wgsl(&self) -> Option<&str> {
+        Some(if self.push_constants {
+            wgsl!("rsqrt_newton_cs")
+        } else {
+            wgsl!("rsqrt_newton_cs_uniform")
+        })
+    }
+
+    fn workgroup_size(&self) -> u32 {
+        WORKGROUP_SIZE
+    }
+
+    fn element_count(&self) -> bool {
+        true
+    }
+}
+
+/// `1 / sqrt(x)` over pairs of `f16` elements packed into `u32` words,
+/// computed in `f32` by the shader. Unpacking needs no `Float16`
+/// capability, which wgpu can't request, so it runs on any device.
+#[cfg(feature = "f16")]
+pub(crate) struct InverseSqrtF16;
+
+#[cfg(feature = "f16")]
+impl GpuKernel for InverseSqrtF16 {
+    fn spirv(&self) -> &[u8] {
+        include_bytes!(env!("main_cs_f16.spv"))
+    }
+
+    fn entry_point(&self) -> &str {
+        "main_cs_f16"
+    }
+
+    fn wgsl(&self) -> Option<&str> {
+        Some(wgsl!("main_cs_f16"))
+    }
+
+    fn workgroup_size(&self) -> u32 {
+        WORKGROUP_SIZE
+    }
+
+    fn element_count(&self) -> bool {
+        true
+    }
+}
+
+/// `1 / sqrt(x)` over `f64` elements, like [`Kernel::InverseSqrt`] with
+/// zeros mapped to NaN. It has no WGSL, which naga can't parse `f64` in,
+/// and runs only on devices with `Features::SHADER_FLOAT64`.
+#[cfg(feature = "f64")]
+pub(crate) struct InverseSqrtF64;
+
+#[cfg(feature = "f64")]
+impl GpuKernel for InverseSqrtF64 {
+    fn spirv(&self) -> &[u8] {
+        include_bytes!(env!("main_cs_f64.spv"))
+    }
+
+    fn entry_point(&self) -> &str {
+        "main_cs_f64"
+    }
+
+    fn workgroup_size(&self) -> u32 {
+        WORKGROUP_SIZE
+    }
+
+    fn element_count(&self) -> bool {
+        true
+    }
+}
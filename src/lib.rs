@@ -0,0 +1,95 @@
+//! Inverse square root computed on the GPU by a [rust-gpu](https://github.com/EmbarkStudios/rust-gpu)
+//! shader, driven through [wgpu](https://github.com/gfx-rs/wgpu).
+//!
+//! Each context polls its device on a thread of its own, so the futures
+//! returned here run on any executor without blocking it;
+//! [`compute_blocking`] needs none at all.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), demo_wgpu_compute::ComputeError> {
+//! let output = demo_wgpu_compute::inverse_sqrt(&[4., 25., 100.]).await?;
+//! assert_eq!(output, [0.5, 0.2, 0.1]);
+//! # Ok(())
+//! # }
+//! ```
+
+mod batch;
+mod context;
+mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "global-context")]
+pub mod global;
+mod gpu_vec;
+mod indirect;
+mod kernel;
+mod mapped;
+mod multi;
+mod options;
+mod poller;
+mod pool;
+mod progress;
+mod report;
+mod stats;
+mod stream;
+mod timeout;
+mod verify;
+
+pub use context::{list_adapters, GpuContext};
+pub use error::{ComputeError, InitError};
+pub use gpu_vec::GpuVec;
+#[cfg(feature = "f16")]
+pub use half::f16;
+pub use indirect::IndirectArgs;
+pub use inverse_sqrt_shared::{
+    DispatchIndirect, WORKGROUP_HEIGHT, WORKGROUP_SIZE, WORKGROUP_WIDTH,
+};
+pub use kernel::{GpuKernel, Kernel};
+pub use mapped::MappedResults;
+pub use multi::MultiGpuContext;
+pub use options::{AdapterSelector, ComputeOptions, NanPolicy, Precision, ZeroPolicy};
+pub use pool::PoolStats;
+pub use progress::ProgressInfo;
+pub use report::ComputeReport;
+pub use stats::Stats;
+pub use stream::ComputeHandle;
+pub use verify::VerifyReport;
+pub use wgpu::{AdapterInfo, Backend, Backends, DeviceType, Features, Limits, PowerPreference};
+
+/// Computes `1 / sqrt(x)` for every element of `input` on the GPU.
+///
+/// Negative inputs map to NaN, and zeros to infinity with their sign, as
+/// IEEE 754 has it, unless the [`ZeroPolicy`] says otherwise. The shader runs workgroups
+/// of [`WORKGROUP_SIZE`] invocations, one invocation per element; an empty
+/// `input` yields an empty output without a dispatch. Each call acquires
+/// its own adapter and device; create a [`GpuContext`] once to
+/// avoid paying that on every call, or enable the `global-context` feature
+/// to share one across calls.
+pub async fn inverse_sqrt(input: &[f32]) -> Result<Vec<f32>, ComputeError> {
+    #[cfg(feature = "global-context")]
+    let ctx = global::context().await?;
+    #[cfg(not(feature = "global-context"))]
+    let ctx = GpuContext::new().await?;
+
+    ctx.compute(input).await
+}
+
+/// Blocking version of [`inverse_sqrt`], for callers without an async
+/// runtime.
+///
+/// The readback is awaited on the calling thread, which blocks until the
+/// GPU has finished.
+pub fn compute_blocking(input: &[f32]) -> Result<Vec<f32>, ComputeError> {
+    futures::executor::block_on(inverse_sqrt(input))
+}
+
+/// [`inverse_sqrt`] with explicit [`ComputeOptions`].
+pub async fn inverse_sqrt_with_options(
+    input: &[f32],
+    options: ComputeOptions,
+) -> Result<Vec<f32>, ComputeError> {
+    GpuContext::with_options(options)
+        .await?
+        .compute(input)
+        .await
+}
@@ -0,0 +1,89 @@
+use std::{ops::Deref, sync::Arc};
+
+use wgpu::BufferView;
+
+use crate::{
+    context::{validate, DISPATCH_BUFFERS},
+    ComputeError, GpuContext, Kernel,
+};
+
+/// Results read straight from the mapped readback buffer, without copying
+/// them to the host.
+///
+/// Borrows the context mutably, so it has to be dropped before the context
+/// can run [`GpuContext::compute_mapped`] again. Dropping it unmaps the
+/// buffer.
+pub struct MappedResults<'a> {
+    buffer: &'a wgpu::Buffer,
+    view: Option<BufferView<'a>>,
+}
+
+impl Deref for MappedResults<'_> {
+    type Target = [f32];
+
+    fn deref(&self) -> &[f32] {
+        bytemuck::cast_slice(self.view.as_ref().unwrap())
+    }
+}
+
+impl Drop for MappedResults<'_> {
+    fn drop(&mut self) {
+        // The view has to go before the buffer can be unmapped.
+        self.view = None;
+        self.buffer.unmap();
+    }
+}
+
+impl GpuContext {
+    /// Computes `1 / sqrt(x)` for every element of `input` and returns a
+    /// view over the mapped results.
+    ///
+    /// Input validation applies, but the zero policy does not: zeros come
+    /// out as NaN. The readback buffer is kept on the context and reused by
+    /// the next call if it is large enough. The results stay in one buffer,
+    /// so an input too large for a single dispatch fails with
+    /// [`ComputeError::TooLarge`], and one over the memory budget with
+    /// [`ComputeError::OutOfBudget`].
+    pub async fn compute_mapped(
+        &mut self,
+        input: &[f32],
+    ) -> Result<MappedResults<'_>, ComputeError> {
+        if self.options.validate_input {
+            validate(input)?;
+        }
+
+        let state = self.state();
+        state.check_fits(&Kernel::InverseSqrt, 4, input.len())?;
+        let size = std::mem::size_of_val(input) as wgpu::BufferAddress;
+        state.check_budget(DISPATCH_BUFFERS, size)?;
+        let storage_buffer = state.create_storage_buffer(bytemuck::cast_slice(input))?;
+        let output_buffer = state.create_output_buffer(size)?;
+        let mut encoder = state.create_command_encoder();
+        let pipeline = state.pipeline(&Kernel::InverseSqrt, 4)?;
+        state.encode_kernel(
+            &mut encoder,
+            &pipeline,
+            &storage_buffer,
+            &output_buffer,
+            input.len() as u32,
+        )?;
+
+        let readback = match self.mapped_readback.take() {
+            Some(readback) if Arc::ptr_eq(&readback.0, &state) && readback.2 >= size => readback,
+            _ => {
+                let buffer = state.create_readback_buffer(size)?;
+                (state, buffer, size)
+            }
+        };
+        readback
+            .0
+            .read_back_into(encoder, &output_buffer, &readback.1, size)
+            .await?;
+
+        let (_, buffer, _) = self.mapped_readback.insert(readback);
+        Ok(MappedResults {
+            buffer,
+            view: Some(buffer.slice(..size).get_mapped_range()),
+        })
+    }
+}
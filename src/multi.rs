@@ -0,0 +1,192 @@
+use std::ops::Range;
+
+use crate::{AdapterSelector, ComputeError, ComputeOptions, GpuContext, InitError};
+
+/// A [`GpuContext`] per adapter, splitting each input between them.
+///
+/// The input is divided into one contiguous shard per context, sized in
+/// proportion to its weight, and the shards run concurrently, each on a
+/// thread of its own. The results are stitched back together in input
+/// order, so they are the same as from a single context.
+pub struct MultiGpuContext {
+    contexts: Vec<GpuContext>,
+    weights: Vec<f64>,
+    rerun_failed_shards: bool,
+}
+
+impl MultiGpuContext {
+    /// A context on every adapter of the default backends.
+    pub async fn new() -> Result<Self, ComputeError> {
+        Self::with_options(ComputeOptions::default()).await
+    }
+
+    /// A context on every adapter of `options`' backends, each created
+    /// with `options` and the adapter selector replaced.
+    pub async fn with_options(options: ComputeOptions) -> Result<Self, ComputeError> {
+        let backends = options.backends_or_default();
+        let adapters = wgpu::Instance::new(backends)
+            .enumerate_adapters(backends)
+            .count();
+        if adapters == 0 {
+            return Err(InitError::NoAdapter {
+                requested_backends: backends,
+                available: Vec::new(),
+            }
+            .into());
+        }
+
+        let mut contexts = Vec::with_capacity(adapters);
+        for index in 0..adapters {
+            let options = options.clone().adapter(AdapterSelector::Index(index));
+            contexts.push(GpuContext::with_options(options).await?);
+        }
+        Ok(Self::from_contexts(contexts))
+    }
+
+    /// Splits inputs between `contexts`, with equal weights.
+    ///
+    /// # Panics
+    ///
+    /// If `contexts` is empty.
+    pub fn from_contexts(contexts: Vec<GpuContext>) -> Self {
+        assert!(!contexts.is_empty(), "contexts must not be empty");
+        MultiGpuContext {
+            weights: vec![1.; contexts.len()],
+            contexts,
+            rerun_failed_shards: false,
+        }
+    }
+
+    /// The share of each input given to each context, in the order of
+    /// [`MultiGpuContext::contexts`]. Only the ratios matter.
+    ///
+    /// # Panics
+    ///
+    /// If there isn't one weight per context, any weight is negative or
+    /// not finite, or they are all zero.
+    pub fn weights(mut self, weights: &[f64]) -> Self {
+        assert_eq!(weights.len(), self.contexts.len(), "one weight per context");
+        assert!(
+            weights
+                .iter()
+                .all(|weight| weight.is_finite() && *weight >= 0.),
+            "weights must be finite and not negative"
+        );
+        assert!(
+            weights.iter().any(|weight| *weight > 0.),
+            "weights must not all be zero"
+        );
+        self.weights = weights.to_vec();
+        self
+    }
+
+    /// Run a shard that failed again on the other contexts, in order,
+    /// instead of failing the whole call. Defaults to false.
+    pub fn rerun_failed_shards(mut self, rerun_failed_shards: bool) -> Self {
+        self.rerun_failed_shards = rerun_failed_shards;
+        self
+    }
+
+    /// The contexts inputs are split between.
+    pub fn contexts(&self) -> &[GpuContext] {
+        &self.contexts
+    }
+
+    /// Computes `1 / sqrt(x)` for every element of `input`, split between
+    /// the contexts.
+    ///
+    /// Each context applies its own options to its shard, and a validation
+    /// error reports the index into the whole input. The calling task is
+    /// blocked while the shards run.
+    pub async fn compute(&self, input: &[f32]) -> Result<Vec<f32>, ComputeError> {
+        let shards = self.shards(input.len());
+        let results = std::thread::scope(|scope| {
+            let threads = shards
+                .iter()
+                .enumerate()
+                .filter(|(_, shard)| !shard.is_empty())
+                .map(|(index, shard)| {
+                    let ctx = &self.contexts[index];
+                    let shard = &input[shard.clone()];
+                    (
+                        index,
+                        scope.spawn(move || futures::executor::block_on(ctx.compute(shard))),
+                    )
+                })
+                .collect::<Vec<_>>();
+            threads
+                .into_iter()
+                .map(|(index, thread)| (index, thread.join().expect("shard thread panicked")))
+                .collect::<Vec<_>>()
+        });
+
+        let mut output = Vec::with_capacity(input.len());
+        for (index, result) in results {
+            let shard = shards[index].clone();
+            let result = match result {
+                // Invalid input fails the same way everywhere.
+                Err(err)
+                    if self.rerun_failed_shards
+                        && !matches!(err, ComputeError::InvalidInput { .. }) =>
+                {
+                    self.rerun(index, &input[shard.clone()], err).await
+                }
+                result => result,
+            };
+            output.extend(result.map_err(|err| offset_index(err, shard.start))?);
+        }
+        Ok(output)
+    }
+
+    /// Contiguous ranges of `len` elements, one per context, sized by
+    /// weight.
+    fn shards(&self, len: usize) -> Vec<Range<usize>> {
+        let total = self.weights.iter().sum::<f64>();
+        let mut cumulative = 0.;
+        let mut start = 0;
+        self.weights
+            .iter()
+            .enumerate()
+            .map(|(index, weight)| {
+                cumulative += weight;
+                let end = if index + 1 == self.weights.len() {
+                    len
+                } else {
+                    ((len as f64 * cumulative / total) as usize).min(len)
+                };
+                let shard = start..end.max(start);
+                start = shard.end;
+                shard
+            })
+            .collect()
+    }
+
+    /// Runs the shard that failed on context `failed` on every other
+    /// context in turn until one succeeds, or returns the first error.
+    async fn rerun(
+        &self,
+        failed: usize,
+        shard: &[f32],
+        err: ComputeError,
+    ) -> Result<Vec<f32>, ComputeError> {
+        for (index, ctx) in self.contexts.iter().enumerate() {
+            if index != failed {
+                if let Ok(output) = ctx.compute(shard).await {
+                    return Ok(output);
+                }
+            }
+        }
+        Err(err)
+    }
+}
+
+/// `err` with an index into a shard turned into an index into the input.
+fn offset_index(err: ComputeError, start: usize) -> ComputeError {
+    match err {
+        ComputeError::InvalidInput { index, value } => ComputeError::InvalidInput {
+            index: start + index,
+            value,
+        },
+        err => err,
+    }
+}
@@ -0,0 +1,487 @@
+#[cfg(feature = "trace")]
+use std::path::PathBuf;
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use wgpu::{Backends, Features, PowerPreference};
+
+use crate::{progress::ProgressHook, ComputeError, GpuContext, ProgressInfo};
+
+/// What a zero input turns into. Negative inputs always become NaN, as
+/// written by the shader; both zeros follow the policy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ZeroPolicy {
+    /// `1 / sqrt(±0)` becomes NaN, as written by the shader. The default
+    /// before zeros followed IEEE 754, kept for callers that rely on it.
+    Nan,
+    /// `1 / sqrt(±0)` becomes `0.0`.
+    Zero,
+    /// `1 / sqrt(±0)` becomes infinity with the sign of the zero, as IEEE
+    /// 754 has it: `+inf` for `+0.0` and `-inf` for `-0.0`.
+    #[default]
+    Infinity,
+}
+
+impl ZeroPolicy {
+    /// What the shader's NaN for the zero `case` is replaced with, if
+    /// anything.
+    pub(crate) fn replace(self, case: f32) -> Option<f32> {
+        match self {
+            ZeroPolicy::Nan => None,
+            ZeroPolicy::Zero => Some(0.),
+            ZeroPolicy::Infinity => Some(f32::INFINITY.copysign(case)),
+        }
+    }
+}
+
+/// What NaN results turn into, once the [`ZeroPolicy`] has been applied.
+///
+/// NaN inputs, negative inputs including negative infinity, and zeros under
+/// [`ZeroPolicy::Nan`] all give NaN on every backend. The policy covers
+/// [`GpuContext::compute`](crate::GpuContext::compute),
+/// [`GpuContext::compute_with`](crate::GpuContext::compute_with),
+/// [`GpuContext::compute_into`](crate::GpuContext::compute_into),
+/// [`GpuContext::compute_read_into`](crate::GpuContext::compute_read_into)
+/// and [`GpuContext::compute_with_report`](crate::GpuContext::compute_with_report).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NanPolicy {
+    /// NaN results are returned as they are.
+    #[default]
+    Propagate,
+    /// NaN results become `0.0`.
+    ZeroFill,
+    /// Any NaN result fails the call with
+    /// [`ComputeError::NanInOutput`](crate::ComputeError::NanInOutput).
+    Error,
+}
+
+/// How [`GpuContext::compute`](crate::GpuContext::compute) takes the
+/// inverse square root.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Precision {
+    /// `1 / sqrt(x)`, by [`Kernel::InverseSqrt`](crate::Kernel::InverseSqrt).
+    #[default]
+    Exact,
+    /// The shading language's `inversesqrt`, by
+    /// [`Kernel::NativeInverseSqrt`](crate::Kernel::NativeInverseSqrt).
+    /// How close it gets is up to the driver, within the two ulps Vulkan
+    /// and WebGPU allow; the exact path measures under one and a half.
+    Fast,
+}
+
+impl Precision {
+    pub(crate) fn kernel(self) -> crate::Kernel {
+        match self {
+            Precision::Exact => crate::Kernel::InverseSqrt,
+            Precision::Fast => crate::Kernel::NativeInverseSqrt,
+        }
+    }
+}
+
+/// Which adapter a context runs on.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum AdapterSelector {
+    /// Let wgpu pick, honoring the power preference.
+    #[default]
+    Auto,
+    /// The adapter at this position in [`list_adapters`](crate::list_adapters).
+    Index(usize),
+    /// The first adapter whose name contains this, ignoring case.
+    Name(String),
+}
+
+impl FromStr for AdapterSelector {
+    type Err = std::convert::Infallible;
+
+    /// `auto`, an index, or otherwise a name.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(if s.eq_ignore_ascii_case("auto") {
+            AdapterSelector::Auto
+        } else if let Ok(index) = s.parse() {
+            AdapterSelector::Index(index)
+        } else {
+            AdapterSelector::Name(s.to_owned())
+        })
+    }
+}
+
+/// Knobs for a [`GpuContext`](crate::GpuContext) and the dispatches it runs.
+///
+/// ```
+/// use demo_wgpu_compute::{ComputeOptions, PowerPreference};
+///
+/// let options = ComputeOptions::new()
+///     .label("mybatch")
+///     .power_preference(PowerPreference::HighPerformance)
+///     .validate_input(true);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ComputeOptions {
+    pub(crate) label: Option<String>,
+    pub(crate) debug: bool,
+    pub(crate) robust: bool,
+    pub(crate) backends: Option<Backends>,
+    pub(crate) power_preference: PowerPreference,
+    pub(crate) force_fallback_adapter: Option<bool>,
+    pub(crate) adapter: AdapterSelector,
+    pub(crate) required_features: Features,
+    pub(crate) disabled_features: Features,
+    pub(crate) max_binding_size: Option<u32>,
+    pub(crate) max_workgroups: Option<u32>,
+    pub(crate) memory_budget: Option<u64>,
+    pub(crate) downlevel_limits: bool,
+    pub(crate) use_adapter_limits: bool,
+    pub(crate) split_large_inputs: bool,
+    pub(crate) max_chunk_len: Option<usize>,
+    pub(crate) validate_input: bool,
+    pub(crate) zero_policy: ZeroPolicy,
+    pub(crate) nan_policy: NanPolicy,
+    pub(crate) precision: Precision,
+    pub(crate) nan_zero_vectors: bool,
+    pub(crate) clamp_negative: bool,
+    pub(crate) on_progress: Option<ProgressHook>,
+    pub(crate) staging_buffers: usize,
+    pub(crate) buffer_pool_size: u64,
+    pub(crate) max_retries: u32,
+    pub(crate) retry_backoff: Duration,
+    pub(crate) timeout: Duration,
+    #[cfg(feature = "trace")]
+    pub(crate) trace_dir: Option<PathBuf>,
+}
+
+impl Default for ComputeOptions {
+    fn default() -> Self {
+        ComputeOptions {
+            label: None,
+            debug: false,
+            robust: false,
+            backends: None,
+            power_preference: PowerPreference::default(),
+            force_fallback_adapter: None,
+            adapter: AdapterSelector::default(),
+            required_features: Features::empty(),
+            disabled_features: Features::empty(),
+            max_binding_size: None,
+            max_workgroups: None,
+            memory_budget: None,
+            downlevel_limits: false,
+            use_adapter_limits: false,
+            split_large_inputs: true,
+            max_chunk_len: None,
+            validate_input: false,
+            zero_policy: ZeroPolicy::default(),
+            nan_policy: NanPolicy::default(),
+            precision: Precision::default(),
+            nan_zero_vectors: false,
+            clamp_negative: true,
+            on_progress: None,
+            staging_buffers: 3,
+            buffer_pool_size: 256 << 20,
+            max_retries: 2,
+            retry_backoff: Duration::from_millis(100),
+            timeout: Duration::from_secs(30),
+            #[cfg(feature = "trace")]
+            trace_dir: None,
+        }
+    }
+}
+
+impl ComputeOptions {
+    /// The defaults, matching the behavior of [`crate::inverse_sqrt`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prefix for the labels of every wgpu object, so validation messages
+    /// name the batch they belong to.
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Labels every wgpu object, `rsqrt storage buffer`, `rsqrt pipeline`
+    /// and so on unless a [`label`](Self::label) is given, for validation
+    /// messages and GPU captures. Off by default.
+    ///
+    /// wgpu 0.12 has no instance flags to set: its backend validation layers
+    /// are enabled whenever wgpu itself is built with debug assertions.
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    /// Runs every kernel through naga, never as SPIR-V passthrough, for
+    /// tracking down out-of-bounds indexing in a kernel. Off by default.
+    ///
+    /// Modules naga translates are validated and get its bounds checks:
+    /// an out-of-range index is clamped into the buffer rather than
+    /// reading or writing past it. Passthrough SPIR-V reaches the driver
+    /// as it is. Objects are labelled as with [`debug`](Self::debug).
+    /// Results on valid inputs are the same either way.
+    pub fn robust(mut self, robust: bool) -> Self {
+        self.robust = robust;
+        self
+    }
+
+    /// Which backends to look for adapters on.
+    ///
+    /// Without an explicit choice, the `WGPU_BACKEND` environment variable
+    /// is honored (e.g. `WGPU_BACKEND=gl`), falling back to the primary
+    /// backends: Vulkan, Metal, DX12 and WebGPU.
+    pub fn backends(mut self, backends: Backends) -> Self {
+        self.backends = Some(backends);
+        self
+    }
+
+    /// Which adapter to prefer when more than one is available and the
+    /// [`AdapterSelector`] is `Auto`.
+    pub fn power_preference(mut self, power_preference: PowerPreference) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+
+    /// Only accept a software adapter, such as lavapipe or WARP, for
+    /// machines without a GPU. Applies when the [`AdapterSelector`] is
+    /// `Auto`.
+    ///
+    /// Without an explicit choice, `DEMO_RSQRT_FALLBACK=1` in the
+    /// environment turns this on. Features the adapter lacks are left out:
+    /// the shader is translated instead of passed through, and reports fall
+    /// back to wall clock timings.
+    pub fn force_fallback_adapter(mut self, force_fallback_adapter: bool) -> Self {
+        self.force_fallback_adapter = Some(force_fallback_adapter);
+        self
+    }
+
+    /// Which adapter to run on.
+    pub fn adapter(mut self, adapter: AdapterSelector) -> Self {
+        self.adapter = adapter;
+        self
+    }
+
+    /// Features a custom [`GpuKernel`](crate::GpuKernel) needs, on top of
+    /// the optional ones the context uses where available. Creating the
+    /// context fails with [`InitError::MissingFeatures`](crate::InitError::MissingFeatures)
+    /// if the adapter lacks any of them.
+    pub fn required_features(mut self, required_features: Features) -> Self {
+        self.required_features = required_features;
+        self
+    }
+
+    /// Optional features to do without even where the adapter supports
+    /// them, e.g. `TIMESTAMP_QUERY` or `PUSH_CONSTANTS`, to exercise the
+    /// paths taken on adapters that lack them.
+    pub fn disable_features(mut self, disabled_features: Features) -> Self {
+        self.disabled_features = disabled_features;
+        self
+    }
+
+    /// Caps the size in bytes of a storage buffer binding below the
+    /// adapter's limit, which is used otherwise.
+    pub fn max_binding_size(mut self, max_binding_size: u32) -> Self {
+        self.max_binding_size = Some(max_binding_size);
+        self
+    }
+
+    /// Caps the workgroups per dispatch dimension below the adapter's
+    /// limit, e.g. to exercise splitting without inputs that large. Zero
+    /// counts as one.
+    pub fn max_workgroups(mut self, max_workgroups: u32) -> Self {
+        self.max_workgroups = Some(max_workgroups);
+        self
+    }
+
+    /// Bytes of GPU buffers a single call may allocate. Larger inputs are
+    /// split into chunks that fit, or fail with
+    /// [`ComputeError::OutOfBudget`](crate::ComputeError::OutOfBudget)
+    /// where their results have to stay in one buffer or splitting is off.
+    ///
+    /// Defaults to two sets of storage, output and readback buffers at the
+    /// device's largest storage binding, as a chunked run uses.
+    pub fn memory_budget(mut self, memory_budget: u64) -> Self {
+        self.memory_budget = Some(memory_budget);
+        self
+    }
+
+    /// Request [`wgpu::Limits::downlevel_defaults`] even on adapters that
+    /// support the full WebGPU limits. GL-class adapters get them anyway.
+    pub fn downlevel_limits(mut self, downlevel_limits: bool) -> Self {
+        self.downlevel_limits = downlevel_limits;
+        self
+    }
+
+    /// Request every limit the adapter reports, e.g. to bind inputs larger
+    /// than the default limits allow in one go, instead of the defaults.
+    /// If the device refuses them, the defaults are used after all.
+    pub fn use_adapter_limits(mut self, use_adapter_limits: bool) -> Self {
+        self.use_adapter_limits = use_adapter_limits;
+        self
+    }
+
+    /// Split inputs too large for one dispatch into several that fit the
+    /// device's limits and the memory budget, instead of failing with
+    /// [`ComputeError::TooLarge`](crate::ComputeError::TooLarge) or
+    /// [`ComputeError::OutOfBudget`](crate::ComputeError::OutOfBudget).
+    /// Defaults to true.
+    pub fn split_large_inputs(mut self, split_large_inputs: bool) -> Self {
+        self.split_large_inputs = split_large_inputs;
+        self
+    }
+
+    /// Splits inputs into dispatches of at most `max_chunk_len` elements,
+    /// below what the device's limits allow, to exercise chunking.
+    #[doc(hidden)]
+    pub fn max_chunk_len(mut self, max_chunk_len: usize) -> Self {
+        self.max_chunk_len = Some(max_chunk_len);
+        self
+    }
+
+    /// Reject negative and NaN inputs with [`ComputeError::InvalidInput`](crate::ComputeError::InvalidInput)
+    /// instead of passing them to the shader.
+    pub fn validate_input(mut self, validate_input: bool) -> Self {
+        self.validate_input = validate_input;
+        self
+    }
+
+    /// What zero inputs map to. [`ZeroPolicy::Infinity`] by default.
+    pub fn zero_policy(mut self, zero_policy: ZeroPolicy) -> Self {
+        self.zero_policy = zero_policy;
+        self
+    }
+
+    /// What NaN results map to. [`NanPolicy::Propagate`] by default.
+    pub fn nan_policy(mut self, nan_policy: NanPolicy) -> Self {
+        self.nan_policy = nan_policy;
+        self
+    }
+
+    /// How [`GpuContext::compute`](crate::GpuContext::compute) takes the
+    /// inverse square root. [`Precision::Exact`] by default.
+    pub fn precision(mut self, precision: Precision) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    /// Map zero vectors to NaN in
+    /// [`GpuContext::normalize_vec3`](crate::GpuContext::normalize_vec3),
+    /// which has no direction to give them, instead of writing them
+    /// through unchanged. Off by default.
+    pub fn nan_zero_vectors(mut self, nan_zero_vectors: bool) -> Self {
+        self.nan_zero_vectors = nan_zero_vectors;
+        self
+    }
+
+    /// Raise negative inputs of
+    /// [`GpuContext::compute_clamped`](crate::GpuContext::compute_clamped)
+    /// to its epsilon like the small positive ones, rather than mapping
+    /// them to NaN, or rejecting them if input validation is enabled. On
+    /// by default.
+    pub fn clamp_negative(mut self, clamp_negative: bool) -> Self {
+        self.clamp_negative = clamp_negative;
+        self
+    }
+
+    /// Called after each chunk of [`GpuContext::compute_stream`](crate::GpuContext::compute_stream)
+    /// has been read back, from the task polling the stream.
+    pub fn on_progress(mut self, hook: impl Fn(ProgressInfo) + Send + Sync + 'static) -> Self {
+        self.on_progress = Some(ProgressHook(Arc::new(hook)));
+        self
+    }
+
+    /// How many chunks of [`GpuContext::compute_stream`](crate::GpuContext::compute_stream)
+    /// can be on the GPU at once, each uploaded through a staging buffer of
+    /// its own. Once all of them are in use, the stream waits for the
+    /// oldest chunk instead of allocating more. Defaults to 3.
+    ///
+    /// # Panics
+    ///
+    /// If `staging_buffers` is zero.
+    pub fn staging_buffers(mut self, staging_buffers: usize) -> Self {
+        assert!(staging_buffers > 0, "staging_buffers must not be zero");
+        self.staging_buffers = staging_buffers;
+        self
+    }
+
+    /// Bytes of storage, output and readback buffers a context keeps
+    /// between calls for later ones of the same size class to reuse. Once
+    /// the pool holds more, the buffers returned longest ago are freed.
+    /// Defaults to 256 MiB.
+    pub fn buffer_pool_size(mut self, buffer_pool_size: u64) -> Self {
+        self.buffer_pool_size = buffer_pool_size;
+        self
+    }
+
+    /// How many times to recreate the device and retry after a readback
+    /// fails, before giving up with [`ComputeError::DeviceLost`](crate::ComputeError::DeviceLost).
+    /// Defaults to 2.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Wait before recreating the device, multiplied by the attempt number.
+    /// Defaults to 100 ms.
+    pub fn retry_backoff(mut self, retry_backoff: Duration) -> Self {
+        self.retry_backoff = retry_backoff;
+        self
+    }
+
+    /// How long to wait for an adapter, a device or a readback before
+    /// giving up with [`ComputeError::Timeout`](crate::ComputeError::Timeout).
+    /// The device keeps being polled meanwhile. Defaults to 30 s.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Records a wgpu API trace of the device into `trace_dir`, for replay
+    /// with `wgpu player`. The directory is created if missing; creating the
+    /// context fails with [`InitError::TraceDir`](crate::InitError::TraceDir)
+    /// if it can't be written to.
+    ///
+    /// Requires the `trace` feature.
+    #[cfg(feature = "trace")]
+    pub fn trace_dir(mut self, trace_dir: impl Into<PathBuf>) -> Self {
+        self.trace_dir = Some(trace_dir.into());
+        self
+    }
+
+    /// Creates a [`GpuContext`] with these options.
+    pub async fn build(self) -> Result<GpuContext, ComputeError> {
+        GpuContext::with_options(self).await
+    }
+
+    /// `DEMO_RSQRT_BACKEND` takes precedence over the options, so CI can pin
+    /// the backend of every context.
+    pub(crate) fn backends_or_default(&self) -> Backends {
+        std::env::var("DEMO_RSQRT_BACKEND")
+            .ok()
+            .map(|backends| wgpu::util::parse_backends_from_comma_list(&backends.to_lowercase()))
+            .or(self.backends)
+            .or_else(wgpu::util::backend_bits_from_env)
+            .unwrap_or(Backends::PRIMARY)
+    }
+
+    /// The adapter to select and where the choice came from:
+    /// `DEMO_RSQRT_ADAPTER`, a name substring, takes precedence over the
+    /// options, so CI can pin the adapter of every context.
+    pub(crate) fn adapter_or_env(&self) -> (AdapterSelector, &'static str) {
+        match std::env::var("DEMO_RSQRT_ADAPTER") {
+            Ok(name) if !name.is_empty() => (AdapterSelector::Name(name), "DEMO_RSQRT_ADAPTER"),
+            _ => (self.adapter.clone(), "the options"),
+        }
+    }
+
+    pub(crate) fn fallback_or_default(&self) -> bool {
+        self.force_fallback_adapter.unwrap_or_else(|| {
+            std::env::var("DEMO_RSQRT_FALLBACK").map_or(false, |value| value == "1")
+        })
+    }
+
+    pub(crate) fn label_for(&self, object: &str) -> Option<String> {
+        let label = self
+            .label
+            .as_deref()
+            .or((self.debug || self.robust).then_some("rsqrt"));
+        label.map(|label| format!("{label} {object}"))
+    }
+}
@@ -0,0 +1,73 @@
+use std::{
+    sync::{Arc, Condvar, Mutex},
+    thread::JoinHandle,
+};
+
+use wgpu::Device;
+
+/// A thread that polls a device whenever work has been submitted, so map
+/// callbacks fire without the caller's thread blocking on the device.
+///
+/// Dropping the poller stops the thread and waits for it to exit.
+pub(crate) struct Poller {
+    shared: Arc<Shared>,
+    thread: Option<JoinHandle<()>>,
+}
+
+#[derive(Default)]
+struct Shared {
+    state: Mutex<State>,
+    kicked: Condvar,
+}
+
+#[derive(Default)]
+struct State {
+    pending: bool,
+    shutdown: bool,
+}
+
+impl Poller {
+    pub(crate) fn new(device: Arc<Device>) -> Self {
+        let shared = Arc::new(Shared::default());
+        let thread = std::thread::Builder::new()
+            .name("wgpu poller".into())
+            .spawn({
+                let shared = shared.clone();
+                move || loop {
+                    let mut state = shared.state.lock().unwrap();
+                    while !state.pending && !state.shutdown {
+                        state = shared.kicked.wait(state).unwrap();
+                    }
+                    if state.shutdown {
+                        return;
+                    }
+                    state.pending = false;
+                    drop(state);
+                    // Anything submitted while this waits kicks the poller
+                    // again, so it is picked up by the next round.
+                    device.poll(wgpu::Maintain::Wait);
+                }
+            })
+            .expect("Failed to spawn the poller thread");
+        Poller {
+            shared,
+            thread: Some(thread),
+        }
+    }
+
+    /// Polls the device until the work submitted so far has finished.
+    pub(crate) fn kick(&self) {
+        self.shared.state.lock().unwrap().pending = true;
+        self.shared.kicked.notify_one();
+    }
+}
+
+impl Drop for Poller {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().shutdown = true;
+        self.shared.kicked.notify_one();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
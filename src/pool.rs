@@ -0,0 +1,118 @@
+use std::{collections::BTreeMap, sync::Arc};
+
+use crate::context::{DeviceState, DispatchBuffers, DISPATCH_BUFFERS};
+
+/// How well the buffer pool of a [`GpuContext`](crate::GpuContext) is
+/// serving its calls, from [`GpuContext::pool_stats`](crate::GpuContext::pool_stats).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Dispatches that found buffers of their size class in the pool.
+    pub hits: u64,
+    /// Dispatches that had to allocate buffers.
+    pub misses: u64,
+    /// Bytes of buffers in the pool right now.
+    pub bytes: u64,
+}
+
+/// Dispatch buffers returned by finished calls, bucketed by capacity, which
+/// is a size class, and evicted least recently returned first once they
+/// take more than `max_bytes`.
+pub(crate) struct BufferPool {
+    classes: BTreeMap<wgpu::BufferAddress, Vec<Pooled>>,
+    max_bytes: u64,
+    stats: PoolStats,
+    /// Bumped on every return, to order the pooled buffers by age.
+    clock: u64,
+}
+
+struct Pooled {
+    buffers: DispatchBuffers,
+    returned: u64,
+}
+
+/// Bytes a set of dispatch buffers takes.
+fn set_bytes(buffers: &DispatchBuffers) -> u64 {
+    DISPATCH_BUFFERS * buffers.capacity
+}
+
+impl BufferPool {
+    pub(crate) fn new(max_bytes: u64) -> Self {
+        BufferPool {
+            classes: BTreeMap::new(),
+            max_bytes,
+            stats: PoolStats::default(),
+            clock: 0,
+        }
+    }
+
+    pub(crate) fn stats(&self) -> PoolStats {
+        self.stats
+    }
+
+    /// The most recently returned buffers of `capacity` on `state`, if any.
+    /// Buffers of a replaced device are dropped on the way.
+    pub(crate) fn check_out(
+        &mut self,
+        state: &Arc<DeviceState>,
+        capacity: wgpu::BufferAddress,
+    ) -> Option<DispatchBuffers> {
+        let mut freed = 0;
+        self.classes.retain(|_, pooled| {
+            pooled.retain(|pooled| {
+                let current = Arc::ptr_eq(&pooled.buffers.state, state);
+                if !current {
+                    freed += set_bytes(&pooled.buffers);
+                }
+                current
+            });
+            !pooled.is_empty()
+        });
+        self.stats.bytes -= freed;
+
+        let class = self.classes.get_mut(&capacity);
+        let Some(pooled) = class.and_then(|class| class.pop()) else {
+            self.stats.misses += 1;
+            return None;
+        };
+        if self.classes[&capacity].is_empty() {
+            self.classes.remove(&capacity);
+        }
+        self.stats.hits += 1;
+        self.stats.bytes -= set_bytes(&pooled.buffers);
+        Some(pooled.buffers)
+    }
+
+    /// Keeps `buffers` for a later call, evicting the least recently
+    /// returned buffers until the pool fits its cap again. Buffers larger
+    /// than the whole cap are dropped right away.
+    pub(crate) fn give_back(&mut self, buffers: DispatchBuffers) {
+        let bytes = set_bytes(&buffers);
+        if bytes > self.max_bytes || buffers.capacity == 0 {
+            return;
+        }
+        self.clock += 1;
+        self.stats.bytes += bytes;
+        self.classes
+            .entry(buffers.capacity)
+            .or_default()
+            .push(Pooled {
+                buffers,
+                returned: self.clock,
+            });
+
+        while self.stats.bytes > self.max_bytes {
+            // Each class is ordered by age, so its oldest entry comes first.
+            let (&capacity, _) = self
+                .classes
+                .iter()
+                .min_by_key(|(_, pooled)| pooled[0].returned)
+                .unwrap();
+            let class = self.classes.get_mut(&capacity).unwrap();
+            let evicted = class.remove(0);
+            if class.is_empty() {
+                self.classes.remove(&capacity);
+            }
+            self.stats.bytes -= set_bytes(&evicted.buffers);
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use std::{fmt, sync::Arc, time::Duration};
+
+/// How far a chunked computation has got, passed to the
+/// [`ComputeOptions::on_progress`](crate::ComputeOptions::on_progress) hook.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProgressInfo {
+    /// Elements whose results have been read back so far.
+    pub completed: usize,
+    /// Elements in the whole input, if the iterator reports an exact length.
+    pub total: Option<usize>,
+    /// Wall time since the first chunk was started.
+    pub elapsed: Duration,
+}
+
+/// A shared progress callback.
+#[derive(Clone)]
+pub(crate) struct ProgressHook(pub(crate) Arc<dyn Fn(ProgressInfo) + Send + Sync>);
+
+impl fmt::Debug for ProgressHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressHook")
+    }
+}
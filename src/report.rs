@@ -0,0 +1,187 @@
+use std::time::Instant;
+
+use wgpu::{util::DeviceExt, Backend, DeviceType};
+
+use crate::{
+    context::{validate, DISPATCH_BUFFERS},
+    kernel::{CountedInverseSqrt, NAN_COUNTER_SIZE},
+    timeout::with_timeout,
+    ComputeError, GpuContext,
+};
+
+/// Where a run happened and how long each stage took.
+#[derive(Clone, Debug)]
+pub struct ComputeReport {
+    /// Name of the adapter the kernel ran on.
+    pub adapter_name: String,
+    /// Backend the adapter was driven through.
+    pub backend: Backend,
+    /// Whether the adapter is integrated, discrete, virtual or a CPU, to
+    /// confirm which way the power preference went.
+    pub device_type: DeviceType,
+    /// PCI vendor ID of the adapter, or 0 if unknown.
+    pub vendor: usize,
+    /// PCI device ID of the adapter, or 0 if unknown.
+    pub device: usize,
+    /// Host time spent creating and filling the storage buffer.
+    pub upload_ns: u64,
+    /// Time spent running the kernel, see [`ComputeReport::gpu_timestamps`].
+    pub gpu_ns: u64,
+    /// Host time spent copying the mapped results into the output.
+    pub readback_ns: u64,
+    /// Number of elements computed.
+    pub elements: usize,
+    /// How many results the kernel wrote as NaN, counted on the GPU across
+    /// every workgroup. Zeros the zero policy replaces afterwards are still
+    /// counted.
+    pub nan_count: usize,
+    /// Whether [`ComputeReport::gpu_ns`] was measured with timestamp queries
+    /// around the compute pass. Otherwise it is the wall time from submission
+    /// until the results were mapped.
+    pub gpu_timestamps: bool,
+}
+
+/// Two timestamps around the compute pass and the buffer they resolve to.
+struct Timestamps {
+    query_set: wgpu::QuerySet,
+    buffer: wgpu::Buffer,
+}
+
+fn elapsed_ns(start: Instant) -> u64 {
+    start.elapsed().as_nanos() as u64
+}
+
+impl GpuContext {
+    /// Like [`GpuContext::compute`], and also reports the adapter, the time
+    /// spent in each stage and how many results were NaN.
+    ///
+    /// The run is timed as a single dispatch, so an input too large for one
+    /// fails with [`ComputeError::TooLarge`], and one over the memory budget
+    /// with [`ComputeError::OutOfBudget`].
+    pub async fn compute_with_report(
+        &self,
+        input: &[f32],
+    ) -> Result<(Vec<f32>, ComputeReport), ComputeError> {
+        if self.options.validate_input {
+            validate(input)?;
+        }
+
+        let state = self.state();
+        state.check_fits(&CountedInverseSqrt, 4, input.len())?;
+        let size = std::mem::size_of_val(input) as wgpu::BufferAddress;
+        state.check_budget(DISPATCH_BUFFERS, size)?;
+        let pipeline = state.counting_pipeline(&CountedInverseSqrt, 4)?;
+
+        let start = Instant::now();
+        let storage_buffer = state.create_storage_buffer(bytemuck::cast_slice(input))?;
+        let output_buffer = state.create_output_buffer(size)?;
+        let nan_counter = state.scoped("NaN counter", || {
+            state
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: state.options.label_for("NaN counter").as_deref(),
+                    contents: bytemuck::bytes_of(&0u32),
+                    usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+                })
+        })?;
+        let upload_ns = elapsed_ns(start);
+
+        let timestamps = state
+            .device
+            .features()
+            .contains(wgpu::Features::TIMESTAMP_QUERY)
+            .then(|| {
+                state.scoped("timestamp queries", || Timestamps {
+                    query_set: state.device.create_query_set(&wgpu::QuerySetDescriptor {
+                        label: state.options.label_for("timestamp query set").as_deref(),
+                        ty: wgpu::QueryType::Timestamp,
+                        count: 2,
+                    }),
+                    buffer: state.device.create_buffer(&wgpu::BufferDescriptor {
+                        label: state.options.label_for("timestamp buffer").as_deref(),
+                        size: 2 * std::mem::size_of::<u64>() as wgpu::BufferAddress,
+                        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                        mapped_at_creation: false,
+                    }),
+                })
+            })
+            .transpose()?;
+
+        let mut encoder = state.create_command_encoder();
+        if let Some(timestamps) = &timestamps {
+            encoder.write_timestamp(&timestamps.query_set, 0);
+        }
+        state.encode_kernel_with_params(
+            &mut encoder,
+            &pipeline,
+            storage_buffer.as_entire_buffer_binding(),
+            output_buffer.as_entire_buffer_binding(),
+            input.len() as u32,
+            None,
+            Some(&nan_counter),
+            &[],
+        )?;
+        if let Some(timestamps) = &timestamps {
+            encoder.write_timestamp(&timestamps.query_set, 1);
+            encoder.resolve_query_set(&timestamps.query_set, 0..2, &timestamps.buffer, 0);
+        }
+
+        let start = Instant::now();
+        let readback_buffer = state.create_readback_buffer(size)?;
+        state
+            .read_back_into(encoder, &output_buffer, &readback_buffer, size)
+            .await?;
+        let mut gpu_ns = elapsed_ns(start);
+
+        let start = Instant::now();
+        let mut output: Vec<f32> =
+            bytemuck::cast_slice(&readback_buffer.slice(..).get_mapped_range()).to_vec();
+        let readback_ns = elapsed_ns(start);
+        readback_buffer.unmap();
+        readback_buffer.destroy();
+
+        if let Some(timestamps) = &timestamps {
+            // The kernel has finished, so this only waits for the mapping.
+            let slice = timestamps.buffer.slice(..);
+            let buffer_future = slice.map_async(wgpu::MapMode::Read);
+            state.poll();
+            with_timeout(state.options.timeout, "buffer mapping", buffer_future).await??;
+
+            let ticks: [u64; 2] = bytemuck::pod_read_unaligned(&slice.get_mapped_range());
+            let period = state.queue.get_timestamp_period() as f64;
+            gpu_ns = (ticks[1].wrapping_sub(ticks[0]) as f64 * period) as u64;
+            timestamps.buffer.unmap();
+            timestamps.buffer.destroy();
+        }
+
+        // Already written, so this only waits for the copy.
+        let nan_count = state
+            .read_back::<u32>(
+                state.create_command_encoder(),
+                &nan_counter,
+                NAN_COUNTER_SIZE,
+            )
+            .await?[0];
+        nan_counter.destroy();
+
+        self.apply_zero_policy(input, &mut output);
+        // Without NaNs from the kernel there are none to look for.
+        if nan_count > 0 {
+            self.apply_nan_policy(&mut output)?;
+        }
+        let report = ComputeReport {
+            adapter_name: state.adapter_info.name.clone(),
+            backend: state.adapter_info.backend,
+            device_type: state.adapter_info.device_type,
+            vendor: state.adapter_info.vendor,
+            device: state.adapter_info.device,
+            upload_ns,
+            gpu_ns,
+            readback_ns,
+            elements: input.len(),
+            nan_count: nan_count as usize,
+            gpu_timestamps: timestamps.is_some(),
+        };
+        Ok((output, report))
+    }
+}
@@ -0,0 +1,56 @@
+use crate::WORKGROUP_SIZE;
+
+/// Summary statistics of a computation's results, from
+/// [`GpuContext::compute_stats`](crate::GpuContext::compute_stats).
+///
+/// NaN results are counted but left out of the other fields, which are NaN
+/// themselves when every result is.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stats {
+    /// The smallest result.
+    pub min: f32,
+    /// The largest result.
+    pub max: f32,
+    /// The mean of the results.
+    pub mean: f32,
+    /// How many results were NaN.
+    pub nan_count: usize,
+}
+
+/// Results each workgroup of the reduction kernel folds into one partial,
+/// four per invocation.
+pub(crate) const REDUCE_GROUP_ELEMENTS: usize = 4 * WORKGROUP_SIZE as usize;
+
+impl Stats {
+    /// Combines the reduction kernel's `[min, max, sum, nan_count]`
+    /// partials over `len` results. The sums are added in `f64`, so only
+    /// each workgroup's own sum rounds in `f32`.
+    pub(crate) fn from_partials(partials: &[[f32; 4]], len: usize) -> Self {
+        let mut min = f32::INFINITY;
+        let mut max = f32::NEG_INFINITY;
+        let mut sum = 0f64;
+        let mut nan_count = 0;
+        for &[partial_min, partial_max, partial_sum, partial_nan_count] in partials {
+            min = min.min(partial_min);
+            max = max.max(partial_max);
+            sum += f64::from(partial_sum);
+            nan_count += partial_nan_count as usize;
+        }
+
+        let counted = len - nan_count;
+        if counted == 0 {
+            return Stats {
+                min: f32::NAN,
+                max: f32::NAN,
+                mean: f32::NAN,
+                nan_count,
+            };
+        }
+        Stats {
+            min,
+            max,
+            mean: (sum / counted as f64) as f32,
+            nan_count,
+        }
+    }
+}
@@ -0,0 +1,381 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use futures::{future::BoxFuture, stream, Stream};
+use inverse_sqrt_shared::Count;
+
+use crate::{
+    context::{count_entry, validate, DeviceState},
+    timeout::with_timeout,
+    ComputeError, GpuContext, Kernel, ProgressInfo,
+};
+
+/// Cancels a chunked computation between chunks.
+///
+/// Clones share the same flag, so one clone can be handed to the stream and
+/// another kept to cancel it from elsewhere.
+#[derive(Clone, Debug, Default)]
+pub struct ComputeHandle {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ComputeHandle {
+    /// A handle that has not been cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stops the computation before its next chunk is yielded. A chunk
+    /// already being waited for still completes and is yielded; chunks read
+    /// ahead of it are dropped.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`ComputeHandle::cancel`] has been called on any clone.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// A staging buffer to upload one chunk through and a readback buffer for
+/// its results, one of a ring of them.
+struct Slot {
+    staging: wgpu::Buffer,
+    readback: wgpu::Buffer,
+    /// Resolves once the GPU is done with the last chunk uploaded through
+    /// the slot.
+    done: Option<BoxFuture<'static, ()>>,
+}
+
+/// Buffers sized for one chunk on one device. The storage and output
+/// buffers are shared by every chunk, which the queue runs in order; each
+/// chunk on the GPU has a slot of its own.
+struct ChunkBuffers {
+    state: Arc<DeviceState>,
+    storage: wgpu::Buffer,
+    output: wgpu::Buffer,
+    /// Rewritten before each chunk, which the queue orders before its
+    /// dispatch.
+    count: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    slots: Vec<Slot>,
+    next_slot: usize,
+}
+
+/// A chunk submitted but not yet yielded.
+struct InFlight {
+    chunk: Vec<f32>,
+    slot: usize,
+    mapped: BoxFuture<'static, Result<(), ComputeError>>,
+}
+
+struct ChunkedRun<'a, I> {
+    ctx: &'a GpuContext,
+    input: I,
+    chunk_size: usize,
+    handle: ComputeHandle,
+    buffers: Option<ChunkBuffers>,
+    in_flight: VecDeque<InFlight>,
+    /// No more chunks are read from the input.
+    exhausted: bool,
+    /// A chunk failed validation with this error, to be yielded once the
+    /// chunks before it are.
+    failed: Option<ComputeError>,
+    done: bool,
+    total: Option<usize>,
+    completed: usize,
+    started: Option<Instant>,
+}
+
+impl<'a, I: Iterator<Item = f32>> ChunkedRun<'a, I> {
+    async fn next_chunk(&mut self) -> Option<Result<Vec<f32>, ComputeError>> {
+        if self.done {
+            return None;
+        }
+
+        if self.handle.is_cancelled() {
+            // Chunks submitted ahead are dropped along with their slots.
+            self.done = true;
+            self.in_flight.clear();
+            return Some(Err(ComputeError::Cancelled {
+                completed: self.completed,
+            }));
+        }
+
+        let Some(result) = self.run_chunk().await else {
+            self.done = true;
+            return None;
+        };
+        match &result {
+            Ok(output) => {
+                self.completed += output.len();
+                if let (Some(hook), Some(started)) = (&self.ctx.options.on_progress, self.started) {
+                    (hook.0)(ProgressInfo {
+                        completed: self.completed,
+                        total: self.total,
+                        elapsed: started.elapsed(),
+                    });
+                }
+            }
+            Err(_) => self.done = true,
+        }
+        Some(result)
+    }
+
+    /// Submits chunks until every slot is in use, then waits for the oldest
+    /// and returns its results.
+    async fn run_chunk(&mut self) -> Option<Result<Vec<f32>, ComputeError>> {
+        let ctx = self.ctx;
+        if let Err(err) = self.fill().await {
+            return Some(Err(err));
+        }
+        let mut attempts = 0;
+        loop {
+            let Some(front) = self.in_flight.front_mut() else {
+                return self.failed.take().map(Err);
+            };
+            let result = (&mut front.mapped).await;
+            match ctx.check_readback(result) {
+                Err(ComputeError::BufferAsync(_)) => {
+                    // The buffers belong to the lost device, so every chunk
+                    // on it goes again on the new one.
+                    let chunks = self.in_flight.drain(..).map(|in_flight| in_flight.chunk);
+                    let chunks = chunks.collect::<Vec<_>>();
+                    self.buffers = None;
+                    if let Err(err) = ctx.recover(&mut attempts).await {
+                        return Some(Err(err));
+                    }
+                    for chunk in chunks {
+                        if let Err(err) = self.submit(chunk).await {
+                            return Some(Err(err));
+                        }
+                    }
+                }
+                Err(err) => return Some(Err(err)),
+                Ok(()) => break,
+            }
+        }
+
+        let InFlight { chunk, slot, .. } = self.in_flight.pop_front().unwrap();
+        let readback = &self.buffers.as_ref().unwrap().slots[slot].readback;
+        let size = (chunk.len() * 4) as wgpu::BufferAddress;
+        let mut output: Vec<f32> =
+            bytemuck::cast_slice(&readback.slice(..size).get_mapped_range()).to_vec();
+        readback.unmap();
+
+        ctx.apply_zero_policy(&chunk, &mut output);
+        Some(Ok(output))
+    }
+
+    /// Reads and submits chunks of the input while there are free slots.
+    async fn fill(&mut self) -> Result<(), ComputeError> {
+        while !self.exhausted && self.in_flight.len() < self.ctx.options.staging_buffers {
+            let chunk = self
+                .input
+                .by_ref()
+                .take(self.chunk_size)
+                .collect::<Vec<_>>();
+            if chunk.is_empty() {
+                self.exhausted = true;
+                break;
+            }
+            if self.ctx.options.validate_input {
+                if let Err(err) = validate(&chunk) {
+                    self.failed = Some(err);
+                    self.exhausted = true;
+                    break;
+                }
+            }
+            self.started.get_or_insert_with(Instant::now);
+            self.submit(chunk).await?;
+        }
+        Ok(())
+    }
+
+    /// Uploads `chunk` through the next slot, waiting for the GPU to finish
+    /// with it if the ring has gone all the way round, and submits it.
+    async fn submit(&mut self, chunk: Vec<f32>) -> Result<(), ComputeError> {
+        let ctx = self.ctx;
+        // Chunks still on the GPU keep the device their slots belong to.
+        let state = match &self.buffers {
+            Some(buffers) if !self.in_flight.is_empty() => buffers.state.clone(),
+            _ => ctx.state(),
+        };
+        let pipeline = state.pipeline(&Kernel::InverseSqrt, 4)?;
+        // The first chunk is the largest one, so later chunks always fit.
+        let size = (chunk.len() * 4) as wgpu::BufferAddress;
+        if let Some(buffers) = &self.buffers {
+            if !Arc::ptr_eq(&buffers.state, &state) {
+                // Another task replaced the device.
+                self.buffers = None;
+            }
+        }
+        if self.buffers.is_none() {
+            let storage = state.create_empty_storage_buffer(size)?;
+            let output = state.create_output_buffer(size)?;
+            let count = state.create_count_buffer(&[0])?;
+            let bind_group = state.scoped("bind group", || {
+                state.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: state.options.label_for("bind group").as_deref(),
+                    layout: &pipeline.bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: storage.as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: output.as_entire_binding(),
+                        },
+                        count_entry(&count),
+                    ],
+                })
+            })?;
+            self.buffers = Some(ChunkBuffers {
+                state: state.clone(),
+                storage,
+                output,
+                count,
+                bind_group,
+                slots: Vec::new(),
+                next_slot: 0,
+            });
+        }
+        let buffers = self.buffers.as_mut().unwrap();
+
+        let index = buffers.next_slot;
+        buffers.next_slot = (index + 1) % ctx.options.staging_buffers;
+        if index == buffers.slots.len() {
+            ctx.staging_allocations.fetch_add(1, Ordering::Relaxed);
+            let staging = state.scoped("staging buffer", || {
+                state.device.create_buffer(&wgpu::BufferDescriptor {
+                    label: state.options.label_for("staging buffer").as_deref(),
+                    size,
+                    usage: wgpu::BufferUsages::MAP_WRITE | wgpu::BufferUsages::COPY_SRC,
+                    mapped_at_creation: true,
+                })
+            })?;
+            buffers.slots.push(Slot {
+                staging,
+                readback: state.create_readback_buffer(size)?,
+                done: None,
+            });
+        } else {
+            let slot = &mut buffers.slots[index];
+            let timeout = state.options.timeout;
+            if let Some(done) = slot.done.take() {
+                state.poll();
+                with_timeout(timeout, "staging buffer", done).await?;
+            }
+            let mapped = slot.staging.slice(..size).map_async(wgpu::MapMode::Write);
+            state.poll();
+            with_timeout(timeout, "buffer mapping", mapped).await??;
+        }
+        let slot = &mut buffers.slots[index];
+        slot.staging
+            .slice(..size)
+            .get_mapped_range_mut()
+            .copy_from_slice(bytemuck::cast_slice(&chunk));
+        slot.staging.unmap();
+
+        let count = Count {
+            element_count: chunk.len() as u32,
+        };
+        state
+            .queue
+            .write_buffer(&buffers.count, 0, bytemuck::bytes_of(&count));
+        let mut encoder = state.create_command_encoder();
+        encoder.copy_buffer_to_buffer(&slot.staging, 0, &buffers.storage, 0, size);
+        state.record_dispatch(
+            &mut encoder,
+            &pipeline,
+            &buffers.bind_group,
+            &[],
+            chunk.len() as u32,
+            None,
+        );
+        let mapped = state.submit_read_back(encoder, &buffers.output, &slot.readback, size);
+        slot.done = Some(Box::pin(state.queue.on_submitted_work_done()));
+        self.in_flight.push_back(InFlight {
+            chunk,
+            slot: index,
+            mapped: Box::pin(mapped),
+        });
+        Ok(())
+    }
+}
+
+impl GpuContext {
+    /// Computes `1 / sqrt(x)` over `input` in chunks of at most `chunk_size`
+    /// elements, yielding each chunk's results as soon as it is read back.
+    ///
+    /// As many chunks as [`ComputeOptions::staging_buffers`](crate::ComputeOptions::staging_buffers)
+    /// allows are read ahead and kept on the GPU at once, each uploaded
+    /// through a staging buffer from a ring that is allocated once and
+    /// reused. The last chunk may be shorter than `chunk_size`, which is
+    /// capped at the most elements the device can dispatch at once and the
+    /// [`ComputeOptions::memory_budget`](crate::ComputeOptions::memory_budget)
+    /// allows. The stream ends after the first error.
+    ///
+    /// The [`ComputeOptions::on_progress`](crate::ComputeOptions::on_progress)
+    /// hook, if set, is called after every chunk.
+    ///
+    /// # Panics
+    ///
+    /// If `chunk_size` is zero.
+    pub fn compute_stream<'a>(
+        &'a self,
+        input: impl Iterator<Item = f32> + 'a,
+        chunk_size: usize,
+    ) -> impl Stream<Item = Result<Vec<f32>, ComputeError>> + 'a {
+        self.compute_stream_with_handle(input, chunk_size, ComputeHandle::new())
+    }
+
+    /// Like [`GpuContext::compute_stream`], but stops with
+    /// [`ComputeError::Cancelled`] once `handle` is cancelled.
+    pub fn compute_stream_with_handle<'a>(
+        &'a self,
+        input: impl Iterator<Item = f32> + 'a,
+        chunk_size: usize,
+        handle: ComputeHandle,
+    ) -> impl Stream<Item = Result<Vec<f32>, ComputeError>> + 'a {
+        assert!(chunk_size > 0, "chunk_size must not be zero");
+        let state = self.state();
+        // Storage and output, and a staging and a readback buffer per slot.
+        let buffers = 2 + 2 * self.options.staging_buffers as u64;
+        let by_budget = (state.memory_budget() / (buffers * 4)).max(1) as usize;
+        let chunk_size = chunk_size
+            .min(state.max_elements(&Kernel::InverseSqrt, 4))
+            .min(by_budget);
+
+        let total = match input.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(lower),
+            _ => None,
+        };
+        let run = ChunkedRun {
+            ctx: self,
+            input,
+            chunk_size,
+            handle,
+            buffers: None,
+            in_flight: VecDeque::new(),
+            exhausted: false,
+            failed: None,
+            done: false,
+            total,
+            completed: 0,
+            started: None,
+        };
+        stream::unfold(run, |mut run| async move {
+            let chunk = run.next_chunk().await?;
+            Some((chunk, run))
+        })
+    }
+}
@@ -0,0 +1,44 @@
+use std::{
+    future::Future,
+    sync::mpsc::{self, RecvTimeoutError},
+    time::Duration,
+};
+
+use futures::{
+    channel::oneshot,
+    future::{self, Either},
+};
+
+use crate::ComputeError;
+
+/// Waits for `future`, failing with [`ComputeError::Timeout`] for `stage`
+/// once `timeout` has passed.
+pub(crate) async fn with_timeout<T>(
+    timeout: Duration,
+    stage: &'static str,
+    future: impl Future<Output = T>,
+) -> Result<T, ComputeError> {
+    match future::select(Box::pin(future), Box::pin(sleep(timeout))).await {
+        Either::Left((value, _)) => Ok(value),
+        Either::Right(_) => Err(ComputeError::Timeout { stage }),
+    }
+}
+
+/// Resolves after `duration`.
+///
+/// The futures here run on any executor, so there is no runtime timer to
+/// lean on. A thread waits out the duration instead and gives up early once
+/// the future is dropped.
+pub(crate) fn sleep(duration: Duration) -> impl Future<Output = ()> {
+    let (elapsed, fired) = oneshot::channel();
+    let (cancel, cancelled) = mpsc::channel::<()>();
+    std::thread::spawn(move || {
+        if let Err(RecvTimeoutError::Timeout) = cancelled.recv_timeout(duration) {
+            let _ = elapsed.send(());
+        }
+    });
+    async move {
+        let _cancel = cancel;
+        let _ = fired.await;
+    }
+}
@@ -0,0 +1,35 @@
+/// How closely a computation's results match a reference, from
+/// [`GpuVec::verify_against`](crate::GpuVec::verify_against).
+///
+/// A NaN result matches a NaN reference; against anything else, as
+/// anything else against a NaN reference, it is infinitely far off.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VerifyReport {
+    /// The largest of `|result - reference| / |reference|` over the
+    /// results, zero if every one matches exactly.
+    pub max_relative_error: f32,
+    /// The first index the largest error was found at, or `None` if every
+    /// result matches exactly.
+    pub index: Option<usize>,
+}
+
+impl VerifyReport {
+    /// Combines the comparison kernel's `[error bits, index]` partials,
+    /// keeping the first of equal errors.
+    pub(crate) fn from_partials(partials: &[[u32; 2]]) -> Self {
+        let mut report = VerifyReport {
+            max_relative_error: 0.,
+            index: None,
+        };
+        for &[error, index] in partials {
+            let error = f32::from_bits(error);
+            if error > report.max_relative_error {
+                report = VerifyReport {
+                    max_relative_error: error,
+                    index: Some(index as usize),
+                };
+            }
+        }
+        report
+    }
+}
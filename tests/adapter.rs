@@ -0,0 +1,46 @@
+use demo_wgpu_compute::{list_adapters, AdapterSelector, ComputeError, ComputeOptions, GpuContext};
+
+#[test]
+fn lists_at_least_one_adapter() {
+    assert!(!list_adapters().is_empty());
+}
+
+#[tokio::test]
+async fn select_first_adapter_by_index() {
+    let options = ComputeOptions::new().adapter(AdapterSelector::Index(0));
+    let ctx = GpuContext::with_options(options)
+        .await
+        .expect("Failed to create context");
+
+    assert_eq!(ctx.adapter_info().name, list_adapters()[0].name);
+    let output = ctx
+        .compute(&[4., 25., 100.])
+        .await
+        .expect("Failed to compute");
+    assert_eq!(output, [0.5, 0.2, 0.1]);
+}
+
+#[tokio::test]
+async fn unknown_name_lists_available_adapters() {
+    let selector = AdapterSelector::Name("no such adapter".to_owned());
+    let result = GpuContext::with_options(ComputeOptions::new().adapter(selector)).await;
+
+    match result {
+        Err(ComputeError::AdapterNotFound { available, .. }) => {
+            let names = list_adapters().into_iter().map(|info| info.name);
+            assert_eq!(available, names.collect::<Vec<_>>());
+        }
+        Err(err) => panic!("Expected AdapterNotFound, got {err}"),
+        Ok(_) => panic!("Expected AdapterNotFound"),
+    }
+}
+
+#[test]
+fn selector_from_str() {
+    assert_eq!("auto".parse(), Ok(AdapterSelector::Auto));
+    assert_eq!("1".parse(), Ok(AdapterSelector::Index(1)));
+    assert_eq!(
+        "NVIDIA".parse(),
+        Ok(AdapterSelector::Name("NVIDIA".to_owned()))
+    );
+}
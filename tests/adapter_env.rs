@@ -0,0 +1,37 @@
+use demo_wgpu_compute::{list_adapters, AdapterSelector, ComputeError, GpuContext};
+
+// The variables are process-wide, so everything that sets them runs in this
+// one test.
+#[tokio::test]
+async fn environment_overrides_adapter_selection() {
+    let adapter = list_adapters().pop().expect("Failed to find any adapter");
+
+    std::env::set_var("DEMO_RSQRT_ADAPTER", &adapter.name);
+    std::env::set_var(
+        "DEMO_RSQRT_BACKEND",
+        format!("{:?}", adapter.backend).to_lowercase(),
+    );
+    let ctx = GpuContext::builder()
+        .adapter(AdapterSelector::Index(usize::MAX))
+        .build()
+        .await
+        .expect("Failed to create context");
+    assert_eq!(ctx.adapter_info().name, adapter.name);
+    assert_eq!(ctx.adapter_info().backend, adapter.backend);
+
+    std::env::set_var("DEMO_RSQRT_ADAPTER", "no such adapter");
+    let result = GpuContext::new().await;
+    std::env::remove_var("DEMO_RSQRT_ADAPTER");
+    std::env::remove_var("DEMO_RSQRT_BACKEND");
+    match result {
+        Err(ComputeError::AdapterNotFound {
+            selector,
+            available,
+        }) => {
+            assert_eq!(selector, AdapterSelector::Name("no such adapter".into()));
+            assert!(available.contains(&adapter.name));
+        }
+        Err(other) => panic!("expected AdapterNotFound, got {other:?}"),
+        Ok(_) => panic!("expected AdapterNotFound"),
+    }
+}
@@ -0,0 +1,36 @@
+use demo_wgpu_compute::{Backends, ComputeError, GpuContext, InitError};
+
+#[tokio::test]
+async fn all_backends_succeed() {
+    let ctx = GpuContext::builder()
+        .backends(Backends::all())
+        .build()
+        .await
+        .expect("Failed to create context");
+
+    let output = ctx
+        .compute(&[4., 25., 100.])
+        .await
+        .expect("Failed to compute");
+    assert_eq!(output, [0.5, 0.2, 0.1]);
+}
+
+#[tokio::test]
+async fn no_backends_is_a_typed_error() {
+    let result = GpuContext::builder()
+        .backends(Backends::empty())
+        .build()
+        .await;
+
+    match result {
+        Err(ComputeError::Init(InitError::NoAdapter {
+            requested_backends,
+            available,
+        })) => {
+            assert_eq!(requested_backends, Backends::empty());
+            assert!(available.is_empty());
+        }
+        Err(err) => panic!("Expected NoAdapter, got {err}"),
+        Ok(_) => panic!("Expected NoAdapter"),
+    }
+}
@@ -0,0 +1,76 @@
+use demo_wgpu_compute::{GpuContext, Kernel, WORKGROUP_SIZE};
+
+/// Lengths on either side of the workgroup sizes in use, and of four
+/// elements per invocation.
+const LENGTHS: [usize; 14] = [
+    1, 2, 63, 64, 65, 127, 128, 129, 255, 256, 257, 4095, 4096, 4097,
+];
+
+/// `len` fixed pseudo-random positive normal floats, different for every
+/// length.
+fn random_input(len: usize) -> Vec<f32> {
+    let mut state = 0x2545_f491u32 ^ len as u32;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            // Exponents from 2^-60 to 2^67, any mantissa.
+            f32::from_bits((0x21 << 23) + state % (0x80 << 23))
+        })
+        .collect()
+}
+
+fn assert_matches_cpu(path: &str, input: &[f32], output: &[f32]) {
+    assert_eq!(output.len(), input.len(), "{path}: length {}", input.len());
+    for (index, (case, result)) in input.iter().zip(output).enumerate() {
+        let expected = 1. / case.sqrt();
+        assert!(
+            ((result - expected) / expected).abs() <= 0.000001,
+            "{path}: length {}, index {index}: expected {expected}, got {result}",
+            input.len()
+        );
+    }
+}
+
+#[tokio::test]
+async fn every_path_handles_lengths_around_workgroup_boundaries() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    // Chunks of one workgroup and of 100 elements, so chunk boundaries
+    // fall both on and between workgroup boundaries.
+    let chunked = [WORKGROUP_SIZE as usize, 100]
+        .map(|max_chunk_len| GpuContext::builder().max_chunk_len(max_chunk_len).build());
+    let chunked = futures::future::try_join_all(chunked)
+        .await
+        .expect("Failed to create context");
+
+    for len in LENGTHS {
+        let input = random_input(len);
+
+        let output = ctx.compute(&input).await.expect("Failed to compute");
+        assert_matches_cpu("compute", &input, &output);
+
+        let mut data = input.clone();
+        ctx.compute_into(&mut data)
+            .await
+            .expect("Failed to compute");
+        assert_matches_cpu("compute_into", &input, &data);
+
+        let mut out = vec![0.; len];
+        ctx.compute_read_into(&input, &mut out)
+            .await
+            .expect("Failed to compute");
+        assert_matches_cpu("compute_read_into", &input, &out);
+
+        for chunked in &chunked {
+            let output = chunked.compute(&input).await.expect("Failed to compute");
+            assert_matches_cpu("chunked", &input, &output);
+        }
+
+        let output = ctx
+            .compute_with(Kernel::InverseSqrtVec4, &input)
+            .await
+            .expect("Failed to compute");
+        assert_matches_cpu("vec4", &input, &output);
+    }
+}
@@ -0,0 +1,138 @@
+use demo_wgpu_compute::GpuContext;
+
+#[tokio::test]
+async fn small_calls_reuse_buffers() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    let mut allocations_after_warmup = 0;
+    for call in 0..1000 {
+        let input = (1..=call % 100 + 1).map(|x| x as f32).collect::<Vec<_>>();
+        let output = ctx.compute(&input).await.expect("Failed to compute");
+        assert_eq!(output.len(), input.len());
+        for (result, case) in output.into_iter().zip(input) {
+            assert!((result - 1. / case.sqrt()).abs() <= 0.000001, "call {call}");
+        }
+        if call == 9 {
+            allocations_after_warmup = ctx.buffer_allocations();
+        }
+    }
+
+    assert!(allocations_after_warmup > 0);
+    assert_eq!(ctx.buffer_allocations(), allocations_after_warmup);
+}
+
+#[tokio::test]
+async fn back_to_back_inputs_share_the_storage_buffer() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    let first = ctx
+        .compute(&[4., 16., 64.])
+        .await
+        .expect("Failed to compute");
+    let allocations = ctx.buffer_allocations();
+    let second = ctx.compute(&[25., 100.]).await.expect("Failed to compute");
+
+    assert_eq!(first, [0.5, 0.25, 0.125]);
+    assert_eq!(second, [0.2, 0.1]);
+    assert_eq!(ctx.buffer_allocations(), allocations);
+}
+
+#[tokio::test]
+async fn chunks_alternate_between_two_buffer_sets() {
+    let ctx = GpuContext::builder()
+        .max_chunk_len(1000)
+        .build()
+        .await
+        .expect("Failed to create context");
+    let input = (1..=100_003).map(|x| x as f32 * 0.25).collect::<Vec<_>>();
+
+    let double_buffered = ctx.compute(&input).await.expect("Failed to compute");
+    assert_eq!(ctx.buffer_allocations(), 2);
+    // compute_into still maps each chunk before submitting the next.
+    let mut single_buffered = input.clone();
+    ctx.compute_into(&mut single_buffered)
+        .await
+        .expect("Failed to compute in place");
+
+    assert_eq!(double_buffered.len(), input.len());
+    for (index, (result, expected)) in double_buffered.iter().zip(&single_buffered).enumerate() {
+        assert_eq!(result.to_bits(), expected.to_bits(), "at {index}");
+    }
+}
+
+/// Lengths whose buffers fall into four different size classes.
+const MIXED_LENS: [usize; 4] = [100, 20_000, 100_000, 300_000];
+
+#[tokio::test]
+async fn mixed_sizes_hit_the_pool_after_warmup() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    for call in 0..40 {
+        let len = MIXED_LENS[call % MIXED_LENS.len()];
+        let input = (1..=len).map(|x| x as f32).collect::<Vec<_>>();
+        let output = ctx.compute(&input).await.expect("Failed to compute");
+        assert_eq!(output.len(), len);
+        assert!((output[len - 1] - 1. / (len as f32).sqrt()).abs() <= 0.000001);
+    }
+
+    let stats = ctx.pool_stats();
+    assert_eq!(stats.misses, MIXED_LENS.len() as u64);
+    assert_eq!(stats.hits, 40 - MIXED_LENS.len() as u64);
+    assert_eq!(ctx.buffer_allocations(), MIXED_LENS.len());
+    assert!(stats.bytes > 0);
+}
+
+#[tokio::test]
+async fn eviction_keeps_the_pool_under_its_cap() {
+    // Room for one set of the 512 KiB class, or the smaller ones together.
+    let cap = 3 * (512 << 10);
+    let ctx = GpuContext::builder()
+        .buffer_pool_size(cap)
+        .build()
+        .await
+        .expect("Failed to create context");
+
+    // Each 512 KiB set evicts everything else, and the small ones evict it.
+    let calls = [100_000, 100_000, 100, 20_000, 100, 100_000];
+    for (call, len) in calls.into_iter().enumerate() {
+        let input = (1..=len).map(|x| x as f32).collect::<Vec<_>>();
+        ctx.compute(&input).await.expect("Failed to compute");
+        assert!(ctx.pool_stats().bytes <= cap, "call {call}");
+    }
+
+    let stats = ctx.pool_stats();
+    assert_eq!((stats.hits, stats.misses), (2, 4));
+    assert_eq!(stats.bytes, cap);
+}
+
+#[tokio::test]
+async fn reused_buffers_never_mix_neighbouring_chunks() {
+    let chunk_len = 100;
+    let ctx = GpuContext::builder()
+        .max_chunk_len(chunk_len)
+        .build()
+        .await
+        .expect("Failed to create context");
+    // 200 chunks, each a constant with an exact inverse sqrt of its own.
+    let input = (1..=200)
+        .flat_map(|chunk| std::iter::repeat((chunk * chunk) as f32).take(chunk_len))
+        .collect::<Vec<_>>();
+
+    let double_buffered = ctx.compute(&input).await.expect("Failed to compute");
+    let mut single_buffered = input.clone();
+    ctx.compute_into(&mut single_buffered)
+        .await
+        .expect("Failed to compute in place");
+
+    assert_eq!(ctx.buffer_allocations(), 2);
+    for output in [double_buffered, single_buffered] {
+        assert_eq!(output.len(), input.len());
+        for (chunk, results) in output.chunks(chunk_len).enumerate() {
+            let expected = 1. / (chunk + 1) as f32;
+            assert!(
+                results.iter().all(|&result| result == expected),
+                "chunk {chunk}: expected {expected}, got {results:?}"
+            );
+        }
+    }
+}
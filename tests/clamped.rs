@@ -0,0 +1,77 @@
+use demo_wgpu_compute::{ComputeOptions, Features, GpuContext};
+
+const EPSILON: f32 = 1e-6;
+
+#[tokio::test]
+async fn zero_and_tiny_inputs_clamp_to_epsilon() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = [0., -0., 1e-30, 1e-40, EPSILON / 2., -4.];
+
+    let output = ctx
+        .compute_clamped(&input, EPSILON)
+        .await
+        .expect("Failed to compute");
+
+    let expected = 1. / EPSILON.sqrt();
+    for (case, result) in input.into_iter().zip(output) {
+        assert!(result.is_finite(), "{case}: {result}");
+        assert!(
+            (result - expected).abs() <= 0.000001 * expected,
+            "{case}: expected {expected}, got {result}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn inputs_above_epsilon_match_the_plain_kernel() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = (1..10_000).map(|x| x as f32 * 0.01).collect::<Vec<_>>();
+
+    let clamped = ctx
+        .compute_clamped(&input, EPSILON)
+        .await
+        .expect("Failed to compute");
+    let plain = ctx.compute(&input).await.expect("Failed to compute");
+
+    assert_eq!(clamped, plain);
+}
+
+#[tokio::test]
+async fn negative_inputs_can_map_to_nan() {
+    let options = ComputeOptions::new().clamp_negative(false);
+    let ctx = GpuContext::with_options(options)
+        .await
+        .expect("Failed to create context");
+
+    let output = ctx
+        .compute_clamped(&[-4., -1e-30, 0., -0., 4.], EPSILON)
+        .await
+        .expect("Failed to compute");
+
+    assert!(output[0].is_nan() && output[1].is_nan(), "{output:?}");
+    assert_eq!(output[2], 1. / EPSILON.sqrt());
+    assert_eq!(output[3], 1. / EPSILON.sqrt());
+    assert_eq!(output[4], 0.5);
+}
+
+#[tokio::test]
+async fn both_params_reach_the_uniform_buffer() {
+    // Without push constants, the epsilon and the negative flag share one
+    // uniform buffer, and each has to land in its own field.
+    let options = ComputeOptions::new()
+        .disable_features(Features::PUSH_CONSTANTS)
+        .clamp_negative(false);
+    let ctx = GpuContext::with_options(options)
+        .await
+        .expect("Failed to create context");
+
+    let output = ctx
+        .compute_clamped(&[0.01, 1e-30, -4., 100.], 0.25)
+        .await
+        .expect("Failed to compute");
+
+    assert_eq!(output[0], 2.);
+    assert_eq!(output[1], 2.);
+    assert!(output[2].is_nan(), "{output:?}");
+    assert_eq!(output[3], 0.1);
+}
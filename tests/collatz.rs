@@ -0,0 +1,39 @@
+use demo_wgpu_compute::{GpuContext, Kernel};
+
+/// Steps `Kernel::Collatz` gives up after.
+const MAX_STEPS: u32 = 1000;
+
+fn collatz_steps(mut n: u32) -> u32 {
+    let mut steps = 0;
+    while n != 1 {
+        n = if n % 2 == 0 { n / 2 } else { 3 * n + 1 };
+        steps += 1;
+    }
+    steps
+}
+
+#[tokio::test]
+async fn collatz_steps_match_the_cpu() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = (1..10_000).collect::<Vec<u32>>();
+
+    let output = ctx
+        .run_compute_shader(&input, &Kernel::Collatz)
+        .await
+        .expect("Failed to run shader");
+
+    let expected = input.iter().map(|&n| collatz_steps(n)).collect::<Vec<_>>();
+    assert_eq!(output, expected);
+}
+
+#[tokio::test]
+async fn zero_and_overflowing_starts_hit_the_cap() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    let output = ctx
+        .run_compute_shader(&[0, u32::MAX, 1, 27], &Kernel::Collatz)
+        .await
+        .expect("Failed to run shader");
+
+    assert_eq!(output, [MAX_STEPS, MAX_STEPS, 0, 111]);
+}
@@ -0,0 +1,61 @@
+use demo_wgpu_compute::{ComputeError, GpuContext};
+
+#[tokio::test]
+async fn normalized_samples_have_unit_magnitude() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    // Past one workgroup, with magnitudes from tiny to huge.
+    let input = (1..1000)
+        .flat_map(|i| {
+            let i = i as f32;
+            let scale = 10f32.powi(i as i32 % 30 - 15);
+            [i.sin() * scale, i.cos() * scale]
+        })
+        .collect::<Vec<_>>();
+
+    let output = ctx
+        .normalize_complex(&input)
+        .await
+        .expect("Failed to normalize");
+
+    assert_eq!(output.len(), input.len());
+    for (sample, result) in input.chunks(2).zip(output.chunks(2)) {
+        let (re, im) = (result[0], result[1]);
+        assert!(
+            (re * re + im * im - 1.).abs() <= 1e-6,
+            "{sample:?}: {result:?}"
+        );
+        // Same phase as the input.
+        assert!(sample[0] * re + sample[1] * im > 0.);
+    }
+}
+
+#[tokio::test]
+async fn zero_samples_pass_through() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    let output = ctx
+        .normalize_complex(&[0., 0., 3., -4., -0., 0.])
+        .await
+        .expect("Failed to normalize");
+
+    assert_eq!(output, [0., 0., 0.6, -0.8, -0., 0.]);
+    assert!(output[4].is_sign_negative());
+}
+
+#[tokio::test]
+async fn odd_length_is_rejected() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    let result = ctx.normalize_complex(&[1., 0., 1.]).await;
+
+    assert!(
+        matches!(
+            result,
+            Err(ComputeError::Misaligned {
+                len: 12,
+                element_size: 8
+            })
+        ),
+        "{result:?}"
+    );
+}
@@ -0,0 +1,60 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use demo_wgpu_compute::GpuContext;
+
+struct PeakAlloc;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(current, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOC: PeakAlloc = PeakAlloc;
+
+#[tokio::test]
+async fn compute_into_1m_without_extra_allocations() {
+    const LEN: usize = 1 << 20;
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let mut data = (1..=LEN).map(|x| x as f32).collect::<Vec<_>>();
+    let bytes = LEN * std::mem::size_of::<f32>();
+
+    let baseline = CURRENT.load(Ordering::SeqCst);
+    PEAK.store(baseline, Ordering::SeqCst);
+    ctx.compute_into(&mut data)
+        .await
+        .expect("Failed to calculate inverse sqrt");
+    let growth = PEAK.load(Ordering::SeqCst) - baseline;
+
+    // Some backends keep the readback mapping in host memory; anything on
+    // top of that would be an intermediate copy of the data.
+    assert!(
+        growth < 2 * bytes,
+        "peak allocations grew by {growth} bytes for a {bytes} byte input"
+    );
+
+    for (case, result) in (1..=LEN).map(|x| x as f32).zip(data) {
+        let local_result = 1. / case.sqrt();
+        assert!(
+            (local_result - result).abs() <= 0.000001,
+            "Failed at {case} case. Expected result: {local_result} Received instead: {result}"
+        );
+    }
+}
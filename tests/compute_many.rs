@@ -0,0 +1,70 @@
+use demo_wgpu_compute::GpuContext;
+
+#[tokio::test]
+async fn compute_many_matches_cpu_reference() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    // Lengths vary from 0 to 343, so some inputs are empty and most do not
+    // end on a workgroup boundary.
+    let inputs = (0..50)
+        .map(|i| {
+            (1..=i * 7)
+                .map(|x| (x * (i + 1)) as f32)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let slices = inputs.iter().map(Vec::as_slice).collect::<Vec<_>>();
+
+    let outputs = ctx
+        .compute_many(&slices)
+        .await
+        .expect("Failed to compute batch");
+
+    assert_eq!(outputs.len(), inputs.len());
+    for (input, output) in inputs.iter().zip(&outputs) {
+        assert_eq!(input.len(), output.len());
+        for (case, result) in input.iter().zip(output) {
+            let local_result = 1. / case.sqrt();
+            assert!(
+                (local_result - result).abs() <= 0.000001,
+                "Failed at {case} case. Expected result: {local_result} Received instead: {result}"
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn compute_many_all_empty() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    let outputs = ctx
+        .compute_many(&[&[], &[]])
+        .await
+        .expect("Failed to compute batch");
+
+    assert_eq!(outputs, [Vec::<f32>::new(), Vec::new()]);
+}
+
+#[tokio::test]
+async fn compute_many_matches_separate_calls() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    // Irregular lengths, so most jobs end off a workgroup boundary and are
+    // padded up to the next aligned offset.
+    let inputs = (0..100)
+        .map(|i: usize| {
+            let len = (i * 37) % 301 + if i % 10 == 0 { 5000 } else { 0 };
+            (1..=len).map(|x| (x + i) as f32 * 0.5).collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let slices = inputs.iter().map(Vec::as_slice).collect::<Vec<_>>();
+
+    let outputs = ctx
+        .compute_many(&slices)
+        .await
+        .expect("Failed to compute batch");
+
+    assert_eq!(outputs.len(), inputs.len());
+    for (job, (input, output)) in inputs.iter().zip(&outputs).enumerate() {
+        let expected = ctx.compute(input).await.expect("Failed to compute");
+        assert_eq!(output, &expected, "job {job}");
+    }
+}
@@ -0,0 +1,87 @@
+use std::{sync::Arc, time::Instant};
+
+use demo_wgpu_compute::{inverse_sqrt, Backend, GpuContext};
+
+fn assert_send_sync<T: Send + Sync>() {}
+
+#[test]
+fn context_is_send_sync() {
+    assert_send_sync::<GpuContext>();
+}
+
+#[tokio::test]
+async fn warm_calls_match_cold_calls_and_are_faster() {
+    let input = (1..1024).map(|x| x as f32).collect::<Vec<_>>();
+
+    let cold_start = Instant::now();
+    let mut cold = Vec::new();
+    for _ in 0..100 {
+        cold.push(inverse_sqrt(&input).await.expect("cold call failed"));
+    }
+    let cold_elapsed = cold_start.elapsed();
+
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let warm_start = Instant::now();
+    let mut warm = Vec::new();
+    for _ in 0..100 {
+        warm.push(ctx.compute(&input).await.expect("warm call failed"));
+    }
+    let warm_elapsed = warm_start.elapsed();
+
+    assert_eq!(cold, warm);
+    assert!(
+        warm_elapsed < cold_elapsed,
+        "warm calls took {warm_elapsed:?}, cold calls took {cold_elapsed:?}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn shared_between_tasks() {
+    let ctx = Arc::new(GpuContext::new().await.expect("Failed to create context"));
+
+    let tasks = (1..=8)
+        .map(|task| {
+            let ctx = ctx.clone();
+            tokio::spawn(async move {
+                let input = vec![(task * task) as f32; 256];
+                let output = ctx.compute(&input).await.expect("compute failed");
+                assert!(output.iter().all(|&x| (x - 1. / task as f32).abs() < 1e-6));
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for task in tasks {
+        task.await.unwrap();
+    }
+}
+
+#[tokio::test]
+async fn warmup_is_idempotent() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    assert!(!ctx.is_warmed());
+
+    ctx.warmup().await.expect("Failed to warm up");
+    assert!(ctx.is_warmed());
+    ctx.warmup().await.expect("Failed to warm up again");
+    assert!(ctx.is_warmed());
+
+    let output = ctx
+        .compute(&[4., 25., 100.])
+        .await
+        .expect("Failed to compute");
+    assert_eq!(output, [0.5, 0.2, 0.1]);
+}
+
+#[tokio::test]
+async fn adapter_info_is_populated() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let info = ctx.adapter_info();
+
+    assert_ne!(info.backend, Backend::Empty);
+    assert!(!info.name.is_empty());
+    let (_, report) = ctx
+        .compute_with_report(&[4.])
+        .await
+        .expect("Failed to compute with report");
+    assert_eq!((report.vendor, report.device), (info.vendor, info.device));
+}
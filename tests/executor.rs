@@ -0,0 +1,14 @@
+use demo_wgpu_compute::GpuContext;
+use futures::executor::block_on;
+
+#[test]
+fn compute_on_futures_executor() {
+    // No tokio here: the library polls the device itself.
+    let output = block_on(async {
+        let ctx = GpuContext::new().await?;
+        ctx.compute(&[4., 25., 100.]).await
+    })
+    .expect("Failed to compute inverse sqrt");
+
+    assert_eq!(output, [0.5, 0.2, 0.1]);
+}
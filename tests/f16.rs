@@ -0,0 +1,73 @@
+#![cfg(feature = "f16")]
+
+use demo_wgpu_compute::{f16, ComputeOptions, GpuContext, ZeroPolicy};
+
+#[tokio::test]
+async fn every_positive_half_matches_an_f32_reference() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    // Subnormals and normals up to the largest finite half: an odd number
+    // of elements, so the last word is padded.
+    let input = (1..0x7c00).map(f16::from_bits).collect::<Vec<_>>();
+
+    let output = ctx.compute_f16(&input).await.expect("Failed to compute");
+
+    assert_eq!(output.len(), input.len());
+    for (case, result) in input.into_iter().zip(output) {
+        let expected = 1. / case.to_f32().sqrt();
+        // Half a unit in the last place of an 11-bit significand, with
+        // some slack for the f32 computation.
+        let error = ((result.to_f32() - expected) / expected).abs();
+        assert!(error <= 1. / 1024., "{case}: {result} != {expected}");
+    }
+}
+
+#[tokio::test]
+async fn special_halves_map_like_f32() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = [f16::ZERO, f16::NEG_ZERO, -f16::ONE, f16::NAN, f16::INFINITY];
+
+    let output = ctx.compute_f16(&input).await.expect("Failed to compute");
+
+    assert_eq!(output[0].to_bits(), f16::INFINITY.to_bits());
+    assert_eq!(output[1].to_bits(), f16::NEG_INFINITY.to_bits());
+    assert!(output[2..4].iter().all(|result| result.is_nan()));
+    assert_eq!(output[4], f16::ZERO);
+}
+
+#[tokio::test]
+async fn half_zeros_follow_the_zero_policy() {
+    let options = ComputeOptions::new().zero_policy(ZeroPolicy::Infinity);
+    let ctx = GpuContext::with_options(options)
+        .await
+        .expect("Failed to create context");
+
+    let output = ctx
+        .compute_f16(&[f16::NEG_ZERO, f16::from_f32(4.), f16::ZERO])
+        .await
+        .expect("Failed to compute");
+
+    assert_eq!(
+        output,
+        [f16::NEG_INFINITY, f16::from_f32(0.5), f16::INFINITY]
+    );
+}
+
+#[tokio::test]
+async fn padded_words_are_cut_off() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let mut state = 0x9e37_79b9u32;
+    let lengths = (0..30).map(|_| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as usize % 10_000
+    });
+
+    for len in [0, 1, 2, 3].into_iter().chain(lengths) {
+        let input = vec![f16::ONE; len];
+
+        let output = ctx.compute_f16(&input).await.expect("Failed to compute");
+
+        assert_eq!(output, input, "{len} halves");
+    }
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "f64")]
+
+use demo_wgpu_compute::{ComputeError, GpuContext};
+
+/// A context whose device runs `f64` shaders, or `None` to skip the test
+/// on adapters without them.
+async fn f64_context() -> Option<GpuContext> {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    match ctx.compute_f64(&[1.]).await {
+        Ok(_) => Some(ctx),
+        Err(ComputeError::F64Unsupported { adapter }) => {
+            eprintln!("skipping, {} has no f64 shaders", adapter.name);
+            None
+        }
+        Err(err) => panic!("Failed to compute: {err}"),
+    }
+}
+
+#[tokio::test]
+async fn f64_results_match_a_double_precision_reference() {
+    let Some(ctx) = f64_context().await else {
+        return;
+    };
+    let input = (1..10_000)
+        .map(|x| x as f64 * 0.731)
+        .chain([1e300, f64::MIN_POSITIVE, 2.])
+        .collect::<Vec<_>>();
+
+    let output = ctx.compute_f64(&input).await.expect("Failed to compute");
+
+    assert_eq!(output.len(), input.len());
+    for (case, result) in input.into_iter().zip(output) {
+        let expected = 1. / case.sqrt();
+        assert!(
+            ((result - expected) / expected).abs() <= 1e-14,
+            "{case}: {result} != {expected}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn f64_special_values_map_like_f32() {
+    let Some(ctx) = f64_context().await else {
+        return;
+    };
+
+    let output = ctx
+        .compute_f64(&[0., -1., f64::NAN, f64::INFINITY])
+        .await
+        .expect("Failed to compute");
+
+    assert!(output[0].is_nan());
+    assert!(output[1].is_nan());
+    assert!(output[2].is_nan());
+    assert_eq!(output[3], 0.);
+}
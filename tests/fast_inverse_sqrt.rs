@@ -0,0 +1,58 @@
+use demo_wgpu_compute::{GpuContext, Kernel};
+
+/// Positive normal floats from `f32::MIN_POSITIVE` up, about three per
+/// binade.
+fn normals() -> Vec<f32> {
+    (0..760)
+        .map(|i| f32::MIN_POSITIVE * 2f32.powf(i as f32 / 3.))
+        .filter(|x| x.is_normal())
+        .collect()
+}
+
+#[tokio::test]
+async fn error_stays_under_a_fifth_of_a_percent() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = normals();
+
+    let error = ctx
+        .fast_inverse_sqrt_error(&input)
+        .await
+        .expect("Failed to compare kernels");
+
+    assert!(error > 0., "the fast kernel should not be exact");
+    assert!(error < 0.002, "relative error {error}");
+}
+
+#[tokio::test]
+async fn fast_kernel_matches_cpu_reference() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = (1..10_000).map(|x| x as f32).collect::<Vec<_>>();
+
+    let output = ctx
+        .compute_with(Kernel::FastInverseSqrt, &input)
+        .await
+        .expect("Failed to calculate fast inverse sqrt");
+
+    for (case, result) in input.into_iter().zip(output) {
+        let local_result = 1. / case.sqrt();
+        assert!(
+            ((result - local_result) / local_result).abs() < 0.002,
+            "Failed at {case} case. Expected result: {local_result} Received instead: {result}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn zeros_map_to_infinity_and_negatives_to_nan() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    let output = ctx
+        .compute_with(Kernel::FastInverseSqrt, &[0., -0., -4., f32::INFINITY])
+        .await
+        .expect("Failed to calculate fast inverse sqrt");
+
+    assert_eq!(output[0].to_bits(), f32::INFINITY.to_bits());
+    assert_eq!(output[1].to_bits(), f32::NEG_INFINITY.to_bits());
+    assert!(output[2].is_nan(), "{output:?}");
+    assert_eq!(output[3], 0.);
+}
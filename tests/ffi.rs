@@ -0,0 +1,39 @@
+#![cfg(feature = "ffi")]
+
+use demo_wgpu_compute::ffi::*;
+
+#[test]
+fn rejects_invalid_arguments() {
+    let input = [4f32];
+    let mut output = [0f32];
+
+    unsafe {
+        assert_eq!(
+            rsqrt_gpu(std::ptr::null(), output.as_mut_ptr(), 1),
+            RSQRT_GPU_INVALID_ARGUMENTS
+        );
+        assert_eq!(
+            rsqrt_gpu(input.as_ptr(), std::ptr::null_mut(), 1),
+            RSQRT_GPU_INVALID_ARGUMENTS
+        );
+        assert_eq!(
+            rsqrt_gpu(input.as_ptr(), output.as_mut_ptr(), 0),
+            RSQRT_GPU_INVALID_ARGUMENTS
+        );
+    }
+}
+
+#[test]
+fn computes_between_init_and_shutdown() {
+    let input = [4f32, 25., 100.];
+    let mut output = [0f32; 3];
+
+    assert_eq!(rsqrt_gpu_init(), RSQRT_GPU_OK);
+    let code = unsafe { rsqrt_gpu(input.as_ptr(), output.as_mut_ptr(), input.len()) };
+    assert_eq!(code, RSQRT_GPU_OK);
+    assert_eq!(output, [0.5, 0.2, 0.1]);
+
+    assert_eq!(rsqrt_gpu_shutdown(), RSQRT_GPU_OK);
+    let code = unsafe { rsqrt_gpu(input.as_ptr(), output.as_mut_ptr(), input.len()) };
+    assert_eq!(code, RSQRT_GPU_NOT_INITIALIZED);
+}
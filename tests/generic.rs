@@ -0,0 +1,61 @@
+use demo_wgpu_compute::{GpuContext, Kernel};
+
+#[tokio::test]
+async fn u32_round_trip_through_generic_runner() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let cases = [1f32, 4., 16., 1024.];
+    let bits = cases.map(f32::to_bits);
+
+    let output = ctx
+        .run_compute_shader::<u32>(&bits, &Kernel::InverseSqrt)
+        .await
+        .expect("Failed to run shader");
+
+    assert_eq!(output.len(), bits.len());
+    for (case, result) in cases.into_iter().zip(output) {
+        assert!((f32::from_bits(result) - 1. / case.sqrt()).abs() <= 0.000001);
+    }
+}
+
+#[tokio::test]
+async fn f32_path_matches_generic_runner() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = (1..1000).map(|x| x as f32).collect::<Vec<_>>();
+
+    let generic = ctx
+        .run_compute_shader(&input, &Kernel::InverseSqrt)
+        .await
+        .expect("Failed to run shader");
+    let compute = ctx.compute(&input).await.expect("Failed to compute");
+
+    assert_eq!(generic, compute);
+}
+
+#[tokio::test]
+async fn odd_byte_lengths_are_truncated_to_the_input() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    // Three 6-byte elements make 18 bytes, which the copy has to round up
+    // past. The kernel stops at the element count, after three f32s.
+    let mut bytes = [4f32, 16., 64., 256.]
+        .iter()
+        .flat_map(|x| x.to_ne_bytes())
+        .collect::<Vec<_>>();
+    bytes.extend([0xaa, 0xbb]);
+    let input = bytes
+        .chunks_exact(6)
+        .map(|chunk| <[u8; 6]>::try_from(chunk).unwrap())
+        .collect::<Vec<_>>();
+
+    let output = ctx
+        .run_compute_shader(&input, &Kernel::InverseSqrt)
+        .await
+        .expect("Failed to run shader");
+
+    assert_eq!(output.len(), input.len());
+    let results = output
+        .concat()
+        .chunks_exact(4)
+        .map(|word| f32::from_ne_bytes(word.try_into().unwrap()))
+        .collect::<Vec<_>>();
+    assert_eq!(results[..3], [0.5, 0.25, 0.125]);
+}
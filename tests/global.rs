@@ -0,0 +1,36 @@
+#![cfg(feature = "global-context")]
+
+use demo_wgpu_compute::{global, inverse_sqrt};
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn concurrent_first_calls_share_one_context() {
+    let before = global::initializations();
+
+    let tasks = (1..=8)
+        .map(|n| {
+            tokio::spawn(async move {
+                let input = [4., 25., 100.].map(|x: f32| x * (n * n) as f32);
+                let output = inverse_sqrt(&input)
+                    .await
+                    .expect("Failed to calculate inverse sqrt");
+                for (result, expected) in output.iter().zip([0.5, 0.2, 0.1]) {
+                    assert!((result - expected / n as f32).abs() <= 0.000001);
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for task in tasks {
+        task.await.expect("Task panicked");
+    }
+    assert_eq!(global::initializations(), before + 1);
+
+    inverse_sqrt(&[4.]).await.expect("Failed to reuse context");
+    assert_eq!(global::initializations(), before + 1);
+
+    global::shutdown();
+    inverse_sqrt(&[4.])
+        .await
+        .expect("Failed to recreate context");
+    assert_eq!(global::initializations(), before + 2);
+    global::shutdown();
+}
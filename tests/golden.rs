@@ -0,0 +1,63 @@
+use demo_wgpu_compute::GpuContext;
+
+/// Reference results from `cargo run --bin regen-golden`.
+const GOLDEN: &str = include_str!("golden/inverse_sqrt.txt");
+
+/// Inputs and expected outputs, as written by `regen-golden`.
+fn golden() -> (Vec<f32>, Vec<f32>) {
+    GOLDEN
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| {
+            let (input, expected) = line.split_once(' ').expect("Malformed golden line");
+            let bits = |hex| u32::from_str_radix(hex, 16).expect("Malformed golden bits");
+            (f32::from_bits(bits(input)), f32::from_bits(bits(expected)))
+        })
+        .unzip()
+}
+
+/// Distance in units in the last place between two positive floats.
+fn ulps(a: f32, b: f32) -> u32 {
+    a.to_bits().abs_diff(b.to_bits())
+}
+
+#[tokio::test]
+async fn results_match_the_golden_file() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let (input, expected) = golden();
+    assert_eq!(input.len(), 1024);
+
+    let output = ctx.compute(&input).await.expect("Failed to compute");
+
+    let mut exact = 0;
+    let mut max_ulps = 0;
+    let mut diff = Vec::new();
+    for (index, ((case, expected), result)) in input.iter().zip(&expected).zip(&output).enumerate()
+    {
+        let distance = ulps(*expected, *result);
+        exact += usize::from(distance == 0);
+        max_ulps = max_ulps.max(distance);
+        if ((result - expected) / expected).abs() > 0.000001 {
+            diff.push(format!(
+                "{index:>5} {case:>14e} {expected:>14e} {result:>14e} {distance:>6}"
+            ));
+        }
+    }
+    // Bit-exactness is only reported: rounding in the last place can
+    // change with the driver without anything being wrong.
+    println!(
+        "{exact} of {} bit-exact against the golden file, at most {max_ulps} ulps apart",
+        input.len()
+    );
+    assert!(
+        diff.is_empty(),
+        "{} results beyond the tolerance:\n{:>5} {:>14} {:>14} {:>14} {:>6}\n{}",
+        diff.len(),
+        "index",
+        "input",
+        "expected",
+        "got",
+        "ulps",
+        diff.join("\n")
+    );
+}
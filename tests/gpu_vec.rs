@@ -0,0 +1,99 @@
+use demo_wgpu_compute::{GpuContext, Kernel};
+
+#[tokio::test]
+async fn chained_kernels_match_cpu_reference() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = (1..10_000).map(|x| x as f32).collect::<Vec<_>>();
+
+    let output = ctx
+        .compute_gpu(&input)
+        .expect("Failed to upload input")
+        .apply(Kernel::InverseSqrt)
+        .expect("Failed to apply kernel")
+        .read_back()
+        .await
+        .expect("Failed to read back results");
+
+    assert_eq!(output.len(), input.len());
+    for (case, result) in input.into_iter().zip(output) {
+        // 1 / sqrt(1 / sqrt(x)) is the fourth root of x.
+        let local_result = 1. / (1. / case.sqrt()).sqrt();
+        assert!(
+            (local_result - result).abs() <= 0.00001 * local_result,
+            "Failed at {case} case. Expected result: {local_result} Received instead: {result}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn large_upload_matches_queued_upload() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    // Over 4 MiB, so the buffer is filled while mapped at creation.
+    let input = (1..=2_000_000).map(|x| x as f32).collect::<Vec<_>>();
+
+    let mapped = ctx
+        .compute_gpu(&input)
+        .expect("Failed to upload input")
+        .read_back()
+        .await
+        .expect("Failed to read back results");
+    let queued = ctx.compute(&input).await.expect("Failed to compute");
+
+    assert_eq!(mapped, queued);
+}
+
+#[tokio::test]
+async fn mapped_kernels_leave_the_source_unchanged() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = (1..10_000).map(|x| x as f32).collect::<Vec<_>>();
+    let source = ctx.upload(&input).expect("Failed to upload input");
+
+    let inverse_sqrt = source
+        .map(Kernel::InverseSqrt)
+        .expect("Failed to map kernel")
+        .read_back()
+        .await
+        .expect("Failed to read back results");
+    let sqrt = source
+        .map(Kernel::Sqrt)
+        .expect("Failed to map kernel")
+        .read_back()
+        .await
+        .expect("Failed to read back results");
+    let unchanged = source
+        .read_back()
+        .await
+        .expect("Failed to read back source");
+
+    assert_eq!(unchanged, input);
+    for ((case, a), b) in input.into_iter().zip(inverse_sqrt).zip(sqrt) {
+        assert!((a - 1. / case.sqrt()).abs() <= 0.000001, "1 / sqrt({case})");
+        assert!((b - case.sqrt()).abs() <= 0.00001 * b, "sqrt({case})");
+    }
+}
+
+#[tokio::test]
+async fn long_chain_in_one_encoder_matches_the_cpu() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    // Over several workgroups, with a partial one at the end.
+    let input = (1..100_004).map(|x| x as f32 * 0.37).collect::<Vec<_>>();
+    let chain = [Kernel::InverseSqrt, Kernel::Reciprocal, Kernel::InverseSqrt].repeat(50);
+
+    let output = ctx
+        .upload(&input)
+        .expect("Failed to upload input")
+        .apply_chain(&chain)
+        .expect("Failed to apply kernels")
+        .read_back()
+        .await
+        .expect("Failed to read back results");
+
+    assert_eq!(output.len(), input.len());
+    for (case, result) in input.into_iter().zip(output) {
+        let expected = (0..50).fold(case, |x, _| 1. / (1. / (1. / x.sqrt())).sqrt());
+        assert!(
+            (expected - result).abs() <= 0.000001 * expected,
+            "{case}: expected {expected}, got {result}"
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use demo_wgpu_compute::{GpuContext, WORKGROUP_SIZE};
+
+#[tokio::test]
+async fn only_the_written_workgroups_are_computed() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let covered = WORKGROUP_SIZE as usize;
+    let input = vec![4f32; 3 * covered];
+    let args = ctx.indirect_args().expect("Failed to create args");
+
+    args.write(1, 1, 1);
+    let output = ctx
+        .compute_indirect(&input, &args)
+        .await
+        .expect("Failed to compute");
+
+    assert_eq!(output.len(), input.len());
+    assert!(output[..covered].iter().all(|&x| x == 0.5), "{output:?}");
+    assert!(output[covered..].iter().all(|&x| x == 0.), "{output:?}");
+}
+
+#[tokio::test]
+async fn rows_along_y_and_the_bounds_check_apply() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    // Two workgroups, the second mostly past the end.
+    let input = vec![16f32; WORKGROUP_SIZE as usize + 3];
+    let args = ctx.indirect_args().expect("Failed to create args");
+
+    args.write(1, 2, 1);
+    let output = ctx
+        .compute_indirect(&input, &args)
+        .await
+        .expect("Failed to compute");
+
+    assert_eq!(output, vec![0.25; input.len()]);
+}
+
+#[tokio::test]
+async fn fresh_args_dispatch_nothing() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let args = ctx.indirect_args().expect("Failed to create args");
+
+    let output = ctx
+        .compute_indirect(&[4., 16.], &args)
+        .await
+        .expect("Failed to compute");
+
+    assert_eq!(output, [0., 0.]);
+}
@@ -0,0 +1,204 @@
+use demo_wgpu_compute::{
+    compute_blocking, inverse_sqrt, Features, GpuContext, Kernel, ZeroPolicy, WORKGROUP_SIZE,
+};
+use futures::StreamExt;
+
+#[tokio::test]
+async fn reverse_sqrt_10k() {
+    let input = (1..i16::MAX).map(f32::from).collect::<Vec<_>>();
+    let output = inverse_sqrt(&input)
+        .await
+        .expect("Failed to calculate inverse sqrt");
+
+    for (result, case) in output.into_iter().zip(input) {
+        let local_result = 1. / case.sqrt();
+
+        if (local_result - result).abs() > 0.000001 {
+            panic!("Failed at {case} case. Expected result: {local_result} Reeceived instead: {result}")
+        }
+    }
+}
+
+#[tokio::test]
+async fn zeros_return_infinity_with_their_sign() {
+    let output = inverse_sqrt(&[0., -0.])
+        .await
+        .expect("Failed to calculate inverse sqrt");
+
+    assert_eq!(output[0].to_bits(), f32::INFINITY.to_bits());
+    assert_eq!(output[1].to_bits(), f32::NEG_INFINITY.to_bits());
+}
+
+#[tokio::test]
+async fn nan_zero_policy_keeps_the_legacy_nan() {
+    let ctx = GpuContext::builder()
+        .zero_policy(ZeroPolicy::Nan)
+        .build()
+        .await
+        .expect("Failed to create context");
+
+    let output = ctx.compute(&[0., -0.]).await.expect("Failed to compute");
+
+    assert!(output.iter().all(|x| x.is_nan()), "{output:?}");
+}
+
+#[tokio::test]
+async fn cast_output_matches_bytewise_conversion() {
+    // The raw bytes skip the zero policy, so keep the shader's NaN.
+    let ctx = GpuContext::builder()
+        .zero_policy(ZeroPolicy::Nan)
+        .build()
+        .await
+        .expect("Failed to create context");
+    let input = [0., 1., 2., 0.25, 1e-30, 1e30, 12345.678];
+
+    let cast = ctx.compute(&input).await.expect("Failed to compute");
+    let bytes = input
+        .iter()
+        .flat_map(|x| x.to_ne_bytes())
+        .collect::<Vec<_>>();
+    let bytewise = ctx
+        .run_kernel(&Kernel::InverseSqrt, &bytes)
+        .await
+        .expect("Failed to run kernel")
+        .chunks_exact(4)
+        .map(|bytes| f32::from_ne_bytes(bytes.try_into().unwrap()))
+        .collect::<Vec<_>>();
+
+    assert!(cast[0].is_nan());
+    assert_eq!(
+        cast.iter().map(|x| x.to_bits()).collect::<Vec<_>>(),
+        bytewise.iter().map(|x| x.to_bits()).collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn empty_input_gives_empty_output() {
+    let output = inverse_sqrt(&[])
+        .await
+        .expect("Failed to calculate inverse sqrt");
+
+    assert!(output.is_empty());
+}
+
+#[tokio::test]
+async fn single_element() {
+    let output = inverse_sqrt(&[4.])
+        .await
+        .expect("Failed to calculate inverse sqrt");
+
+    assert_eq!(output, [0.5]);
+}
+
+#[tokio::test]
+async fn lengths_around_the_workgroup_size() {
+    let size = WORKGROUP_SIZE as usize;
+    for len in [1, size - 1, size, size + 1, 1_000_003] {
+        let input = (1..=len).map(|x| x as f32).collect::<Vec<_>>();
+        let output = inverse_sqrt(&input)
+            .await
+            .expect("Failed to calculate inverse sqrt");
+
+        assert_eq!(output.len(), len);
+        for (result, case) in output.into_iter().zip(input) {
+            let local_result = 1. / case.sqrt();
+            assert!(
+                (local_result - result).abs() <= 0.000001,
+                "len {len}, case {case}: {result} != {local_result}"
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn whole_and_partial_workgroups_match_the_cpu_exactly() {
+    let ctx = GpuContext::builder()
+        .debug(true)
+        .build()
+        .await
+        .expect("Failed to create context");
+    let size = WORKGROUP_SIZE as usize;
+    // Largest first, so the smaller lengths run in pooled buffers with
+    // room past their end for stray invocations to write to.
+    for len in [64 * size + 1, 64 * size, size + 1, size, 1] {
+        let input = (1..=len).map(|x| x as f32 * 0.37).collect::<Vec<_>>();
+        let output = ctx.compute(&input).await.expect("Failed to compute");
+
+        assert_eq!(output.len(), len);
+        for (case, result) in input.iter().zip(&output) {
+            assert_eq!(
+                result.to_bits(),
+                (1. / case.sqrt()).to_bits(),
+                "len {len}, case {case}: {result}"
+            );
+        }
+    }
+    assert!(ctx.pool_stats().hits > 0);
+}
+
+#[tokio::test]
+async fn one_past_a_workgroup_stops_at_the_element_count() {
+    let input = (1..=65).map(|x| x as f32).collect::<Vec<_>>();
+    let whole = GpuContext::builder()
+        .debug(true)
+        .build()
+        .await
+        .expect("Failed to create context");
+    // The last chunk holds a single element in buffers sized for 64.
+    let chunked = GpuContext::builder()
+        .debug(true)
+        .max_chunk_len(64)
+        .build()
+        .await
+        .expect("Failed to create context");
+
+    for ctx in [whole, chunked] {
+        let output = ctx.compute(&input).await.expect("Failed to compute");
+        assert_eq!(output.len(), 65);
+        assert!((output[64] - 1. / 65f32.sqrt()).abs() <= 0.000001);
+        let streamed = ctx
+            .compute_stream(input.iter().copied(), 64)
+            .collect::<Vec<_>>()
+            .await;
+        assert_eq!(streamed.len(), 2);
+        let last = streamed[1].as_ref().expect("Failed to compute");
+        assert_eq!(last.len(), 1);
+        assert_eq!(last[0].to_bits(), output[64].to_bits());
+    }
+}
+
+#[tokio::test]
+async fn infinity_and_subnormals_match_a_double_precision_reference() {
+    let input = [
+        f32::INFINITY,
+        f32::MIN_POSITIVE,
+        f32::MIN_POSITIVE / 2.,
+        f32::from_bits(1),
+        f32::from_bits(2),
+        f32::from_bits(0x7f_ffff),
+        1e-40,
+        f32::MAX,
+    ];
+    for disabled in [Features::empty(), Features::SPIRV_SHADER_PASSTHROUGH] {
+        let ctx = GpuContext::builder()
+            .disable_features(disabled)
+            .build()
+            .await
+            .expect("Failed to create context");
+        let output = ctx.compute(&input).await.expect("Failed to compute");
+
+        assert_eq!(output[0].to_bits(), 0f32.to_bits());
+        for (&case, &result) in input.iter().zip(&output).skip(1) {
+            let expected = 1. / (case as f64).sqrt();
+            let error = (result as f64 - expected).abs() / expected;
+            assert!(error <= 1e-6, "{case:e}: {result:e} != {expected:e}");
+        }
+    }
+}
+
+#[test]
+fn compute_blocking_without_runtime() {
+    let output = compute_blocking(&[4., 25., 100.]).expect("Failed to compute inverse sqrt");
+
+    assert_eq!(output, [0.5, 0.2, 0.1]);
+}
@@ -0,0 +1,47 @@
+use demo_wgpu_compute::{ComputeOptions, GpuContext, Kernel};
+
+async fn inverse_square(input: &[f32]) -> Vec<f32> {
+    let ctx = GpuContext::with_options(ComputeOptions::new().validate_input(true))
+        .await
+        .expect("Failed to create context");
+    ctx.compute_with(Kernel::InverseSquare, input)
+        .await
+        .expect("Failed to compute")
+}
+
+#[tokio::test]
+async fn distances_match_the_cpu() {
+    let input = (1..10_000)
+        .map(|x| x as f32 * 0.37)
+        .chain([-2., -0.5, 1e-10, 1e15, -1e18])
+        .collect::<Vec<_>>();
+
+    let output = inverse_square(&input).await;
+
+    for (case, result) in input.into_iter().zip(output) {
+        let expected = (1. / (f64::from(case) * f64::from(case))) as f32;
+        assert!(
+            ((result - expected) / expected).abs() <= 1e-6,
+            "{case}: expected {expected}, got {result}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn overflow_and_both_zeros_are_infinity() {
+    let output = inverse_square(&[1e-20, -1e-30, f32::from_bits(1), 0., -0.]).await;
+
+    assert_eq!(output[0], f32::INFINITY);
+    assert_eq!(output[1], f32::INFINITY);
+    assert_eq!(output[2], f32::INFINITY);
+    // Both zeros square to +0.
+    assert_eq!(output[3].to_bits(), f32::INFINITY.to_bits());
+    assert_eq!(output[4].to_bits(), f32::INFINITY.to_bits());
+}
+
+#[tokio::test]
+async fn huge_inputs_and_infinities_underflow_to_zero() {
+    let output = inverse_square(&[1e30, f32::MAX, f32::INFINITY, f32::NEG_INFINITY]).await;
+
+    assert!(output.iter().all(|&x| x == 0.), "{output:?}");
+}
@@ -0,0 +1,201 @@
+use demo_wgpu_compute::{ComputeError, Features, GpuContext, GpuKernel, Kernel, WORKGROUP_SIZE};
+
+#[tokio::test]
+async fn compute_with_inverse_sqrt_matches_compute() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = (0..1000).map(|x| x as f32).collect::<Vec<_>>();
+
+    let with_kernel = ctx
+        .compute_with(Kernel::InverseSqrt, &input)
+        .await
+        .expect("Failed to compute with kernel");
+    let compute = ctx.compute(&input).await.expect("Failed to compute");
+
+    // Compare the bits, which tell the zero's infinity from NaN.
+    let bits = |v: &[f32]| v.iter().map(|x| x.to_bits()).collect::<Vec<_>>();
+    assert_eq!(bits(&with_kernel), bits(&compute));
+}
+
+/// The built-in SPIR-V and WGSL behind a caller-defined kernel type. The
+/// GLSL that naga translates SPIR-V to has no NaN literal, so GL devices
+/// need the WGSL.
+struct Wrapped {
+    spirv: Vec<u8>,
+    wgsl: String,
+}
+
+impl GpuKernel for Wrapped {
+    fn spirv(&self) -> &[u8] {
+        &self.spirv
+    }
+
+    fn entry_point(&self) -> &str {
+        "main_cs"
+    }
+
+    fn wgsl(&self) -> Option<&str> {
+        Some(&self.wgsl)
+    }
+
+    fn workgroup_size(&self) -> u32 {
+        WORKGROUP_SIZE
+    }
+
+    fn element_count(&self) -> bool {
+        true
+    }
+}
+
+#[tokio::test]
+async fn custom_kernel_runs_through_generic_path() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let kernel = Wrapped {
+        spirv: Kernel::InverseSqrt.spirv().to_vec(),
+        wgsl: Kernel::InverseSqrt.wgsl().unwrap().to_owned(),
+    };
+    let input = [4f32, 16., 64.];
+
+    let output = ctx
+        .run_kernel(&kernel, bytemuck::cast_slice(&input))
+        .await
+        .expect("Failed to run kernel");
+
+    assert_eq!(bytemuck::cast_slice::<u8, f32>(&output), [0.5, 0.25, 0.125]);
+}
+
+#[tokio::test]
+async fn partial_element_is_rejected() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    let result = ctx.run_kernel(&Kernel::InverseSqrt, &[0, 0, 128]).await;
+
+    assert!(
+        matches!(
+            result,
+            Err(ComputeError::Misaligned {
+                len: 3,
+                element_size: 4
+            })
+        ),
+        "{result:?}"
+    );
+}
+
+#[tokio::test]
+async fn bad_lengths_are_rejected_before_submitting() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let submissions = ctx.submissions();
+
+    for len in [0, 3, 5] {
+        let result = ctx.run_kernel(&Kernel::InverseSqrt, &vec![0; len]).await;
+
+        match (len, result) {
+            (0, Err(ComputeError::EmptyInput)) => {}
+            (
+                _,
+                Err(ComputeError::Misaligned {
+                    len: rejected,
+                    element_size: 4,
+                }),
+            ) if rejected == len => {}
+            (_, result) => panic!("{len} bytes: {result:?}"),
+        }
+    }
+    assert_eq!(ctx.submissions(), submissions);
+}
+
+#[tokio::test]
+async fn wgsl_matches_spirv() {
+    let spirv = GpuContext::new().await.expect("Failed to create context");
+    let wgsl = GpuContext::builder()
+        .disable_features(Features::SPIRV_SHADER_PASSTHROUGH)
+        .build()
+        .await
+        .expect("Failed to create WGSL context");
+    let input = (0..1000).map(|x| x as f32).collect::<Vec<_>>();
+
+    let pairs = [
+        (
+            spirv.compute(&input).await.expect("Failed to compute"),
+            wgsl.compute(&input).await.expect("Failed to compute"),
+        ),
+        (
+            spirv
+                .compute_scaled(&input, 2.)
+                .await
+                .expect("Failed to compute"),
+            wgsl.compute_scaled(&input, 2.)
+                .await
+                .expect("Failed to compute"),
+        ),
+    ];
+    for (from_spirv, from_wgsl) in pairs {
+        for (case, (a, b)) in input.iter().zip(from_spirv.iter().zip(from_wgsl)) {
+            assert!(
+                *a == b || (a.is_nan() && b.is_nan()) || (a - b).abs() <= 0.000001,
+                "{case}: {a} from SPIR-V, {b} from WGSL"
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn kernels_from_different_modules_share_a_context() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = (1..1000).map(|x| x as f32).collect::<Vec<_>>();
+
+    // Back and forth, so the second inverse sqrt runs on the cached
+    // pipeline after the sqrt module was loaded.
+    for kernel in [Kernel::InverseSqrt, Kernel::Sqrt, Kernel::InverseSqrt] {
+        let output = ctx
+            .compute_with(kernel, &input)
+            .await
+            .expect("Failed to compute");
+
+        for (case, result) in input.iter().zip(output) {
+            let expected = match kernel {
+                Kernel::Sqrt => case.sqrt(),
+                _ => 1. / case.sqrt(),
+            };
+            assert!(
+                (expected - result).abs() <= 0.000001 * expected,
+                "{kernel:?} at {case}: expected {expected}, got {result}"
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn unaligned_bytes_run_like_typed_elements() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = (1..1000).map(|x| x as f32 * 0.5).collect::<Vec<_>>();
+    // One byte into a larger buffer, so the bytes aren't aligned for f32.
+    let mut buffer = vec![0u8; 1];
+    buffer.extend_from_slice(bytemuck::cast_slice(&input));
+    let bytes = &buffer[1..];
+    assert!(bytemuck::try_cast_slice::<u8, f32>(bytes).is_err());
+
+    let from_bytes = ctx
+        .run_compute_shader_bytes::<f32>(bytes, &Kernel::InverseSqrt)
+        .await
+        .expect("Failed to run kernel");
+    let typed = ctx
+        .run_compute_shader(&input, &Kernel::InverseSqrt)
+        .await
+        .expect("Failed to run kernel");
+    assert_eq!(from_bytes, typed);
+
+    let result = ctx
+        .run_compute_shader_bytes::<f32>(&buffer[1..6], &Kernel::InverseSqrt)
+        .await;
+    assert!(
+        matches!(
+            result,
+            Err(ComputeError::Misaligned {
+                len: 5,
+                element_size: 4
+            })
+        ),
+        "{result:?}"
+    );
+}
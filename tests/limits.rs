@@ -0,0 +1,280 @@
+use demo_wgpu_compute::{ComputeError, GpuContext, Kernel, Limits, WORKGROUP_SIZE};
+
+/// A binding limit of 64 `f32`s.
+const MAX_BINDING_SIZE: u32 = 64 * 4;
+
+fn reference(input: &[f32]) -> Vec<f32> {
+    input.iter().map(|x| 1. / x.sqrt()).collect()
+}
+
+fn assert_close(output: &[f32], expected: &[f32]) {
+    assert_eq!(output.len(), expected.len());
+    for (result, expected) in output.iter().zip(expected) {
+        assert!(
+            (result - expected).abs() <= 0.000001,
+            "{result} != {expected}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn too_large_without_splitting() {
+    let ctx = GpuContext::builder()
+        .max_binding_size(MAX_BINDING_SIZE)
+        .split_large_inputs(false)
+        .build()
+        .await
+        .expect("Failed to create context");
+
+    let fits = (1..=64).map(|x| x as f32).collect::<Vec<_>>();
+    let output = ctx.compute(&fits).await.expect("Failed to compute");
+    assert_close(&output, &reference(&fits));
+
+    let too_large = (1..=65).map(|x| x as f32).collect::<Vec<_>>();
+    match ctx.compute(&too_large).await {
+        Err(ComputeError::TooLarge { requested, max }) => {
+            assert_eq!(requested, 65);
+            assert_eq!(max, 64);
+        }
+        other => panic!("expected TooLarge, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn large_input_is_split_across_dispatches() {
+    let ctx = GpuContext::builder()
+        .max_binding_size(MAX_BINDING_SIZE)
+        .build()
+        .await
+        .expect("Failed to create context");
+    let input = (1..=1000).map(|x| x as f32).collect::<Vec<_>>();
+
+    let output = ctx.compute(&input).await.expect("Failed to compute");
+    assert_close(&output, &reference(&input));
+
+    let scaled = ctx
+        .compute_scaled(&input, 2.)
+        .await
+        .expect("Failed to compute scaled");
+    let doubled = reference(&input).iter().map(|x| 2. * x).collect::<Vec<_>>();
+    assert_close(&scaled, &doubled);
+
+    let mut data = input.clone();
+    ctx.compute_into(&mut data)
+        .await
+        .expect("Failed to compute in place");
+    assert_close(&data, &reference(&input));
+
+    assert!(matches!(
+        ctx.compute_gpu(&input),
+        Err(ComputeError::TooLarge {
+            requested: 1000,
+            max: 64
+        })
+    ));
+}
+
+#[tokio::test]
+async fn downlevel_limits_compute_correctly() {
+    let ctx = GpuContext::builder()
+        .downlevel_limits(true)
+        .build()
+        .await
+        .expect("Failed to create context");
+    let input = (1..=1000).map(|x| x as f32).collect::<Vec<_>>();
+
+    let output = ctx.compute(&input).await.expect("Failed to compute");
+    assert_close(&output, &reference(&input));
+}
+
+#[tokio::test]
+async fn adapter_limits_are_at_least_the_defaults() {
+    let ctx = GpuContext::builder()
+        .use_adapter_limits(true)
+        .build()
+        .await
+        .expect("Failed to create context");
+
+    assert!(
+        ctx.limits().max_storage_buffer_binding_size
+            >= Limits::default().max_storage_buffer_binding_size
+    );
+}
+
+#[tokio::test]
+async fn chunked_run_matches_single_dispatch() {
+    let single = GpuContext::new().await.expect("Failed to create context");
+    let chunked = GpuContext::builder()
+        .max_chunk_len(1000)
+        .build()
+        .await
+        .expect("Failed to create context");
+    let input = (0..100_003).map(|x| x as f32 * 0.25).collect::<Vec<_>>();
+
+    let expected = single.compute(&input).await.expect("Failed to compute");
+    let output = chunked.compute(&input).await.expect("Failed to compute");
+    assert_eq!(output.len(), input.len());
+    for (index, (result, expected)) in output.iter().zip(&expected).enumerate() {
+        assert_eq!(result.to_bits(), expected.to_bits(), "at {index}");
+    }
+}
+
+#[tokio::test]
+async fn tiny_budget_rejects_one_buffer_and_chunks_the_rest() {
+    let budget = 64 << 10;
+    let ctx = GpuContext::builder()
+        .memory_budget(budget)
+        .build()
+        .await
+        .expect("Failed to create context");
+    let unsplit = GpuContext::builder()
+        .memory_budget(budget)
+        .split_large_inputs(false)
+        .build()
+        .await
+        .expect("Failed to create context");
+    let input = (1..=100_000).map(|x| x as f32).collect::<Vec<_>>();
+
+    let result = ctx.compute_gpu(&input).map(|_| ());
+    assert!(
+        matches!(
+            result,
+            Err(ComputeError::OutOfBudget {
+                required: 800_000,
+                budget: 65_536
+            })
+        ),
+        "{result:?}"
+    );
+    let result = unsplit.compute(&input).await;
+    assert!(
+        matches!(result, Err(ComputeError::OutOfBudget { .. })),
+        "{result:?}"
+    );
+
+    let output = ctx.compute(&input).await.expect("Failed to compute");
+    assert_eq!(output.len(), input.len());
+    for (case, result) in input.into_iter().zip(output) {
+        assert!((result - 1. / case.sqrt()).abs() <= 0.000001, "at {case}");
+    }
+}
+
+#[tokio::test]
+async fn grid_stride_kernel_fits_inputs_past_the_workgroup_limit() {
+    let capped = GpuContext::builder()
+        .max_workgroups(16)
+        .split_large_inputs(false)
+        .build()
+        .await
+        .expect("Failed to create context");
+    let reference = GpuContext::new().await.expect("Failed to create context");
+    // Past what 16 by 16 workgroups cover at one element per invocation,
+    // even with the plain kernel spilling into y.
+    let covered = 16 * 16 * WORKGROUP_SIZE as usize;
+    let input = (0..covered + 3)
+        .map(|x| x as f32 * 0.75)
+        .collect::<Vec<_>>();
+
+    let result = capped.compute_with(Kernel::InverseSqrt, &input).await;
+    assert!(
+        matches!(result, Err(ComputeError::TooLarge { max, .. }) if max == covered),
+        "{result:?}"
+    );
+
+    let grid_stride = capped
+        .compute_with(Kernel::InverseSqrtGridStride, &input)
+        .await
+        .expect("Failed to compute");
+    let expected = reference.compute(&input).await.expect("Failed to compute");
+    assert_eq!(grid_stride.len(), input.len());
+    for (index, (a, b)) in grid_stride.iter().zip(&expected).enumerate() {
+        assert_eq!(a.to_bits(), b.to_bits(), "at {index}");
+    }
+}
+
+#[tokio::test]
+async fn dispatches_past_the_workgroup_limit_spill_into_y() {
+    let capped = GpuContext::builder()
+        .max_workgroups(16)
+        .split_large_inputs(false)
+        .build()
+        .await
+        .expect("Failed to create context");
+    // Ten rows of 16 workgroups and a partial one, in a single dispatch.
+    let input = (0..160 * WORKGROUP_SIZE as usize + 3)
+        .map(|x| x as f32 * 0.75)
+        .collect::<Vec<_>>();
+
+    for kernel in [Kernel::InverseSqrt, Kernel::Sqrt, Kernel::Reciprocal] {
+        let output = capped
+            .compute_with(kernel, &input)
+            .await
+            .expect("Failed to compute");
+
+        assert_eq!(output.len(), input.len());
+        for (index, (case, result)) in input.iter().zip(output).enumerate() {
+            let expected = match kernel {
+                Kernel::Sqrt => case.sqrt(),
+                Kernel::Reciprocal => 1. / case,
+                _ => 1. / case.sqrt(),
+            };
+            assert!(
+                (case == &0. && result.is_nan() && kernel != Kernel::Sqrt)
+                    || result.to_bits() == expected.to_bits(),
+                "{kernel:?} at {index}: expected {expected}, got {result}"
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn tiny_inputs_are_computed_under_a_zero_workgroup_cap() {
+    let capped = GpuContext::builder()
+        .max_workgroups(0)
+        .build()
+        .await
+        .expect("Failed to create context");
+
+    for input in [&[4f32][..], &[4., 16.]] {
+        for kernel in [Kernel::InverseSqrt, Kernel::InverseSqrtGridStride] {
+            let output = capped
+                .compute_with(kernel, input)
+                .await
+                .expect("Failed to compute");
+            assert_eq!(output, reference(input), "{kernel:?}");
+        }
+    }
+}
+
+#[tokio::test]
+async fn overlapping_small_chunks_never_read_stale_results() {
+    let ctx = GpuContext::builder()
+        .max_chunk_len(7)
+        .build()
+        .await
+        .expect("Failed to create context");
+    // Eight calls at once, each hundreds of chunks, over inputs that differ
+    // everywhere, so a result from another chunk or call would show.
+    let inputs = (0..8)
+        .map(|call| {
+            (0..3001)
+                .map(|x| (x * 8 + call + 1) as f32)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let outputs = futures::future::try_join_all(inputs.iter().map(|input| ctx.compute(input)))
+        .await
+        .expect("Failed to compute");
+
+    for (call, (input, output)) in inputs.iter().zip(outputs).enumerate() {
+        assert_eq!(output.len(), input.len());
+        for (index, (result, expected)) in output.iter().zip(reference(input)).enumerate() {
+            assert_eq!(
+                result.to_bits(),
+                expected.to_bits(),
+                "call {call} at {index}: {result} != {expected}"
+            );
+        }
+    }
+}
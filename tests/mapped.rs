@@ -0,0 +1,21 @@
+use demo_wgpu_compute::GpuContext;
+
+#[tokio::test]
+async fn mapped_view_unmaps_on_drop() {
+    let mut ctx = GpuContext::new().await.expect("Failed to create context");
+
+    let results = ctx
+        .compute_mapped(&[4., 25., 100.])
+        .await
+        .expect("Failed to compute");
+    assert_eq!(*results, [0.5, 0.2, 0.1]);
+    drop(results);
+
+    // A smaller input reuses the readback buffer, which only works if the
+    // previous view unmapped it.
+    let results = ctx
+        .compute_mapped(&[16., 64.])
+        .await
+        .expect("Failed to compute after dropping the view");
+    assert_eq!(*results, [0.25, 0.125]);
+}
@@ -0,0 +1,71 @@
+use demo_wgpu_compute::{ComputeError, GpuContext};
+
+/// A `width` by `height` matrix of distinct positive values.
+fn matrix(width: u32, height: u32) -> Vec<f32> {
+    (0..width * height).map(|i| i as f32 * 0.5 + 1.).collect()
+}
+
+async fn assert_matches_cpu(ctx: &GpuContext, width: u32, height: u32) {
+    let input = matrix(width, height);
+
+    let output = ctx
+        .compute_2d(&input, width, height)
+        .await
+        .expect("Failed to compute");
+
+    assert_eq!(output.len(), input.len());
+    for (index, (case, result)) in input.iter().zip(output).enumerate() {
+        let expected = 1. / case.sqrt();
+        assert!(
+            (expected - result).abs() <= 0.000001 * expected,
+            "{width}x{height} at row {}, column {}: expected {expected}, got {result}",
+            index / width as usize,
+            index % width as usize
+        );
+    }
+}
+
+#[tokio::test]
+async fn small_matrix_matches_cpu_reference() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    assert_matches_cpu(&ctx, 7, 5).await;
+}
+
+#[tokio::test]
+async fn large_matrix_matches_cpu_reference() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    assert_matches_cpu(&ctx, 1024, 769).await;
+}
+
+#[tokio::test]
+async fn chunks_keep_whole_rows() {
+    // Not a multiple of the width, so chunks have to be shortened.
+    let chunked = GpuContext::builder()
+        .max_chunk_len(1001)
+        .build()
+        .await
+        .expect("Failed to create context");
+
+    assert_matches_cpu(&chunked, 7, 500).await;
+}
+
+#[tokio::test]
+async fn shape_must_match_the_input() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    let result = ctx.compute_2d(&matrix(7, 5), 5, 5).await;
+
+    assert!(
+        matches!(
+            result,
+            Err(ComputeError::InvalidShape {
+                len: 35,
+                width: 5,
+                height: 5
+            })
+        ),
+        "{result:?}"
+    );
+}
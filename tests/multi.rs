@@ -0,0 +1,88 @@
+use demo_wgpu_compute::{ComputeError, ComputeOptions, GpuContext, MultiGpuContext};
+
+fn bits(values: &[f32]) -> Vec<u32> {
+    values.iter().map(|x| x.to_bits()).collect()
+}
+
+async fn context(options: ComputeOptions) -> GpuContext {
+    GpuContext::with_options(options)
+        .await
+        .expect("Failed to create context")
+}
+
+#[tokio::test]
+async fn single_context_matches_normal_path() {
+    let ctx = context(ComputeOptions::new()).await;
+    let input = (0..1000).map(|x| x as f32).collect::<Vec<_>>();
+    let expected = ctx.compute(&input).await.expect("Failed to compute");
+
+    let multi = MultiGpuContext::from_contexts(vec![ctx]);
+    let output = multi.compute(&input).await.expect("Failed to compute");
+    assert_eq!(bits(&output), bits(&expected));
+}
+
+#[tokio::test]
+async fn shards_are_stitched_in_order() {
+    let input = (0..1001).map(|x| x as f32).collect::<Vec<_>>();
+    let expected = context(ComputeOptions::new())
+        .await
+        .compute(&input)
+        .await
+        .expect("Failed to compute");
+
+    // Several contexts on the same adapter exercise the sharding as well as
+    // several adapters would, with uneven shards and an empty one.
+    let mut contexts = Vec::new();
+    for _ in 0..3 {
+        contexts.push(context(ComputeOptions::new()).await);
+    }
+    let multi = MultiGpuContext::from_contexts(contexts).weights(&[1., 0., 3.]);
+    let output = multi.compute(&input).await.expect("Failed to compute");
+    assert_eq!(bits(&output), bits(&expected));
+}
+
+#[tokio::test]
+async fn validation_errors_index_the_whole_input() {
+    let options = ComputeOptions::new().validate_input(true);
+    let multi = MultiGpuContext::from_contexts(vec![
+        context(options.clone()).await,
+        context(options).await,
+    ]);
+    let mut input = vec![1.; 100];
+    input[75] = -1.;
+
+    match multi.compute(&input).await {
+        Err(ComputeError::InvalidInput { index, .. }) => assert_eq!(index, 75),
+        other => panic!("expected InvalidInput, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn failed_shard_fails_or_reruns() {
+    let input = (1..=100).map(|x| x as f32).collect::<Vec<_>>();
+    let expected = context(ComputeOptions::new())
+        .await
+        .compute(&input)
+        .await
+        .expect("Failed to compute");
+    let failing = ComputeOptions::new().max_retries(0);
+
+    let multi = MultiGpuContext::from_contexts(vec![
+        context(ComputeOptions::new()).await,
+        context(failing.clone()).await,
+    ]);
+    multi.contexts()[1].inject_readback_failures(1);
+    assert!(matches!(
+        multi.compute(&input).await,
+        Err(ComputeError::DeviceLost)
+    ));
+
+    let multi = MultiGpuContext::from_contexts(vec![
+        context(ComputeOptions::new()).await,
+        context(failing).await,
+    ])
+    .rerun_failed_shards(true);
+    multi.contexts()[1].inject_readback_failures(1);
+    let output = multi.compute(&input).await.expect("Failed to rerun shard");
+    assert_eq!(bits(&output), bits(&expected));
+}
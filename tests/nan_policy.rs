@@ -0,0 +1,95 @@
+use demo_wgpu_compute::{
+    ComputeError, ComputeOptions, Features, GpuContext, NanPolicy, ZeroPolicy,
+};
+
+/// Every input without a real inverse square root, then two with one.
+const INPUT: [f32; 5] = [f32::NAN, -1., f32::NEG_INFINITY, 4., f32::INFINITY];
+
+/// A context on the SPIR-V path where the device takes it, and one on the
+/// WGSL path.
+async fn contexts(options: ComputeOptions) -> [GpuContext; 2] {
+    let spirv = GpuContext::with_options(options.clone())
+        .await
+        .expect("Failed to create context");
+    let wgsl =
+        GpuContext::with_options(options.disable_features(Features::SPIRV_SHADER_PASSTHROUGH))
+            .await
+            .expect("Failed to create WGSL context");
+    [spirv, wgsl]
+}
+
+#[tokio::test]
+async fn non_finite_cases_propagate_nan() {
+    for ctx in contexts(ComputeOptions::new()).await {
+        let output = ctx.compute(&INPUT).await.expect("Failed to compute");
+
+        assert!(output[..3].iter().all(|x| x.is_nan()), "{output:?}");
+        assert_eq!(output[3..], [0.5, 0.]);
+    }
+}
+
+#[tokio::test]
+async fn zero_fill_replaces_every_nan() {
+    let options = ComputeOptions::new()
+        .zero_policy(ZeroPolicy::Nan)
+        .nan_policy(NanPolicy::ZeroFill);
+    for ctx in contexts(options).await {
+        let output = ctx.compute(&INPUT).await.expect("Failed to compute");
+        assert_eq!(output, [0., 0., 0., 0.5, 0.]);
+
+        // Zeros under the NaN zero policy too.
+        let output = ctx.compute(&[0., -0.]).await.expect("Failed to compute");
+        assert_eq!(output, [0., 0.]);
+    }
+}
+
+#[tokio::test]
+async fn error_reports_the_first_nan() {
+    let options = ComputeOptions::new().nan_policy(NanPolicy::Error);
+    for ctx in contexts(options).await {
+        let result = ctx.compute(&[4., 16., -1., f32::NAN]).await;
+        assert!(
+            matches!(result, Err(ComputeError::NanInOutput { first_index: 2 })),
+            "{result:?}"
+        );
+
+        let output = ctx.compute(&INPUT[3..]).await.expect("Failed to compute");
+        assert_eq!(output, [0.5, 0.]);
+    }
+}
+
+#[tokio::test]
+async fn zero_policy_applies_before_the_nan_policy() {
+    let options = ComputeOptions::new()
+        .zero_policy(ZeroPolicy::Infinity)
+        .nan_policy(NanPolicy::Error);
+    for ctx in contexts(options).await {
+        let output = ctx.compute(&[0., -0.]).await.expect("Failed to compute");
+        assert_eq!(output, [f32::INFINITY, f32::NEG_INFINITY]);
+
+        let result = ctx.compute(&[0., f32::NAN]).await;
+        assert!(
+            matches!(result, Err(ComputeError::NanInOutput { first_index: 1 })),
+            "{result:?}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn report_and_in_place_paths_follow_the_policy() {
+    let options = ComputeOptions::new().nan_policy(NanPolicy::ZeroFill);
+    let [ctx, _] = contexts(options).await;
+
+    let (output, report) = ctx
+        .compute_with_report(&INPUT)
+        .await
+        .expect("Failed to compute");
+    assert_eq!(output, [0., 0., 0., 0.5, 0.]);
+    assert_eq!(report.nan_count, 3);
+
+    let mut data = INPUT;
+    ctx.compute_into(&mut data)
+        .await
+        .expect("Failed to compute");
+    assert_eq!(data, [0., 0., 0., 0.5, 0.]);
+}
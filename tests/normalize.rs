@@ -0,0 +1,73 @@
+use demo_wgpu_compute::{ComputeError, ComputeOptions, GpuContext};
+
+#[tokio::test]
+async fn normalized_vectors_have_unit_length() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    // Past one workgroup, with lengths from tiny to huge.
+    let input = (1..1000)
+        .map(|i| {
+            let i = i as f32;
+            let scale = 10f32.powi(i as i32 % 30 - 15);
+            [i.sin() * scale, i.cos() * scale, (i * 0.37).sin() * scale]
+        })
+        .collect::<Vec<_>>();
+
+    let output = ctx
+        .normalize_vec3(&input)
+        .await
+        .expect("Failed to normalize");
+
+    assert_eq!(output.len(), input.len());
+    for (vector, [x, y, z]) in input.iter().zip(output) {
+        let length = (x * x + y * y + z * z).sqrt();
+        assert!((length - 1.).abs() <= 1e-6, "{vector:?}: length {length}");
+        // Same direction as the input.
+        assert!(vector[0] * x + vector[1] * y + vector[2] * z > 0.);
+    }
+}
+
+#[tokio::test]
+async fn zero_vectors_pass_through_or_become_nan() {
+    let input = [[0., 0., 0.], [3., 0., 4.], [-0., 0., -0.]];
+
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let output = ctx
+        .normalize_vec3(&input)
+        .await
+        .expect("Failed to normalize");
+    assert_eq!(output, [[0., 0., 0.], [0.6, 0., 0.8], [-0., 0., -0.]]);
+
+    let options = ComputeOptions::new().nan_zero_vectors(true);
+    let ctx = GpuContext::with_options(options)
+        .await
+        .expect("Failed to create context");
+    let output = ctx
+        .normalize_vec3(&input)
+        .await
+        .expect("Failed to normalize");
+    assert!(output[0].iter().chain(&output[2]).all(|c| c.is_nan()));
+    assert_eq!(output[1], [0.6, 0., 0.8]);
+}
+
+#[tokio::test]
+async fn flat_input_must_hold_whole_vectors() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    let output = ctx
+        .normalize_vec3_flat(&[0., 2., 0., 0., 0., -5.])
+        .await
+        .expect("Failed to normalize");
+    assert_eq!(output, [0., 1., 0., 0., 0., -1.]);
+
+    let err = ctx
+        .normalize_vec3_flat(&[1., 2., 3., 4.])
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ComputeError::Misaligned {
+            len: 16,
+            element_size: 12
+        }
+    ));
+}
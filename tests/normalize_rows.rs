@@ -0,0 +1,67 @@
+use demo_wgpu_compute::{ComputeError, GpuContext, WORKGROUP_SIZE};
+
+/// `rows` rows of `dim` elements, with norms from tiny to huge.
+fn matrix(rows: usize, dim: usize) -> Vec<f32> {
+    (0..rows * dim)
+        .map(|i| {
+            let scale = 10f32.powi((i / dim) as i32 % 20 - 10);
+            (i as f32 * 0.37).sin() * scale
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn normalized_rows_have_unit_norm() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    // Shorter than a workgroup, exactly one, and longer than four, so
+    // some lanes loop over more elements than others.
+    for dim in [3, WORKGROUP_SIZE as usize, 4 * WORKGROUP_SIZE as usize + 1] {
+        let input = matrix(100, dim);
+        let output = ctx
+            .normalize_rows(&input, dim)
+            .await
+            .expect("Failed to normalize");
+
+        assert_eq!(output.len(), input.len());
+        for (row, (case, result)) in input.chunks(dim).zip(output.chunks(dim)).enumerate() {
+            let norm = result.iter().map(|x| x * x).sum::<f32>().sqrt();
+            assert!(
+                (norm - 1.).abs() <= 1e-5,
+                "dim {dim}, row {row}: norm {norm}"
+            );
+            // Same direction as the input.
+            let dot = case.iter().zip(result).map(|(a, b)| a * b).sum::<f32>();
+            assert!(dot > 0., "dim {dim}, row {row}");
+        }
+    }
+}
+
+#[tokio::test]
+async fn zero_rows_pass_through() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = [0., -0., 0., 3., 0., 4., 0., 0., 0.];
+
+    let output = ctx
+        .normalize_rows(&input, 3)
+        .await
+        .expect("Failed to normalize");
+
+    assert_eq!(output, [0., -0., 0., 0.6, 0., 0.8, 0., 0., 0.]);
+}
+
+#[tokio::test]
+async fn dim_must_divide_the_input() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    for dim in [0, 4] {
+        let result = ctx.normalize_rows(&[1.; 6], dim).await;
+        assert!(
+            matches!(
+                result,
+                Err(ComputeError::Misaligned { len: 24, element_size }) if element_size == 4 * dim as u64
+            ),
+            "dim {dim}: {result:?}"
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use demo_wgpu_compute::{
+    inverse_sqrt_with_options, ComputeError, ComputeOptions, Features, GpuContext, InitError,
+    PowerPreference, ZeroPolicy,
+};
+
+#[tokio::test]
+async fn label_shows_up_in_validation_errors() {
+    let ctx = GpuContext::builder()
+        .label("mybatch")
+        .build()
+        .await
+        .expect("Failed to create context");
+    // An empty buffer on the GPU gets a zero-sized binding, which wgpu
+    // rejects.
+    match ctx.compute_gpu(&[]) {
+        Err(ComputeError::Validation { stage, message }) => {
+            assert_eq!(stage, "bind group");
+            assert!(message.contains("mybatch bind group"), "{message}");
+        }
+        Err(other) => panic!("expected a validation error, got {other:?}"),
+        Ok(_) => panic!("expected a validation error"),
+    }
+}
+
+#[tokio::test]
+async fn zero_maps_to_infinity_by_default() {
+    let output = inverse_sqrt_with_options(&[0., 4., -0.], ComputeOptions::new())
+        .await
+        .expect("Failed to calculate inverse sqrt");
+
+    assert_eq!(output[0].to_bits(), f32::INFINITY.to_bits());
+    assert_eq!(output[1], 0.5);
+    assert_eq!(output[2].to_bits(), f32::NEG_INFINITY.to_bits());
+}
+
+#[tokio::test]
+async fn zero_policy_maps_zero_to_nan() {
+    let options = ComputeOptions::new().zero_policy(ZeroPolicy::Nan);
+    let output = inverse_sqrt_with_options(&[0., 4., -0.], options)
+        .await
+        .expect("Failed to calculate inverse sqrt");
+
+    assert!(output[0].is_nan() && output[2].is_nan(), "{output:?}");
+    assert_eq!(output[1], 0.5);
+}
+
+#[tokio::test]
+async fn zero_policy_maps_zero_to_zero() {
+    let options = ComputeOptions::new().zero_policy(ZeroPolicy::Zero);
+    let output = inverse_sqrt_with_options(&[0., 4., 0.], options)
+        .await
+        .expect("Failed to calculate inverse sqrt");
+
+    assert_eq!(output, [0., 0.5, 0.]);
+}
+
+#[tokio::test]
+async fn negatives_and_signed_zeros_follow_the_policy() {
+    let input = [-1., -0., 0., f32::NEG_INFINITY, 4.];
+    let cases = [
+        (ZeroPolicy::Nan, [f32::NAN, f32::NAN, f32::NAN]),
+        (ZeroPolicy::Zero, [f32::NAN, 0., 0.]),
+        (
+            ZeroPolicy::Infinity,
+            [f32::NAN, f32::NEG_INFINITY, f32::INFINITY],
+        ),
+    ];
+    for (policy, expected) in cases {
+        // The translated WGSL as well as the SPIR-V.
+        for disabled in [Features::empty(), Features::SPIRV_SHADER_PASSTHROUGH] {
+            let options = ComputeOptions::new()
+                .zero_policy(policy)
+                .disable_features(disabled);
+            let output = inverse_sqrt_with_options(&input, options)
+                .await
+                .expect("Failed to calculate inverse sqrt");
+
+            let bits = |v: &[f32]| {
+                v.iter()
+                    .map(|x| if x.is_nan() { None } else { Some(x.to_bits()) })
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(bits(&output[..3]), bits(&expected), "{policy:?}");
+            assert!(output[3].is_nan(), "{policy:?}");
+            assert_eq!(output[4], 0.5);
+        }
+    }
+}
+
+#[tokio::test]
+async fn validation_rejects_negative_input() {
+    let ctx = GpuContext::with_options(ComputeOptions::new().validate_input(true))
+        .await
+        .expect("Failed to create context");
+
+    match ctx.compute(&[4., -1., 9.]).await {
+        Err(ComputeError::InvalidInput { index, value }) => {
+            assert_eq!(index, 1);
+            assert_eq!(value, -1.);
+        }
+        other => panic!("expected InvalidInput, got {other:?}"),
+    }
+    assert!(ctx.compute(&[4., 0., 9.]).await.is_ok());
+}
+
+#[tokio::test]
+async fn both_power_preferences_work() {
+    for power_preference in [PowerPreference::LowPower, PowerPreference::HighPerformance] {
+        let options = ComputeOptions::new().power_preference(power_preference);
+        let output = inverse_sqrt_with_options(&[4., 25., 100.], options)
+            .await
+            .expect("Failed to calculate inverse sqrt");
+
+        assert_eq!(output, [0.5, 0.2, 0.1], "{power_preference:?}");
+    }
+}
+
+#[tokio::test]
+async fn fallback_adapter_computes_or_is_missing() {
+    match GpuContext::builder()
+        .force_fallback_adapter(true)
+        .build()
+        .await
+    {
+        Ok(ctx) => {
+            let output = ctx
+                .compute(&[4., 25., 100.])
+                .await
+                .expect("Failed to compute on the fallback adapter");
+            assert_eq!(output, [0.5, 0.2, 0.1]);
+        }
+        // Not every machine has a software adapter installed.
+        Err(ComputeError::Init(InitError::NoAdapter { .. })) => {}
+        Err(other) => panic!("expected a context or NoAdapter, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn debug_and_default_contexts_compute_the_same() {
+    let input = [4., 25., 100.];
+    for debug in [false, true] {
+        let ctx = GpuContext::builder()
+            .debug(debug)
+            .build()
+            .await
+            .expect("Failed to create context");
+        let output = ctx.compute(&input).await.expect("Failed to compute");
+        assert_eq!(output, [0.5, 0.2, 0.1], "debug: {debug}");
+    }
+}
@@ -0,0 +1,35 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use demo_wgpu_compute::{Backend, GpuContext};
+
+#[tokio::test]
+async fn compute_does_not_block_the_executor() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = (1..=1 << 22).map(|x| x as f32).collect::<Vec<_>>();
+
+    // The test runtime has a single thread, so the ticker only advances
+    // while the compute is waiting for the GPU.
+    let ticks = Arc::new(AtomicUsize::new(0));
+    let ticker = tokio::spawn({
+        let ticks = ticks.clone();
+        async move {
+            loop {
+                ticks.fetch_add(1, Ordering::Relaxed);
+                tokio::task::yield_now().await;
+            }
+        }
+    });
+
+    let output = ctx.compute(&input).await.expect("Failed to compute");
+    ticker.abort();
+
+    assert_eq!(output.len(), input.len());
+    // GL issues the work while submitting it, so the readback may well be
+    // ready the first time the future is polled.
+    if ctx.adapter_info().backend != Backend::Gl {
+        assert!(ticks.load(Ordering::Relaxed) > 0);
+    }
+}
@@ -0,0 +1,82 @@
+use demo_wgpu_compute::{GpuContext, Precision};
+
+/// A fixed pseudo-random input of positive normal floats.
+fn random_input() -> Vec<f32> {
+    let mut state = 0x2545_f491u32;
+    (0..10_000)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            // Exponents from 2^-60 to 2^67, any mantissa.
+            f32::from_bits((0x21 << 23) + state % (0x80 << 23))
+        })
+        .collect()
+}
+
+fn max_relative_error(results: &[f32], input: &[f32]) -> f64 {
+    results
+        .iter()
+        .zip(input)
+        .map(|(&result, &case)| {
+            let expected = 1. / (case as f64).sqrt();
+            (result as f64 - expected).abs() / expected
+        })
+        .fold(0., f64::max)
+}
+
+/// Two ulps of an `f32` relative to its value, the most Vulkan and WebGPU
+/// let `inverseSqrt` be off by. Dividing by a correctly rounded `sqrt` stays
+/// within that too.
+const TWO_ULPS: f64 = 2. * f32::EPSILON as f64;
+
+#[tokio::test]
+async fn both_precisions_stay_within_two_ulps() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = random_input();
+
+    let exact = ctx
+        .compute_with_precision(&input, Precision::Exact)
+        .await
+        .expect("Failed to compute");
+    let fast = ctx
+        .compute_with_precision(&input, Precision::Fast)
+        .await
+        .expect("Failed to compute");
+
+    // llvmpipe lowers `inverseSqrt` to the division, and both measure
+    // 8.7e-8 here; hardware estimates come closer to the bound.
+    let (exact_error, fast_error) = (
+        max_relative_error(&exact, &input),
+        max_relative_error(&fast, &input),
+    );
+    assert!(exact_error <= TWO_ULPS, "exact error {exact_error}");
+    assert!(fast_error <= TWO_ULPS, "fast error {fast_error}");
+    assert!(
+        (fast_error - exact_error).abs() <= TWO_ULPS / 2.,
+        "exact error {exact_error}, fast error {fast_error}"
+    );
+}
+
+#[tokio::test]
+async fn fast_precision_keeps_the_special_cases() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = [0., -0., -1., f32::NAN, f32::INFINITY, f32::from_bits(1), 4.];
+
+    let exact = ctx.compute(&input).await.expect("Failed to compute");
+    let fast = GpuContext::builder()
+        .precision(Precision::Fast)
+        .build()
+        .await
+        .expect("Failed to create context")
+        .compute(&input)
+        .await
+        .expect("Failed to compute");
+
+    for (case, (a, b)) in input.iter().zip(exact.iter().zip(&fast)) {
+        assert!(
+            a == b || (a.is_nan() && b.is_nan()) || (a - b).abs() <= TWO_ULPS as f32 * a,
+            "{case}: {a} exact, {b} fast"
+        );
+    }
+}
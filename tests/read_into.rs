@@ -0,0 +1,56 @@
+use demo_wgpu_compute::{ComputeError, GpuContext};
+
+#[tokio::test]
+async fn length_mismatch_is_an_error() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let mut out = [0.; 2];
+
+    let result = ctx.compute_read_into(&[4., 16., 64.], &mut out).await;
+
+    assert!(
+        matches!(
+            result,
+            Err(ComputeError::LengthMismatch {
+                input: 3,
+                output: 2
+            })
+        ),
+        "{result:?}"
+    );
+    assert_eq!(out, [0.; 2]);
+}
+
+#[tokio::test]
+async fn large_slice_matches_compute() {
+    let ctx = GpuContext::builder()
+        .max_chunk_len(100_000)
+        .build()
+        .await
+        .expect("Failed to create context");
+    let input = (1..=1_000_003).map(|x| x as f32).collect::<Vec<_>>();
+    let mut out = vec![0.; input.len()];
+
+    ctx.compute_read_into(&input, &mut out)
+        .await
+        .expect("Failed to compute");
+
+    let expected = ctx.compute(&input).await.expect("Failed to compute");
+    for (index, (result, expected)) in out.iter().zip(&expected).enumerate() {
+        assert_eq!(result.to_bits(), expected.to_bits(), "at {index}");
+    }
+}
+
+#[tokio::test]
+async fn out_is_reused_across_calls() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let mut out = vec![0.; 3];
+
+    ctx.compute_read_into(&[4., 16., 64.], &mut out)
+        .await
+        .expect("Failed to compute");
+    assert_eq!(out, [0.5, 0.25, 0.125]);
+    ctx.compute_read_into(&[25., 100., 1.], &mut out)
+        .await
+        .expect("Failed to compute");
+    assert_eq!(out, [0.2, 0.1, 1.]);
+}
@@ -0,0 +1,71 @@
+use demo_wgpu_compute::{ComputeOptions, GpuContext, Kernel, ZeroPolicy};
+
+#[tokio::test]
+async fn reciprocals_match_the_cpu() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = (1..10_000).map(|x| x as f32).collect::<Vec<_>>();
+
+    let output = ctx
+        .compute_with(Kernel::Reciprocal, &input)
+        .await
+        .expect("Failed to compute");
+
+    for (case, result) in input.into_iter().zip(output) {
+        assert!(
+            ((result - 1. / case) * case).abs() <= 1e-6,
+            "{case}: {result}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn negatives_infinities_and_zeros_have_reciprocals() {
+    let ctx = GpuContext::with_options(ComputeOptions::new().validate_input(true))
+        .await
+        .expect("Failed to create context");
+    let largest_subnormal = f32::from_bits(0x007f_ffff);
+
+    let output = ctx
+        .compute_with(
+            Kernel::Reciprocal,
+            &[
+                -4.,
+                f32::INFINITY,
+                f32::NEG_INFINITY,
+                -largest_subnormal,
+                f32::from_bits(1),
+                0.,
+            ],
+        )
+        .await
+        .expect("Failed to compute");
+
+    assert_eq!(output[0], -0.25);
+    assert_eq!(output[1].to_bits(), 0f32.to_bits());
+    assert_eq!(output[2].to_bits(), (-0f32).to_bits());
+    let expected = (1. / -f64::from(largest_subnormal)) as f32;
+    assert!(((output[3] - expected) / expected).abs() <= 1e-6);
+    assert_eq!(output[4], f32::INFINITY);
+    assert_eq!(output[5].to_bits(), f32::INFINITY.to_bits());
+}
+
+#[tokio::test]
+async fn zeros_follow_the_policy_and_kernels_share_a_context() {
+    let options = ComputeOptions::new().zero_policy(ZeroPolicy::Infinity);
+    let ctx = GpuContext::with_options(options)
+        .await
+        .expect("Failed to create context");
+    let input = [-0., 4., 0.];
+
+    // Interleaved with the inverse sqrt kernel, each through its own
+    // cached pipeline.
+    for _ in 0..2 {
+        let reciprocal = ctx
+            .compute_with(Kernel::Reciprocal, &input)
+            .await
+            .expect("Failed to compute");
+        let inverse_sqrt = ctx.compute(&input).await.expect("Failed to compute");
+        assert_eq!(reciprocal, [f32::NEG_INFINITY, 0.25, f32::INFINITY]);
+        assert_eq!(inverse_sqrt, [f32::NEG_INFINITY, 0.5, f32::INFINITY]);
+    }
+}
@@ -0,0 +1,55 @@
+use std::time::Duration;
+
+use demo_wgpu_compute::{ComputeError, ComputeOptions, GpuContext};
+use futures::TryStreamExt;
+
+async fn context(max_retries: u32) -> GpuContext {
+    let options = ComputeOptions::new()
+        .max_retries(max_retries)
+        .retry_backoff(Duration::ZERO);
+    GpuContext::with_options(options)
+        .await
+        .expect("Failed to create context")
+}
+
+#[tokio::test]
+async fn compute_recovers_from_lost_device() {
+    let ctx = context(2).await;
+
+    ctx.inject_readback_failures(2);
+    let output = ctx
+        .compute(&[4., 25., 100.])
+        .await
+        .expect("Failed to recover");
+
+    assert_eq!(output, [0.5, 0.2, 0.1]);
+}
+
+#[tokio::test]
+async fn compute_gives_up_after_max_retries() {
+    let ctx = context(2).await;
+
+    ctx.inject_readback_failures(3);
+    let result = ctx.compute(&[4., 25., 100.]).await;
+
+    assert!(
+        matches!(result, Err(ComputeError::DeviceLost)),
+        "{result:?}"
+    );
+}
+
+#[tokio::test]
+async fn stream_recovers_mid_run() {
+    let ctx = context(1).await;
+    let input = (1..=4096).map(|x| x as f32).collect::<Vec<_>>();
+    let expected = ctx.compute(&input).await.expect("Failed to compute");
+
+    ctx.inject_readback_failures(1);
+    let chunks = ctx
+        .compute_stream(input.iter().copied(), 1024)
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Failed to recover");
+
+    assert_eq!(chunks.concat(), expected);
+}
@@ -0,0 +1,83 @@
+use demo_wgpu_compute::{GpuContext, Kernel};
+
+/// A fixed pseudo-random input of positive normal floats.
+fn random_input() -> Vec<f32> {
+    let mut state = 0x2545_f491u32;
+    (0..10_000)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            // Exponents from 2^-60 to 2^67, any mantissa.
+            f32::from_bits((0x21 << 23) + state % (0x80 << 23))
+        })
+        .collect()
+}
+
+fn max_relative_error(results: &[f32], input: &[f32]) -> f64 {
+    results
+        .iter()
+        .zip(input)
+        .map(|(&result, &case)| {
+            let expected = 1. / (case as f64).sqrt();
+            (result as f64 - expected).abs() / expected
+        })
+        .fold(0., f64::max)
+}
+
+#[tokio::test]
+async fn error_decreases_with_iterations() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = random_input();
+
+    let mut errors = Vec::new();
+    for iterations in 0..=3 {
+        let output = ctx
+            .compute_rsqrt_refined(&input, iterations)
+            .await
+            .expect("Failed to compute");
+        errors.push(max_relative_error(&output, &input));
+    }
+
+    for pair in errors.windows(2) {
+        assert!(pair[1] < pair[0], "errors by iteration: {errors:?}");
+    }
+    assert!(errors[0] < 0.04, "raw estimate error {}", errors[0]);
+}
+
+#[tokio::test]
+async fn many_iterations_converge_to_the_precise_kernel() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = random_input();
+
+    // Far past the shader's cap, which still has to finish promptly.
+    let refined = ctx
+        .compute_rsqrt_refined(&input, u32::MAX)
+        .await
+        .expect("Failed to compute");
+    let precise = ctx
+        .compute_with(Kernel::InverseSqrt, &input)
+        .await
+        .expect("Failed to compute");
+
+    assert!(max_relative_error(&refined, &input) <= 1e-6);
+    assert!(max_relative_error(&precise, &input) <= 1e-6);
+}
+
+#[tokio::test]
+async fn one_iteration_matches_the_fast_kernel() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = [0., 1., 2., 1e10, -1.];
+
+    let refined = ctx
+        .compute_rsqrt_refined(&input, 1)
+        .await
+        .expect("Failed to compute");
+    let fast = ctx
+        .compute_with(Kernel::FastInverseSqrt, &input)
+        .await
+        .expect("Failed to compute");
+
+    let bits = |v: &[f32]| v.iter().map(|x| x.to_bits()).collect::<Vec<_>>();
+    assert_eq!(bits(&refined), bits(&fast));
+}
@@ -0,0 +1,65 @@
+use demo_wgpu_compute::{Features, GpuContext};
+
+#[tokio::test]
+async fn report_describes_the_run() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = (1..10_000).map(|x| x as f32).collect::<Vec<_>>();
+
+    let (output, report) = ctx
+        .compute_with_report(&input)
+        .await
+        .expect("Failed to compute with report");
+
+    assert_eq!(
+        output,
+        ctx.compute(&input).await.expect("Failed to compute")
+    );
+    assert_eq!(report.elements, input.len());
+    assert_eq!(report.adapter_name, ctx.adapter_info().name);
+    assert_eq!(report.backend, ctx.adapter_info().backend);
+    assert!(report.gpu_ns > 0);
+}
+
+#[tokio::test]
+async fn compute_without_timestamps() {
+    let ctx = GpuContext::builder()
+        .disable_features(Features::TIMESTAMP_QUERY)
+        .build()
+        .await
+        .expect("Failed to create context");
+    let input = (1..1_000).map(|x| x as f32).collect::<Vec<_>>();
+
+    let (output, report) = ctx
+        .compute_with_report(&input)
+        .await
+        .expect("Failed to compute with report");
+
+    assert_eq!(
+        output,
+        input.iter().map(|x| 1. / x.sqrt()).collect::<Vec<_>>()
+    );
+    assert!(!report.gpu_timestamps);
+    assert!(report.gpu_ns > 0);
+}
+
+#[tokio::test]
+async fn report_counts_nans_across_workgroups() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    // Every 7th element zero and every 11th negative, spread over many
+    // workgroups so the count comes from more than one of them.
+    let input = (0..10_000)
+        .map(|x| match x {
+            x if x % 7 == 0 => 0.,
+            x if x % 11 == 0 => -(x as f32),
+            x => x as f32,
+        })
+        .collect::<Vec<_>>();
+    let expected = (0..10_000).filter(|x| x % 7 == 0 || x % 11 == 0).count();
+
+    let (_, report) = ctx
+        .compute_with_report(&input)
+        .await
+        .expect("Failed to compute with report");
+
+    assert_eq!(report.nan_count, expected);
+}
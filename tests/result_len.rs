@@ -0,0 +1,42 @@
+use demo_wgpu_compute::GpuContext;
+
+/// Fixed pseudo-random lengths below 10 000, with the empty and the
+/// one-element input.
+fn random_lengths() -> Vec<usize> {
+    let mut state = 0x9e37_79b9u32;
+    let mut lengths = vec![0, 1];
+    lengths.extend((0..30).map(|_| {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as usize % 10_000
+    }));
+    lengths
+}
+
+async fn assert_lengths_kept(ctx: &GpuContext) {
+    for len in random_lengths() {
+        let input = (0..len).map(|x| x as f32 + 1.).collect::<Vec<_>>();
+
+        let output = ctx.compute(&input).await.expect("Failed to compute");
+
+        assert_eq!(output.len(), len);
+    }
+}
+
+#[tokio::test]
+async fn single_dispatch_returns_one_result_per_element() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    assert_lengths_kept(&ctx).await;
+}
+
+#[tokio::test]
+async fn chunked_run_returns_one_result_per_element() {
+    // Chunks that neither divide most lengths nor fill whole workgroups.
+    let ctx = GpuContext::builder()
+        .max_chunk_len(777)
+        .build()
+        .await
+        .expect("Failed to create context");
+    assert_lengths_kept(&ctx).await;
+}
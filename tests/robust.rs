@@ -0,0 +1,40 @@
+use demo_wgpu_compute::{ComputeOptions, GpuContext, Kernel};
+
+const LENGTHS: [usize; 8] = [1, 63, 64, 65, 1000, 4095, 4096, 4097];
+
+#[tokio::test]
+async fn robust_results_match_the_fast_path() {
+    let fast = GpuContext::new().await.expect("Failed to create context");
+    let robust = GpuContext::with_options(ComputeOptions::new().robust(true))
+        .await
+        .expect("Failed to create robust context");
+
+    for kernel in [
+        Kernel::InverseSqrt,
+        Kernel::InverseSqrtVec4,
+        Kernel::Sqrt,
+        Kernel::Reciprocal,
+    ] {
+        for len in LENGTHS {
+            let input = (0..len).map(|x| x as f32 * 0.25).collect::<Vec<_>>();
+            let from_fast = fast
+                .compute_with(kernel, &input)
+                .await
+                .expect("Failed to compute");
+            let from_robust = robust
+                .compute_with(kernel, &input)
+                .await
+                .expect("Failed to compute robustly");
+
+            assert_eq!(from_robust.len(), len, "{kernel:?}, length {len}");
+            // Where the fast path uses SPIR-V passthrough, the robust one
+            // runs the WGSL, which may round differently in the last place.
+            for (index, (a, b)) in from_fast.iter().zip(from_robust).enumerate() {
+                assert!(
+                    a.to_bits() == b.to_bits() || (a - b).abs() <= 0.000001 * a.abs(),
+                    "{kernel:?}, length {len}, index {index}: {a} fast, {b} robust"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use demo_wgpu_compute::GpuContext;
+
+#[tokio::test]
+async fn compute_scaled_matches_cpu_reference() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = (1..10_000).map(|x| x as f32).collect::<Vec<_>>();
+
+    for scale in [1., 2.5, -3.] {
+        let output = ctx
+            .compute_scaled(&input, scale)
+            .await
+            .expect("Failed to compute scaled inverse sqrt");
+
+        for (case, result) in input.iter().zip(output) {
+            let local_result = scale / case.sqrt();
+            assert!(
+                (local_result - result).abs() <= 0.000001 * scale.abs(),
+                "Failed at {case} case with scale {scale}. Expected result: {local_result} Received instead: {result}"
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn compute_still_unscaled() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = [4., 25., 100.];
+
+    let scaled = ctx
+        .compute_scaled(&input, 1.)
+        .await
+        .expect("Failed to compute scaled");
+    let plain = ctx.compute(&input).await.expect("Failed to compute");
+
+    assert_eq!(plain, [0.5, 0.2, 0.1]);
+    assert_eq!(scaled, plain);
+}
@@ -0,0 +1,27 @@
+use demo_wgpu_compute::GpuContext;
+
+#[tokio::test]
+async fn repeated_calls_stay_valid() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = (1..1_000).map(|x| x as f32).collect::<Vec<_>>();
+    let expected = ctx.compute(&input).await.expect("Failed to compute");
+
+    for i in 0..300 {
+        let pooled = ctx.compute(&input).await.expect("Failed to compute");
+        assert_eq!(pooled, expected, "Pooled results diverged on iteration {i}");
+
+        let released = ctx
+            .compute_gpu(&input)
+            .expect("Failed to upload input")
+            .read_back()
+            .await
+            .expect("Failed to read back results");
+        assert_eq!(released, expected, "Read back diverged on iteration {i}");
+
+        let (reported, _) = ctx
+            .compute_with_report(&input)
+            .await
+            .expect("Failed to compute with report");
+        assert_eq!(reported, expected, "Report diverged on iteration {i}");
+    }
+}
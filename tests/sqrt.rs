@@ -0,0 +1,98 @@
+use demo_wgpu_compute::{ComputeOptions, Features, GpuContext, Kernel, ZeroPolicy, WORKGROUP_SIZE};
+
+#[tokio::test]
+async fn sqrt_10k() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = (1..i16::MAX).map(f32::from).collect::<Vec<_>>();
+
+    let output = ctx
+        .compute_with(Kernel::Sqrt, &input)
+        .await
+        .expect("Failed to calculate sqrt");
+
+    assert_eq!(output.len(), input.len());
+    for (result, case) in output.into_iter().zip(input) {
+        let local_result = case.sqrt();
+        assert!(
+            (local_result - result).abs() <= 0.000001 * local_result,
+            "Failed at {case} case. Expected result: {local_result} Received instead: {result}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn zeros_stay_and_negatives_are_nan() {
+    // Zeros map to themselves whatever the policy says.
+    let ctx = GpuContext::with_options(ComputeOptions::new().zero_policy(ZeroPolicy::Infinity))
+        .await
+        .expect("Failed to create context");
+
+    let output = ctx
+        .compute_with(Kernel::Sqrt, &[0., -0., -1., f32::NEG_INFINITY, 4.])
+        .await
+        .expect("Failed to calculate sqrt");
+
+    assert_eq!(output[0].to_bits(), 0f32.to_bits());
+    assert_eq!(output[1].to_bits(), (-0f32).to_bits());
+    assert!(output[2].is_nan());
+    assert!(output[3].is_nan());
+    assert_eq!(output[4], 2.);
+}
+
+#[tokio::test]
+async fn lengths_around_the_workgroup_size() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    let size = WORKGROUP_SIZE as usize;
+    for len in [1, size - 1, size, size + 1, 1_000_003] {
+        let input = (1..=len).map(|x| x as f32).collect::<Vec<_>>();
+        let output = ctx
+            .compute_with(Kernel::Sqrt, &input)
+            .await
+            .expect("Failed to calculate sqrt");
+
+        assert_eq!(output.len(), len);
+        for (result, case) in output.into_iter().zip(input) {
+            let local_result = case.sqrt();
+            assert!(
+                (local_result - result).abs() <= 0.000001 * local_result,
+                "len {len}, case {case}: {result} != {local_result}"
+            );
+        }
+    }
+}
+
+#[tokio::test]
+async fn infinity_and_subnormals_match_a_double_precision_reference() {
+    let input = [
+        f32::MIN_POSITIVE,
+        f32::MIN_POSITIVE / 2.,
+        f32::from_bits(1),
+        f32::from_bits(2),
+        f32::from_bits(0x7f_ffff),
+        1e-40,
+        f32::MAX,
+    ];
+    for disabled in [Features::empty(), Features::SPIRV_SHADER_PASSTHROUGH] {
+        let ctx = GpuContext::builder()
+            .disable_features(disabled)
+            .build()
+            .await
+            .expect("Failed to create context");
+        let output = ctx
+            .compute_with(Kernel::Sqrt, &input)
+            .await
+            .expect("Failed to compute");
+        let infinity = ctx
+            .compute_with(Kernel::Sqrt, &[f32::INFINITY])
+            .await
+            .expect("Failed to compute");
+
+        assert_eq!(infinity, [f32::INFINITY]);
+        for (&case, &result) in input.iter().zip(&output) {
+            let expected = (case as f64).sqrt();
+            let error = (result as f64 - expected).abs() / expected;
+            assert!(error <= 1e-6, "{case:e}: {result:e} != {expected:e}");
+        }
+    }
+}
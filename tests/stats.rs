@@ -0,0 +1,70 @@
+use demo_wgpu_compute::{GpuContext, Stats, ZeroPolicy};
+
+/// Deterministic pseudo-random floats in `[0, 100)`, every seventh one
+/// zero.
+fn random_input(len: usize) -> Vec<f32> {
+    let mut state = 0x2545_f491u32;
+    (0..len)
+        .map(|i| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            if i % 7 == 0 {
+                0.
+            } else {
+                (state >> 8) as f32 / (1 << 24) as f32 * 100.
+            }
+        })
+        .collect()
+}
+
+fn cpu_stats(results: &[f32]) -> Stats {
+    let counted = results.iter().filter(|x| !x.is_nan());
+    let sum = counted.clone().map(|&x| f64::from(x)).sum::<f64>();
+    let count = counted.clone().count();
+    Stats {
+        min: counted.clone().copied().fold(f32::INFINITY, f32::min),
+        max: counted.copied().fold(f32::NEG_INFINITY, f32::max),
+        mean: (sum / count as f64) as f32,
+        nan_count: results.len() - count,
+    }
+}
+
+#[tokio::test]
+async fn stats_match_the_cpu_on_random_input() {
+    // Zeros left on the GPU count as NaN, as under the NaN zero policy.
+    let ctx = GpuContext::builder()
+        .zero_policy(ZeroPolicy::Nan)
+        .build()
+        .await
+        .expect("Failed to create context");
+    // Not a multiple of the 256 elements each workgroup reduces.
+    let input = random_input(100_003);
+
+    let stats = ctx.compute_stats(&input).await.expect("Failed to compute");
+
+    let results = ctx.compute(&input).await.expect("Failed to compute");
+    let expected = cpu_stats(&results);
+    assert_eq!(stats.nan_count, expected.nan_count);
+    assert_eq!(stats.nan_count, (input.len() + 6) / 7);
+    assert_eq!(stats.min, expected.min);
+    assert_eq!(stats.max, expected.max);
+    assert!(((stats.mean - expected.mean) / expected.mean).abs() <= 1e-5);
+}
+
+#[tokio::test]
+async fn stats_of_nothing_but_nans_are_nan() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    let stats = ctx
+        .compute_stats(&[0., -1., f32::NAN])
+        .await
+        .expect("Failed to compute");
+
+    assert_eq!(stats.nan_count, 3);
+    assert!(stats.min.is_nan() && stats.max.is_nan() && stats.mean.is_nan());
+
+    let empty = ctx.compute_stats(&[]).await.expect("Failed to compute");
+    assert_eq!(empty.nan_count, 0);
+    assert!(empty.mean.is_nan());
+}
@@ -0,0 +1,117 @@
+use std::sync::{Arc, Mutex};
+
+use demo_wgpu_compute::{ComputeError, ComputeHandle, ComputeOptions, GpuContext, ProgressInfo};
+use futures::{StreamExt, TryStreamExt};
+
+#[tokio::test]
+async fn stream_matches_monolithic_compute() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    // 10_000 is not a multiple of the chunk size, so the last chunk is partial.
+    let input = (1..=10_000).map(|x| x as f32).collect::<Vec<_>>();
+
+    let chunks = ctx
+        .compute_stream(input.iter().copied(), 1024)
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Failed to stream inverse sqrt");
+    let monolithic = ctx.compute(&input).await.expect("Failed to compute");
+
+    assert_eq!(chunks.len(), 10);
+    assert!(chunks[..9].iter().all(|chunk| chunk.len() == 1024));
+    assert_eq!(chunks[9].len(), 10_000 - 9 * 1024);
+    assert_eq!(chunks.concat(), monolithic);
+}
+
+#[tokio::test]
+async fn empty_iterator_yields_nothing() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    let chunks = ctx
+        .compute_stream(std::iter::empty(), 64)
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Failed to stream inverse sqrt");
+
+    assert!(chunks.is_empty());
+}
+
+async fn progress_of(len: usize, chunk_size: usize) -> Vec<ProgressInfo> {
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let hook = progress.clone();
+    let options = ComputeOptions::new().on_progress(move |info| hook.lock().unwrap().push(info));
+    let ctx = GpuContext::with_options(options)
+        .await
+        .expect("Failed to create context");
+    let input = (1..=len).map(|x| x as f32).collect::<Vec<_>>();
+
+    ctx.compute_stream(input.iter().copied(), chunk_size)
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Failed to stream inverse sqrt");
+
+    let progress = progress.lock().unwrap().clone();
+    progress
+}
+
+#[tokio::test]
+async fn progress_reported_after_every_chunk() {
+    let progress = progress_of(10_000, 1024).await;
+
+    assert_eq!(progress.len(), 10);
+    for pair in progress.windows(2) {
+        assert!(pair[0].completed < pair[1].completed);
+        assert!(pair[0].elapsed <= pair[1].elapsed);
+    }
+    assert!(progress.iter().all(|info| info.total == Some(10_000)));
+    assert_eq!(progress[9].completed, 10_000);
+}
+
+#[tokio::test]
+async fn progress_reported_for_single_chunk() {
+    let progress = progress_of(100, 1024).await;
+
+    assert_eq!(progress.len(), 1);
+    assert_eq!(progress[0].completed, 100);
+}
+
+#[tokio::test]
+async fn cancel_stops_before_next_chunk() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = (1..=10_000).map(|x| x as f32).collect::<Vec<_>>();
+    let handle = ComputeHandle::new();
+
+    let mut stream =
+        Box::pin(ctx.compute_stream_with_handle(input.iter().copied(), 1024, handle.clone()));
+    for _ in 0..3 {
+        let chunk = stream.next().await.expect("Stream ended early");
+        assert_eq!(chunk.expect("Failed to stream inverse sqrt").len(), 1024);
+    }
+    handle.cancel();
+
+    match stream.next().await {
+        Some(Err(ComputeError::Cancelled { completed })) => assert_eq!(completed, 3 * 1024),
+        other => panic!("Expected cancellation, got {other:?}"),
+    }
+    assert!(stream.next().await.is_none());
+}
+
+#[tokio::test]
+async fn tiny_ring_serves_many_chunks() {
+    let ctx = GpuContext::builder()
+        .staging_buffers(2)
+        .build()
+        .await
+        .expect("Failed to create context");
+    let input = (1..=100 * 256).map(|x| x as f32).collect::<Vec<_>>();
+
+    let chunks = ctx
+        .compute_stream(input.iter().copied(), 256)
+        .try_collect::<Vec<_>>()
+        .await
+        .expect("Failed to stream inverse sqrt");
+    let monolithic = ctx.compute(&input).await.expect("Failed to compute");
+
+    assert_eq!(chunks.len(), 100);
+    assert_eq!(chunks.concat(), monolithic);
+    assert_eq!(ctx.staging_allocations(), 2);
+}
@@ -0,0 +1,87 @@
+use demo_wgpu_compute::{ComputeError, GpuContext};
+
+/// `[x, y, z, w]` records with negative `x`s, which only pass validation
+/// while they are left alone, and a final record cut short.
+fn records() -> Vec<f32> {
+    (0..4003)
+        .map(|i| match i % 4 {
+            0 => -(i as f32),
+            lane => (i * lane) as f32 + 1.,
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn stride_4_touches_only_the_selected_lane() {
+    let ctx = GpuContext::builder()
+        .validate_input(true)
+        .build()
+        .await
+        .expect("Failed to create context");
+    let input = records();
+
+    for offset in 0..4 {
+        let result = ctx.compute_strided(&input, offset, 4).await;
+        if offset == 0 {
+            assert!(
+                matches!(result, Err(ComputeError::InvalidInput { index: 4, .. })),
+                "{result:?}"
+            );
+            continue;
+        }
+        let output = result.expect("Failed to compute");
+
+        assert_eq!(output.len(), input.len());
+        for (index, (case, result)) in input.iter().zip(&output).enumerate() {
+            if index % 4 == offset {
+                let expected = 1. / case.sqrt();
+                assert!(
+                    (expected - result).abs() <= 0.000001 * expected,
+                    "offset {offset}, index {index}: expected {expected}, got {result}"
+                );
+            } else {
+                assert_eq!(
+                    case.to_bits(),
+                    result.to_bits(),
+                    "offset {offset}, index {index}"
+                );
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn chunks_keep_the_lane() {
+    let single = GpuContext::new().await.expect("Failed to create context");
+    // Not a multiple of the stride, so chunks have to be shortened.
+    let chunked = GpuContext::builder()
+        .max_chunk_len(1001)
+        .build()
+        .await
+        .expect("Failed to create context");
+    let input = records();
+
+    let expected = single
+        .compute_strided(&input, 3, 4)
+        .await
+        .expect("Failed to compute");
+    let output = chunked
+        .compute_strided(&input, 3, 4)
+        .await
+        .expect("Failed to compute");
+
+    assert_eq!(output, expected);
+}
+
+#[tokio::test]
+async fn offset_outside_the_stride_is_rejected() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    for (offset, stride) in [(0, 0), (4, 4)] {
+        let result = ctx.compute_strided(&[1.; 8], offset, stride).await;
+        assert!(
+            matches!(result, Err(ComputeError::InvalidLayout { .. })),
+            "{result:?}"
+        );
+    }
+}
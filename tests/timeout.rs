@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+use demo_wgpu_compute::{ComputeError, GpuContext};
+
+#[tokio::test]
+async fn slow_readback_times_out_and_context_recovers() {
+    let ctx = GpuContext::builder()
+        .timeout(Duration::from_millis(200))
+        .build()
+        .await
+        .expect("Failed to create context");
+
+    ctx.inject_readback_delay(Duration::from_secs(5));
+    match ctx.compute(&[4.]).await {
+        Err(ComputeError::Timeout { stage }) => assert_eq!(stage, "buffer mapping"),
+        other => panic!("expected a timeout, got {other:?}"),
+    }
+
+    let output = ctx
+        .compute(&[4., 16.])
+        .await
+        .expect("Failed to compute after the timeout");
+    assert_eq!(output, [0.5, 0.25]);
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "trace")]
+
+use std::path::PathBuf;
+
+use demo_wgpu_compute::{ComputeError, GpuContext, InitError};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("demo_wgpu_compute-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[tokio::test]
+async fn compute_writes_a_trace() {
+    let dir = temp_dir("trace").join("nested");
+    let ctx = GpuContext::builder()
+        .trace_dir(&dir)
+        .build()
+        .await
+        .expect("Failed to create context");
+    let output = ctx
+        .compute(&[4., 25., 100.])
+        .await
+        .expect("Failed to compute");
+    assert_eq!(output, [0.5, 0.2, 0.1]);
+    drop(ctx);
+
+    let files = std::fs::read_dir(&dir)
+        .expect("Trace directory missing")
+        .map(|entry| entry.unwrap().file_name())
+        .collect::<Vec<_>>();
+    assert!(files.iter().any(|name| name == "trace.ron"), "{files:?}");
+}
+
+#[tokio::test]
+async fn unwritable_trace_dir_is_an_error() {
+    let file = temp_dir("not-a-dir");
+    std::fs::write(&file, []).expect("Failed to create file");
+
+    let result = GpuContext::builder()
+        .trace_dir(file.join("trace"))
+        .build()
+        .await;
+    match result {
+        Err(ComputeError::Init(InitError::TraceDir { path, .. })) => {
+            assert_eq!(path, file.join("trace"))
+        }
+        Err(err) => panic!("Expected TraceDir, got {err}"),
+        Ok(_) => panic!("Expected TraceDir"),
+    }
+}
@@ -0,0 +1,55 @@
+use demo_wgpu_compute::{GpuContext, ZeroPolicy};
+
+/// What the kernel computes, on the CPU.
+fn expected(byte: u8) -> f32 {
+    1. / (byte as f32 / 255.).sqrt()
+}
+
+#[tokio::test]
+async fn every_byte_matches_the_cpu() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    // Every byte twice, and three more so the last word is partial.
+    let input = (0..=255u8)
+        .chain(0..=255)
+        .chain([7, 0, 255])
+        .collect::<Vec<_>>();
+
+    let output = ctx.compute_unorm8(&input).await.expect("Failed to compute");
+
+    assert_eq!(output.len(), input.len());
+    for (&byte, result) in input.iter().zip(output) {
+        let expected = expected(byte);
+        assert!(
+            result == expected || (expected - result).abs() <= 0.000001 * expected,
+            "{byte}: expected {expected}, got {result}"
+        );
+    }
+}
+
+#[tokio::test]
+async fn tail_bytes_are_not_padded_into_the_output() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    for len in 1..=9 {
+        let input = vec![255; len];
+        let output = ctx.compute_unorm8(&input).await.expect("Failed to compute");
+        assert_eq!(output, vec![1.; len]);
+    }
+    assert!(ctx.compute_unorm8(&[]).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn zero_bytes_follow_the_zero_policy() {
+    let ctx = GpuContext::builder()
+        .zero_policy(ZeroPolicy::Zero)
+        .build()
+        .await
+        .expect("Failed to create context");
+
+    let output = ctx
+        .compute_unorm8(&[0, 255, 0])
+        .await
+        .expect("Failed to compute");
+
+    assert_eq!(output, [0., 1., 0.]);
+}
@@ -0,0 +1,51 @@
+use demo_wgpu_compute::{GpuContext, Kernel, WORKGROUP_SIZE};
+
+#[tokio::test]
+async fn vec4_matches_scalar_on_awkward_lengths() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    for len in [1, 3, 4, 5, 255, 256, 257, 4097, 1_000_003] {
+        // Special values in the mix send some vectors down the lane path.
+        let input = (0..len)
+            .map(|x| match x % 97 {
+                0 => 0.,
+                1 => -1.,
+                2 => f32::INFINITY,
+                3 => f32::from_bits(5),
+                _ => x as f32 * 0.5,
+            })
+            .collect::<Vec<_>>();
+        let scalar = ctx
+            .compute_with(Kernel::InverseSqrt, &input)
+            .await
+            .expect("Failed to compute");
+        let vec4 = ctx
+            .compute_with(Kernel::InverseSqrtVec4, &input)
+            .await
+            .expect("Failed to compute");
+
+        assert_eq!(vec4.len(), len);
+        for (index, (a, b)) in scalar.iter().zip(&vec4).enumerate() {
+            assert_eq!(a.to_bits(), b.to_bits(), "len {len}, index {index}");
+        }
+    }
+}
+
+#[tokio::test]
+async fn vec4_covers_past_the_scalar_dispatch_limit() {
+    let ctx = GpuContext::builder()
+        .split_large_inputs(false)
+        .build()
+        .await
+        .expect("Failed to create context");
+    // More elements than 65535 full workgroups reach, but within their
+    // reach at four elements each.
+    let input = vec![4f32; 65_535 * WORKGROUP_SIZE as usize + 1];
+
+    let output = ctx
+        .compute_with(Kernel::InverseSqrtVec4, &input)
+        .await
+        .expect("Failed to compute");
+
+    assert!(output.iter().all(|&x| x == 0.5));
+}
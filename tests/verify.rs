@@ -0,0 +1,88 @@
+use demo_wgpu_compute::{ComputeError, GpuContext};
+
+fn input() -> Vec<f32> {
+    (1..20_000).map(|x| x as f32 * 0.25).collect()
+}
+
+fn cpu_reference(input: &[f32]) -> Vec<f32> {
+    input.iter().map(|x| 1. / x.sqrt()).collect()
+}
+
+#[tokio::test]
+async fn results_match_cpu_reference() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = input();
+
+    let report = ctx
+        .compute_gpu(&input)
+        .expect("Failed to upload input")
+        .verify_against(&cpu_reference(&input))
+        .await
+        .expect("Failed to verify");
+
+    assert!(report.max_relative_error <= 0.000001, "{report:?}");
+}
+
+#[tokio::test]
+async fn injected_mismatch_is_pinpointed() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let input = input();
+    let mut reference = cpu_reference(&input);
+    reference[12_345] *= 2.;
+    // Off by as much, but later, so the first is reported.
+    reference[15_000] *= 2.;
+
+    let report = ctx
+        .compute_gpu(&input)
+        .expect("Failed to upload input")
+        .verify_against(&reference)
+        .await
+        .expect("Failed to verify");
+
+    assert_eq!(report.index, Some(12_345), "{report:?}");
+    assert!(
+        (report.max_relative_error - 0.5).abs() <= 0.000001,
+        "{report:?}"
+    );
+}
+
+#[tokio::test]
+async fn nan_matches_nan_but_nothing_else() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    // Zero comes out as NaN.
+    let input = [4., 0., 16., 0.];
+    let results = ctx.compute_gpu(&input).expect("Failed to upload input");
+
+    let matching = results
+        .verify_against(&[0.5, f32::NAN, 0.25, f32::NAN])
+        .await
+        .expect("Failed to verify");
+    let mismatched = results
+        .verify_against(&[0.5, f32::NAN, 0.25, 1.])
+        .await
+        .expect("Failed to verify");
+
+    assert_eq!(matching.max_relative_error, 0.);
+    assert_eq!(matching.index, None);
+    assert_eq!(mismatched.max_relative_error, f32::INFINITY);
+    assert_eq!(mismatched.index, Some(3));
+}
+
+#[tokio::test]
+async fn reference_of_another_length_is_rejected() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let results = ctx.compute_gpu(&[4., 16.]).expect("Failed to upload input");
+
+    let result = results.verify_against(&[0.5]).await;
+
+    assert!(
+        matches!(
+            result,
+            Err(ComputeError::LengthMismatch {
+                input: 2,
+                output: 1
+            })
+        ),
+        "{result:?}"
+    );
+}
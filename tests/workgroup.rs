@@ -0,0 +1,29 @@
+use demo_wgpu_compute::{GpuContext, Kernel, WORKGROUP_SIZE};
+
+#[tokio::test]
+async fn shaders_agree_on_the_workgroup_size() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+
+    ctx.check_workgroup_size()
+        .await
+        .expect("Shaders disagree on the workgroup size");
+}
+
+#[tokio::test]
+async fn kernels_cover_lengths_around_multiples_of_the_workgroup_size() {
+    let ctx = GpuContext::new().await.expect("Failed to create context");
+    let size = WORKGROUP_SIZE as usize;
+
+    for len in [2 * size - 1, 2 * size, 2 * size + 1, 4 * size + 1] {
+        let input = vec![4f32; len];
+        for kernel in [Kernel::InverseSqrt, Kernel::InverseSqrtVec4] {
+            let output = ctx
+                .compute_with(kernel, &input)
+                .await
+                .expect("Failed to compute");
+
+            assert_eq!(output.len(), len, "{kernel:?}");
+            assert!(output.iter().all(|&x| x == 0.5), "{kernel:?}, len {len}");
+        }
+    }
+}